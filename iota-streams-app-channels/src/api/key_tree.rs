@@ -0,0 +1,363 @@
+//! Logical key hierarchy for keyloads shared with large groups of recipients.
+//!
+//! Members are the leaves of a binary tree and every node holds a key known to the members below
+//! it. The root key is thus known to all members and a keyload only needs to encapsulate the
+//! session key once, under the root key. When a member is added or removed the keys on the path
+//! from its leaf to the root are replaced, each new key being encapsulated under the keys of the
+//! children of its node, which costs O(log n) encapsulations. The new keys are published as
+//! [`KeyUpdate`]s with the next keyload.
+//!
+//! Leaves of members identified by a pre-shared key hold the pre-shared key itself. Leaves of
+//! members identified by an Ed25519 public key hold a random key encapsulated with X25519.
+
+use iota_streams_app::{
+    identifier::Identifier,
+    message::{
+        ContentSizeof,
+        ContentUnwrap,
+        ContentUnwrapNew,
+        ContentWrap,
+    },
+};
+use iota_streams_core::{
+    async_trait,
+    err,
+    prelude::{
+        hex,
+        Box,
+        Vec,
+    },
+    prng,
+    psk::{
+        self,
+        Psk,
+    },
+    sponge::prp::PRP,
+    try_or,
+    Errors::{
+        BadIdentifier,
+        BadOneof,
+        TreeMemberAlreadyExists,
+        TreeMemberNotFound,
+    },
+    Result,
+};
+use iota_streams_ddml::{
+    command::*,
+    io,
+    types::*,
+};
+
+use crate::message::keyload::{
+    KeyUpdate,
+    NodeKey,
+};
+
+/// Position of the node at `offset` in `level` of a key tree, leaves being at level 0.
+pub fn node_position(level: usize, offset: usize) -> u64 {
+    ((level as u64) << 32) | offset as u64
+}
+
+/// Key tree maintained by the Author.
+///
+/// The tree doubles its capacity when full, nodes keep their positions as it grows.
+#[derive(Clone, Default)]
+pub struct KeyTree {
+    /// Node keys by level, leaves first. Nodes without members below them have no key.
+    pub(crate) levels: Vec<Vec<Option<NodeKey>>>,
+    /// Members by leaf.
+    pub(crate) members: Vec<Option<Identifier>>,
+    /// Key updates not published yet, children come before their parents.
+    pub(crate) pending: Vec<KeyUpdate>,
+}
+
+impl KeyTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Members of the tree.
+    pub fn members(&self) -> impl Iterator<Item = &Identifier> {
+        self.members.iter().flatten()
+    }
+
+    pub fn contains(&self, member: &Identifier) -> bool {
+        self.members().any(|m| m == member)
+    }
+
+    /// Key of the root of the tree, known to all members.
+    pub fn group_key(&self) -> Option<&NodeKey> {
+        self.levels.last().and_then(|level| level[0].as_ref())
+    }
+
+    /// Key updates to be published with the next keyload.
+    pub fn pending_updates(&self) -> &[KeyUpdate] {
+        &self.pending
+    }
+
+    /// Forget the key updates once published.
+    pub fn clear_pending_updates(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Add a member to the tree.
+    ///
+    ///   # Arguments
+    ///   * `member` - Identifier of the member
+    ///   * `store_id` - Pre-shared key or X25519 public key of the member
+    pub fn add<F: PRP>(&mut self, member: Identifier, store_id: Vec<u8>) -> Result<()> {
+        try_or!(
+            !self.contains(&member),
+            TreeMemberAlreadyExists(hex::encode(member.to_bytes()))
+        )?;
+        let offset = match self.members.iter().position(Option::is_none) {
+            Some(offset) => offset,
+            None => self.grow(),
+        };
+        let position = node_position(0, offset);
+        let (leaf, delivery) = match member {
+            Identifier::PskId(_) => {
+                try_or!(store_id.len() == psk::PSK_SIZE, BadIdentifier)?;
+                let leaf = NodeKey {
+                    position,
+                    id: member,
+                    key: Psk::clone_from_slice(&store_id),
+                };
+                (leaf, None)
+            }
+            Identifier::EdPubKey(_) => {
+                let leaf = NodeKey::new::<F>(position, prng::random_key());
+                let delivery = KeyUpdate {
+                    recipient: member,
+                    recipient_key: store_id,
+                    node: leaf.clone(),
+                };
+                (leaf, Some(delivery))
+            }
+        };
+        self.members[offset] = Some(member);
+        self.replace(0, offset, Some(leaf));
+        self.pending.extend(delivery);
+        self.refresh_path::<F>(offset);
+        Ok(())
+    }
+
+    /// Remove a member from the tree, it cannot derive the keys replaced on its path.
+    pub fn remove<F: PRP>(&mut self, member: &Identifier) -> Result<()> {
+        match self.members.iter().position(|m| m.as_ref() == Some(member)) {
+            Some(offset) => {
+                self.members[offset] = None;
+                self.replace(0, offset, None);
+                self.refresh_path::<F>(offset);
+                Ok(())
+            }
+            None => err!(TreeMemberNotFound(hex::encode(member.to_bytes()))),
+        }
+    }
+
+    /// Key updates delivering again the keys on the path from the leaf of a member to the root, for a
+    /// member that missed keyloads of the tree. The leaf key of a member identified by a public key
+    /// is encapsulated under `store_id`, the keys of the ancestors under the leaf key.
+    ///
+    ///   # Arguments
+    ///   * `member` - Identifier of the member
+    ///   * `store_id` - Pre-shared key or X25519 public key of the member
+    pub fn resync_updates(&self, member: &Identifier, store_id: Vec<u8>) -> Result<Vec<KeyUpdate>> {
+        let path = self
+            .members
+            .iter()
+            .position(|m| m.as_ref() == Some(member))
+            .and_then(|offset| self.levels[0][offset].as_ref().map(|leaf| (offset, leaf)));
+        let (offset, leaf) = match path {
+            Some(path) => path,
+            None => return err!(TreeMemberNotFound(hex::encode(member.to_bytes()))),
+        };
+        let mut updates = Vec::new();
+        if let Identifier::EdPubKey(_) = member {
+            updates.push(KeyUpdate {
+                recipient: *member,
+                recipient_key: store_id,
+                node: leaf.clone(),
+            });
+        }
+        for (level, nodes) in self.levels.iter().enumerate().skip(1) {
+            if let Some(node) = &nodes[offset >> level] {
+                updates.push(KeyUpdate {
+                    recipient: leaf.id,
+                    recipient_key: leaf.key.to_vec(),
+                    node: node.clone(),
+                });
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Double the number of leaves and return the offset of the first new leaf.
+    fn grow(&mut self) -> usize {
+        let offset = self.members.len();
+        if self.levels.is_empty() {
+            self.levels.push(vec![None]);
+            self.members.push(None);
+        } else {
+            self.members.resize(2 * offset, None);
+            for level in self.levels.iter_mut() {
+                level.resize(2 * level.len(), None);
+            }
+            self.levels.push(vec![None]);
+        }
+        offset
+    }
+
+    /// Replace the key of a node. Pending updates of the node are dropped, those encapsulated under
+    /// its former key are updates of its parent which is replaced as well.
+    fn replace(&mut self, level: usize, offset: usize, node: Option<NodeKey>) {
+        let position = node_position(level, offset);
+        self.pending.retain(|update| update.node.position != position);
+        self.levels[level][offset] = node;
+    }
+
+    /// Replace the keys of the ancestors of a leaf, from the bottom up.
+    fn refresh_path<F: PRP>(&mut self, offset: usize) {
+        for level in 1..self.levels.len() {
+            let idx = offset >> level;
+            let children: Vec<NodeKey> = self.levels[level - 1][2 * idx..2 * idx + 2]
+                .iter()
+                .flatten()
+                .cloned()
+                .collect();
+            if children.is_empty() {
+                self.replace(level, idx, None);
+            } else {
+                let node = NodeKey::new::<F>(node_position(level, idx), prng::random_key());
+                self.replace(level, idx, Some(node.clone()));
+                for child in children {
+                    self.pending.push(KeyUpdate {
+                        recipient: child.id,
+                        recipient_key: child.key.to_vec(),
+                        node: node.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP> ContentSizeof<F> for KeyTree {
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        ctx.absorb(Size(self.levels.len()))?;
+        for level in self.levels.iter() {
+            ctx.absorb(Size(level.len()))?;
+            for node in level {
+                ctx.absorb(Uint8(node.is_some() as u8))?;
+                if let Some(node) = node {
+                    node.sizeof(ctx).await?;
+                }
+            }
+        }
+
+        ctx.absorb(Size(self.members.len()))?;
+        for member in self.members.iter() {
+            ctx.absorb(Uint8(member.is_some() as u8))?;
+            if let Some(member) = member {
+                member.sizeof(ctx).await?;
+            }
+        }
+
+        ctx.absorb(Size(self.pending.len()))?;
+        for update in self.pending.iter() {
+            update.sizeof(ctx).await?;
+        }
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> ContentWrap<F, Store> for KeyTree {
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.absorb(Size(self.levels.len()))?;
+        for level in self.levels.iter() {
+            ctx.absorb(Size(level.len()))?;
+            for node in level {
+                ctx.absorb(Uint8(node.is_some() as u8))?;
+                if let Some(node) = node {
+                    node.wrap(store, ctx).await?;
+                }
+            }
+        }
+
+        ctx.absorb(Size(self.members.len()))?;
+        for member in self.members.iter() {
+            ctx.absorb(Uint8(member.is_some() as u8))?;
+            if let Some(member) = member {
+                member.wrap(store, ctx).await?;
+            }
+        }
+
+        ctx.absorb(Size(self.pending.len()))?;
+        for update in self.pending.iter() {
+            update.wrap(store, ctx).await?;
+        }
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> ContentUnwrap<F, Store> for KeyTree {
+    async fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut repeated_levels = Size(0);
+        let mut levels = Vec::new();
+        ctx.absorb(&mut repeated_levels)?;
+        for _ in 0..repeated_levels.0 {
+            let mut repeated_nodes = Size(0);
+            ctx.absorb(&mut repeated_nodes)?;
+            let mut level = Vec::new();
+            for _ in 0..repeated_nodes.0 {
+                let mut oneof_node = Uint8(0);
+                ctx.absorb(&mut oneof_node)?.guard(oneof_node.0 < 2, BadOneof)?;
+                if oneof_node.0 == 1 {
+                    let (node, _) = NodeKey::unwrap_new(store, ctx).await?;
+                    level.push(Some(node));
+                } else {
+                    level.push(None);
+                }
+            }
+            levels.push(level);
+        }
+
+        let mut repeated_members = Size(0);
+        let mut members = Vec::new();
+        ctx.absorb(&mut repeated_members)?;
+        for _ in 0..repeated_members.0 {
+            let mut oneof_member = Uint8(0);
+            ctx.absorb(&mut oneof_member)?.guard(oneof_member.0 < 2, BadOneof)?;
+            if oneof_member.0 == 1 {
+                let (member, _) = Identifier::unwrap_new(store, ctx).await?;
+                members.push(Some(member));
+            } else {
+                members.push(None);
+            }
+        }
+
+        let mut repeated_pending = Size(0);
+        let mut pending = Vec::new();
+        ctx.absorb(&mut repeated_pending)?;
+        for _ in 0..repeated_pending.0 {
+            let (update, _) = KeyUpdate::unwrap_new(store, ctx).await?;
+            pending.push(update);
+        }
+
+        self.levels = levels;
+        self.members = members;
+        self.pending = pending;
+        Ok(ctx)
+    }
+}
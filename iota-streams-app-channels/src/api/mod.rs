@@ -1,6 +1,9 @@
 /// Identifier Key storage. Used for keeping track of channel state
 pub mod key_store;
 
+/// Key tree for keyloads shared with large groups
+pub mod key_tree;
+
 /// We would need an array import in prelude, and using IntoIter with size specifying...
 /// type_complexity to be determined in future issue
 
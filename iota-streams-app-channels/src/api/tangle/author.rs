@@ -12,7 +12,6 @@ use crate::api::tangle::{
 
 use iota_streams_app::identifier::Identifier;
 use iota_streams_core::{
-    panic_if_not,
     prelude::{
         String,
         Vec,
@@ -24,6 +23,9 @@ use iota_streams_core::{
 };
 use iota_streams_core_edsig::signature::ed25519;
 
+#[cfg(feature = "std")]
+use futures::Stream;
+
 /// Author Object. Contains User API.
 pub struct Author<Trans> {
     user: User<Trans>,
@@ -45,11 +47,71 @@ impl<Trans> Author<Trans> {
         Self { user }
     }
 
+    /// Create a new Author instance for the channel with index `channel_idx` of the seed. Channels
+    /// of the same seed share the key pair, their application instances differ.
+    ///
+    /// # Arguments
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `channel_type` - Implementation type: [0: Single Branch, 1: Multi Branch , 2: Single Depth]
+    /// * `channel_idx` - Index of the channel
+    /// * `transport` - Transport object used for sending and receiving
+    pub fn with_channel_index(
+        seed: &str,
+        channel_type: ChannelType,
+        channel_idx: u64,
+        transport: Trans,
+    ) -> Result<Self> {
+        let mut user = User::new(seed, channel_type, transport);
+        user.user.create_channel(channel_idx)?;
+        Ok(Self { user })
+    }
+
     /// Returns a clone of the transport object
     pub fn get_transport(&self) -> &Trans {
         self.user.get_transport()
     }
 
+    /// Pad sent messages to a uniform length, see [`User::set_uniform_payload_length`].
+    ///
+    /// # Arguments
+    /// * `length` - Size in bytes messages are padded to, zero disables padding
+    /// * `pad_keyloads` - Whether keyloads are padded as well as signed and tagged packets
+    pub fn set_uniform_payload_length(&mut self, length: usize, pad_keyloads: bool) {
+        self.user.set_uniform_payload_length(length, pad_keyloads)
+    }
+
+    /// Include the time of publishing in sent signed and tagged packets. Recipients get it as
+    /// `sender_timestamp` of the unwrapped message.
+    pub fn set_sender_timestamps(&mut self, enabled: bool) {
+        self.user.set_sender_timestamps(enabled)
+    }
+
+    /// Erase the state of a message once a signed or tagged packet linked to it is sent or
+    /// received, see [`User::set_ratchet`].
+    pub fn set_ratchet(&mut self, enabled: bool) {
+        self.user.set_ratchet(enabled)
+    }
+
+    /// Select the codec encoding payloads of sent packets.
+    ///
+    /// # Arguments
+    /// * `encoding` - Name of a registered codec [built-in: utf-8, raw, deflate, zstd, cbor, utf-8-strict]
+    pub fn set_message_encoding(&mut self, encoding: &str) -> Result<()> {
+        self.user.set_message_encoding(encoding)
+    }
+
+    /// Publish a fresh keyload for the remaining subscribers whenever a subscriber is revoked with
+    /// `revoke_subscriber` or unsubscribes, so that they cannot read messages attached to it.
+    pub fn set_rekey_on_removal(&mut self, enabled: bool) {
+        self.user.set_rekey_on_removal(enabled)
+    }
+
+    /// Hold received subscriptions for approval. Pending subscribers are not registered, and thus
+    /// not included in `send_keyload_for_everyone`, until accepted with `accept_subscription`.
+    pub fn set_subscription_approval(&mut self, required: bool) {
+        self.user.set_subscription_approval(required)
+    }
+
     /// Return boolean representing the sequencing nature of the channel
     pub fn is_multi_branching(&self) -> bool {
         self.user.is_multi_branching()
@@ -60,6 +122,29 @@ impl<Trans> Author<Trans> {
         self.user.is_single_depth()
     }
 
+    /// Return boolean representing whether keyloads of the channel hide their recipients
+    pub fn has_anonymous_keyloads(&self) -> bool {
+        self.user.has_anonymous_keyloads()
+    }
+
+    /// Hide the recipients of keyloads behind anonymous slots that they trial-decrypt. Subscribers
+    /// learn the option from the announcement, so it must be selected before `send_announce`.
+    pub fn set_anonymous_keyloads(&mut self, anonymous: bool) {
+        self.user.set_anonymous_keyloads(anonymous)
+    }
+
+    /// Return boolean representing whether packets are only accepted from certified publishers
+    pub fn requires_certificates(&self) -> bool {
+        self.user.requires_certificates()
+    }
+
+    /// Only accept signed packets from publishers certified with `send_publisher_certificate`, tagged
+    /// packets are rejected. Subscribers learn the option from the announcement, so it must be
+    /// selected before `send_announce`.
+    pub fn set_certified_publishers(&mut self, required: bool) {
+        self.user.set_certified_publishers(required)
+    }
+
     /// Fetch the Address (application instance) of the channel.
     pub fn channel_address(&self) -> Option<&ChannelAddress> {
         self.user.channel_address()
@@ -108,6 +193,89 @@ impl<Trans> Author<Trans> {
         self.user.remove_subscriber(pk)
     }
 
+    /// Link new messages are attached to by default: the latest keyload sent, or the announcement if
+    /// none.
+    pub fn default_link_to(&self) -> Option<Address> {
+        self.user.default_link_to()
+    }
+
+    /// Branches that still depend on keyloads including revoked subscribers or PSKs: keyload links
+    /// together with the revoked identifiers they included.
+    pub fn compromised_branches(&self) -> Vec<(Address, Vec<Identifier>)> {
+        self.user.compromised_branches()
+    }
+
+    /// Store a named group of keyload recipients, replacing the group with the same name if any
+    ///
+    ///   # Arguments
+    ///   * `name` - Name of the group
+    ///   * `members` - Identifiers (ed25519 public keys and PSK ids) of the group members
+    pub fn store_group(&mut self, name: &str, members: Vec<Identifier>) {
+        self.user.store_group(name, members)
+    }
+
+    /// Remove a named group of keyload recipients
+    pub fn remove_group(&mut self, name: &str) -> Result<()> {
+        self.user.remove_group(name)
+    }
+
+    /// Members of a named group of keyload recipients
+    pub fn group_members(&self, name: &str) -> Result<Vec<Identifier>> {
+        self.user.group_members(name)
+    }
+
+    /// Names of the stored groups of keyload recipients
+    pub fn group_names(&self) -> Vec<String> {
+        self.user.group_names()
+    }
+
+    /// Add a subscriber or PSK to the key tree shared by group keyloads. Adding or removing a member
+    /// costs a number of key encapsulations logarithmic in the number of members.
+    ///
+    ///   # Arguments
+    ///   * `id` - Identifier (ed25519 public key or PSK id) of a known subscriber or stored PSK
+    pub fn add_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        self.user.add_tree_member(id)
+    }
+
+    /// Remove a subscriber or PSK from the key tree, it cannot read the keyloads sent for the tree
+    /// afterwards
+    pub fn remove_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        self.user.remove_tree_member(id)
+    }
+
+    /// Members of the key tree
+    pub fn tree_members(&self) -> Vec<Identifier> {
+        self.user.tree_members()
+    }
+
+    /// Subscriptions awaiting approval: subscriber public key and link to the subscribe message
+    pub fn pending_subscriptions(&self) -> Vec<(PublicKey, Address)> {
+        self.user.pending_subscriptions()
+    }
+
+    /// Accept a pending subscription, registering its subscriber
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the pending subscriber
+    pub fn accept_subscription(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.accept_subscription(pk)
+    }
+
+    /// Reject a pending subscription, its subscriber is not registered
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the pending subscriber
+    pub fn reject_subscription(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.reject_subscription(pk)
+    }
+
+    /// Take the messages rejected since the last call among several messages published at the
+    /// same link, see [`User::take_rejected_messages`].
+    pub fn take_rejected_messages(&mut self) -> Vec<RejectedMessage> {
+        self.user.take_rejected_messages()
+    }
+
     /// Generate a vector containing the next sequenced message identifier for each publishing
     /// participant in the channel
     ///
@@ -176,9 +344,14 @@ impl<Trans> Author<Trans> {
 }
 
 impl<Trans: Transport + Clone> Author<Trans> {
-    /// Generates a new Author implementation from input. If the announcement message generated by
-    /// this instance matches that of an existing (and provided) announcement link, the user will
-    /// sync to the latest state
+    /// Generates a new Author implementation from input and rebuilds its state by walking the
+    /// channel from the announcement, see [`User::recover_channel`]. The recovered cursors are
+    /// returned by `fetch_state`. Channels whose Author key was rotated are recovered with
+    /// [`Author::recover_rotated`].
+    ///
+    /// Only the subscribers listed in the keyloads of the channel are rebuilt, pending
+    /// subscriptions and subscribers that were not sent a keyload yet are lost and must subscribe
+    /// again.
     ///
     ///  # Arguements
     /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
@@ -191,14 +364,27 @@ impl<Trans: Transport + Clone> Author<Trans> {
         channel_type: ChannelType,
         transport: Trans,
     ) -> Result<Self> {
-        let mut author = Author::new(seed, channel_type, transport);
-
-        let ann = author.user.user.announce().await?;
-        let retrieved: Message = author.user.transport.recv_message(announcement).await?;
-        panic_if_not(retrieved.binary == ann.message);
-
-        author.user.commit_wrapped(ann.wrapped, MsgInfo::Announce)?;
+        Self::recover_rotated(seed, &[], announcement, channel_type, transport).await
+    }
 
+    /// Recover an Author whose key was rotated with `send_key_rotation`, see [`Author::recover`].
+    /// The Author signs with the key derived from the last rotation seed once recovered.
+    ///
+    ///  # Arguements
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `rotation_seeds` - Seeds passed to `send_key_rotation`, in any order
+    /// * `announcement` - An existing announcement message link for validation of ownership
+    /// * `multi_branching` - Boolean representing use of multi-branch or single-branch sequencing
+    /// * `transport` - Transport object used for sending and receiving
+    pub async fn recover_rotated(
+        seed: &str,
+        rotation_seeds: &[&str],
+        announcement: &Address,
+        channel_type: ChannelType,
+        transport: Trans,
+    ) -> Result<Self> {
+        let mut author = Author::new(seed, channel_type, transport);
+        author.user.recover_channel(announcement, rotation_seeds).await?;
         Ok(author)
     }
 
@@ -227,6 +413,81 @@ impl<Trans: Transport + Clone> Author<Trans> {
         self.user.send_keyload_for_everyone(link_to).await
     }
 
+    /// Create and send a keyload for the members of a named group. Returns the links of the keyload
+    /// and of its sequence message, and the members that could not be included because they are not
+    /// known subscribers or stored PSKs. Fails if none of the members are known.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `name` - Name of the group of recipients
+    pub async fn send_keyload_for_group(
+        &mut self,
+        link_to: &Address,
+        name: &str,
+    ) -> Result<(Address, Option<Address>, Vec<Identifier>)> {
+        self.user.send_keyload_for_group(link_to, name).await
+    }
+
+    /// Create and send a keyload for the members of the key tree. Subscribers must receive these
+    /// keyloads in order to keep up with the keys of the tree.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    pub async fn send_keyload_for_tree(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        self.user.send_keyload_for_tree(link_to).await
+    }
+
+    /// Create and send a keyload for the members of the key tree which also delivers the keys of the
+    /// tree to a member again. A member that missed keyloads of the tree cannot read the following
+    /// ones until it receives such a keyload.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `id` - Identifier of the member of the key tree
+    pub async fn send_tree_resync(&mut self, link_to: &Address, id: &Identifier) -> Result<(Address, Option<Address>)> {
+        self.user.send_tree_resync(link_to, id).await
+    }
+
+    /// Create and send a KeyRotation message replacing the signing key with the key pair derived
+    /// from `seed`. The message is signed with the current key, Subscribers verify it against the
+    /// key they know and then switch to the new key.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the key rotation will be attached to
+    ///  * `seed` - A string slice representing the seed of the new key pair [Characters: A-Z, 9]
+    pub async fn send_key_rotation(&mut self, link_to: &Address, seed: &str) -> Result<(Address, Option<Address>)> {
+        self.user.send_key_rotation(link_to, seed).await
+    }
+
+    /// Create and send a PublisherCertificate message granting a publisher the rights to send the
+    /// packets of the certificate scope until it expires. A new certificate for the same publisher
+    /// replaces the former one, a certificate with an empty scope revokes the rights.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the certificate will be attached to
+    ///  * `certificate` - Publisher, scope and expiry of the rights
+    pub async fn send_publisher_certificate(
+        &mut self,
+        link_to: &Address,
+        certificate: Certificate,
+    ) -> Result<(Address, Option<Address>)> {
+        self.user.send_publisher_certificate(link_to, certificate).await
+    }
+
+    /// Send a keyload for all subscribers attached to the default link, which then moves to it.
+    pub async fn rekey(&mut self) -> Result<(Address, Option<Address>)> {
+        self.user.rekey().await
+    }
+
+    /// Remove a subscriber, then send a fresh keyload for the remaining subscribers if rekeying on
+    /// removal is enabled. Returns the link of the fresh keyload and of its sequence message, if any.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the subscriber
+    pub async fn revoke_subscriber(&mut self, pk: PublicKey) -> Result<Option<(Address, Option<Address>)>> {
+        self.user.revoke_subscriber(pk).await
+    }
+
     /// Create and send a signed packet.
     ///
     ///  # Arguments
@@ -277,6 +538,13 @@ impl<Trans: Transport + Clone> Author<Trans> {
         self.user.receive_unsubscribe(link).await
     }
 
+    /// Subscribers that left the channel together with the timestamp of their unsubscription, in
+    /// milliseconds since the unix epoch. Only unsubscribe messages linked to the subscribe message
+    /// of the subscriber are accepted.
+    pub fn unsubscriptions(&self) -> Vec<(PublicKey, u64)> {
+        self.user.unsubscriptions()
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -306,6 +574,12 @@ impl<Trans: Transport + Clone> Author<Trans> {
         self.user.fetch_next_msgs().await
     }
 
+    /// Tries to retrieve the next message for each user and returns the outcome for every one of
+    /// them, including failures to receive or handle a message.
+    pub async fn fetch_next_outcomes(&mut self) -> Vec<FetchOutcome> {
+        self.user.fetch_next_outcomes().await
+    }
+
     /// Iteratively fetches next message until no new messages can be found, and return a vector
     /// containing all of them.
     pub async fn fetch_all_next_msgs(&mut self) -> Vec<UnwrappedMessage> {
@@ -322,6 +596,12 @@ impl<Trans: Transport + Clone> Author<Trans> {
         msgs
     }
 
+    /// Stream of the messages published in the channel, see [`User::messages`].
+    #[cfg(feature = "std")]
+    pub fn messages(&mut self, options: MessageStreamOptions) -> impl Stream<Item = Result<UnwrappedMessage>> + '_ {
+        self.user.messages(options)
+    }
+
     /// Retrieves the previous message from the message specified (provided the user has access to it)
     pub async fn fetch_prev_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
         self.user.fetch_prev_msg(link).await
@@ -350,6 +630,14 @@ impl<Trans: Transport + Clone> Author<Trans> {
         self.user.receive_message(link).await
     }
 
+    /// Process a message of unknown type already retrieved from the transport.
+    ///
+    ///   # Arguments
+    ///   * `msg` - Binary message of unknown type
+    pub async fn handle_msg(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        self.user.handle_message(msg, true).await
+    }
+
     // Unsubscribe a subscriber
     // pub async fn receive_unsubscribe(&mut self, link: Address) -> Result<()> {
     // self.user.handle_unsubscribe(link, MsgInfo::Unsubscribe).await
@@ -0,0 +1,218 @@
+//! Several channels of one Author identity.
+//!
+//! Channels derived from the same seed share the Author key pair and differ by their channel index,
+//! which determines the application instance of the channel. The manager routes messages to the
+//! channel of their application instance and exports the state of all channels in one bundle:
+//!
+//! ```ddml
+//! message ChannelBundle {
+//!     absorb u8 version;
+//!     absorb external u8 key[32];
+//!     mask bytes seed;
+//!     absorb size n;
+//!     repeated(n) {
+//!         absorb u64 channel_idx;
+//!         mask bytes channel;
+//!     }
+//!     commit;
+//!     squeeze u8 mac[32];
+//! }
+//! ```
+//!
+//! Each `channel` is the export of the Author of the channel with the same password.
+
+use iota_streams_core::{
+    err,
+    prelude::{
+        typenum::U32,
+        String,
+        ToString,
+        Vec,
+    },
+    prng,
+    try_or,
+    Errors::*,
+    Result,
+};
+use iota_streams_ddml::{
+    command::*,
+    types::*,
+};
+
+use super::*;
+
+const BUNDLE_VERSION: u8 = 0;
+
+/// Channels of one Author identity, indexed by channel index.
+pub struct ChannelManager<Trans> {
+    seed: String,
+    transport: Trans,
+    channels: Vec<(u64, Author<Trans>)>,
+}
+
+impl<Trans: Clone> ChannelManager<Trans> {
+    /// Create a manager without channels.
+    ///
+    /// # Arguments
+    /// * `seed` - A string slice representing the seed of the Author [Characters: A-Z, 9]
+    /// * `transport` - Transport object shared by the channels
+    pub fn new(seed: &str, transport: Trans) -> Self {
+        Self {
+            seed: seed.to_string(),
+            transport,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Create the channel with index `channel_idx`, which is announced with `send_announce` of the
+    /// returned Author.
+    ///
+    /// # Arguments
+    /// * `channel_idx` - Index of the channel, distinct channels have distinct indices
+    /// * `channel_type` - Implementation type of the channel
+    pub fn create_channel(&mut self, channel_idx: u64, channel_type: ChannelType) -> Result<&mut Author<Trans>> {
+        try_or!(self.channel(channel_idx).is_none(), ChannelAlreadyManaged(channel_idx))?;
+        let author = Author::with_channel_index(&self.seed, channel_type, channel_idx, self.transport.clone())?;
+        self.channels.push((channel_idx, author));
+        Ok(&mut self.channels.last_mut().unwrap().1)
+    }
+
+    /// Indices of the managed channels, in order of creation.
+    pub fn channel_indices(&self) -> Vec<u64> {
+        self.channels.iter().map(|(idx, _)| *idx).collect()
+    }
+
+    pub fn channel(&self, channel_idx: u64) -> Option<&Author<Trans>> {
+        self.channels
+            .iter()
+            .find(|(idx, _)| *idx == channel_idx)
+            .map(|(_, author)| author)
+    }
+
+    pub fn channel_mut(&mut self, channel_idx: u64) -> Option<&mut Author<Trans>> {
+        self.channels
+            .iter_mut()
+            .find(|(idx, _)| *idx == channel_idx)
+            .map(|(_, author)| author)
+    }
+
+    /// Channel with application instance `appinst`.
+    pub fn channel_by_address(&mut self, appinst: &ChannelAddress) -> Option<&mut Author<Trans>> {
+        self.channels
+            .iter_mut()
+            .map(|(_, author)| author)
+            .find(|author| author.channel_address() == Some(appinst))
+    }
+
+    fn route(&mut self, appinst: &ChannelAddress) -> Result<&mut Author<Trans>> {
+        match self.channel_by_address(appinst) {
+            Some(author) => Ok(author),
+            None => err!(ChannelNotManaged(appinst.to_string())),
+        }
+    }
+
+    /// Serialize the state of all channels and encrypt it with password.
+    ///
+    ///   # Arguments
+    ///   * `pwd` - Encryption password
+    pub async fn export(&self, pwd: &str) -> Result<Vec<u8>> {
+        let seed = Bytes(self.seed.as_bytes().to_vec());
+        let mut channels = Vec::new();
+        for (idx, author) in self.channels.iter() {
+            channels.push((Uint64(*idx), Bytes(author.export(pwd).await?)));
+        }
+
+        let buf_size = {
+            let mut ctx = sizeof::Context::<DefaultF>::new();
+            ctx.absorb(Uint8(BUNDLE_VERSION))?
+                .mask(&seed)?
+                .absorb(Size(channels.len()))?;
+            for (idx, channel) in channels.iter() {
+                ctx.absorb(idx)?.mask(channel)?;
+            }
+            ctx.commit()?.squeeze(Mac(32))?;
+            ctx.get_size()
+        };
+
+        let mut buf = vec![0; buf_size];
+        {
+            let mut ctx = wrap::Context::new(&mut buf[..]);
+            let key = bundle_key(pwd);
+            ctx.absorb(Uint8(BUNDLE_VERSION))?
+                .absorb(External(&key))?
+                .mask(&seed)?
+                .absorb(Size(channels.len()))?;
+            for (idx, channel) in channels.iter() {
+                ctx.absorb(idx)?.mask(channel)?;
+            }
+            ctx.commit()?.squeeze(Mac(32))?;
+            try_or!(ctx.stream.is_empty(), OutputStreamNotFullyConsumed(ctx.stream.len()))?;
+        }
+        Ok(buf)
+    }
+
+    /// Deserialize the state of all channels and decrypt it with password.
+    ///
+    ///   # Arguments
+    ///   * `bytes` - Encrypted serialized channel bundle
+    ///   * `pwd` - Encryption password
+    ///   * `transport` - Transport object shared by the channels
+    pub async fn import(bytes: &[u8], pwd: &str, transport: Trans) -> Result<Self> {
+        let mut ctx = unwrap::Context::new(bytes);
+        let key = bundle_key(pwd);
+        let mut version = Uint8(0);
+        let mut seed = Bytes::default();
+        let mut repeated_channels = Size(0);
+        ctx.absorb(&mut version)?
+            .guard(
+                version.0 == BUNDLE_VERSION,
+                UserVersionRecoveryFailure(BUNDLE_VERSION, version.0),
+            )?
+            .absorb(External(&key))?
+            .mask(&mut seed)?
+            .absorb(&mut repeated_channels)?;
+        let mut exports = Vec::new();
+        for _ in 0..repeated_channels.0 {
+            let mut idx = Uint64(0);
+            let mut channel = Bytes::default();
+            ctx.absorb(&mut idx)?.mask(&mut channel)?;
+            exports.push((idx.0, channel));
+        }
+        ctx.commit()?.squeeze(Mac(32))?;
+        try_or!(ctx.stream.is_empty(), InputStreamNotFullyConsumed(ctx.stream.len()))?;
+
+        let mut channels = Vec::new();
+        for (idx, channel) in exports {
+            channels.push((idx, Author::import(&channel.0, pwd, transport.clone()).await?));
+        }
+        Ok(Self {
+            seed: String::from_utf8_lossy(&seed.0).to_string(),
+            transport,
+            channels,
+        })
+    }
+}
+
+impl<Trans: Transport + Clone> ChannelManager<Trans> {
+    /// Process a message in the channel of its application instance.
+    ///
+    ///   # Arguments
+    ///   * `msg` - Binary message of unknown type
+    pub async fn handle_msg(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        let appinst = msg.binary.link.appinst;
+        self.route(&appinst)?.handle_msg(msg).await
+    }
+
+    /// Receive and process a message in the channel of its application instance.
+    ///
+    ///   # Arguments
+    ///   * `link` - Address of the message to be processed
+    pub async fn receive_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
+        self.route(&link.appinst)?.receive_msg(link).await
+    }
+}
+
+fn bundle_key(pwd: &str) -> NBytes<U32> {
+    let prng = prng::from_seed::<DefaultF>("IOTA Streams Channels app", pwd);
+    NBytes::<U32>(prng.gen_arr("channel bundle export key"))
+}
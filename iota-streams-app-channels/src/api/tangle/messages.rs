@@ -0,0 +1,185 @@
+//! Stream of the messages published in a channel.
+
+use core::{
+    future::Future,
+    sync::atomic::{
+        AtomicBool,
+        Ordering,
+    },
+    task::Poll,
+    time::Duration,
+};
+use futures::{
+    future,
+    stream,
+    task::AtomicWaker,
+    Stream,
+};
+use futures_timer::Delay;
+
+use iota_streams_app::transport::{
+    is_message_not_found,
+    is_transient,
+};
+
+use iota_streams_core::{
+    prelude::{
+        Arc,
+        Vec,
+    },
+    Result,
+};
+
+use super::*;
+
+#[derive(Default, Debug)]
+struct StopState {
+    stopped: AtomicBool,
+    /// Waker of the stream waiting for the next round.
+    waker: AtomicWaker,
+}
+
+/// Handle stopping a message stream, clones stop the same stream.
+#[derive(Clone, Default, Debug)]
+pub struct StopHandle(Arc<StopState>);
+
+impl StopHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the stream. The message being processed is still yielded, then the stream ends. A
+    /// stream waiting for its next round ends right away.
+    pub fn stop(&self) {
+        self.0.stopped.store(true, Ordering::SeqCst);
+        self.0.waker.wake();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Future completing once the stream is stopped.
+    fn stopped(&self) -> impl Future<Output = ()> + Unpin + '_ {
+        future::poll_fn(move |cx| {
+            self.0.waker.register(cx.waker());
+            if self.is_stopped() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+    }
+}
+
+/// Options of the message stream.
+#[derive(Clone, Default, Debug)]
+pub struct MessageStreamOptions {
+    /// Delay between polls for new messages once all published messages have been received.
+    /// The stream ends at that point if `None`.
+    pub polling_interval: Option<Duration>,
+
+    /// Handle to stop the stream with.
+    pub stop: StopHandle,
+}
+
+impl MessageStreamOptions {
+    pub fn with_polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = Some(polling_interval);
+        self
+    }
+
+    pub fn with_stop_handle(mut self, stop: StopHandle) -> Self {
+        self.stop = stop;
+        self
+    }
+}
+
+struct MessageStream<'a, Trans> {
+    user: &'a mut User<Trans>,
+    options: MessageStreamOptions,
+    /// Links of the next messages of the publishers not polled yet in the current round.
+    links: Vec<Address>,
+    /// Whether a round of polls has completed.
+    polled: bool,
+    /// Whether a message has been handled in the current round.
+    found: bool,
+    /// Links of the messages that failed to be handled for a reason that persists, they are
+    /// yielded as errors once and skipped afterwards.
+    failed: Vec<Address>,
+}
+
+impl<'a, Trans: Transport + Clone> MessageStream<'a, Trans> {
+    async fn next(&mut self) -> Option<Result<UnwrappedMessage>> {
+        loop {
+            if self.options.stop.is_stopped() {
+                return None;
+            }
+
+            match self.links.pop() {
+                Some(link) if self.failed.contains(&link) => {}
+                Some(link) => match self.user.receive_message(&link).await {
+                    Ok(msg) => {
+                        self.found = true;
+                        return Some(Ok(msg));
+                    }
+                    // Publisher has not sent its next message yet
+                    Err(e) if is_message_not_found(&e) => {}
+                    Err(e) => {
+                        if !is_transient(&e) {
+                            self.failed.push(link);
+                        }
+                        return Some(Err(e));
+                    }
+                },
+                None => {
+                    if self.polled && !self.found {
+                        match self.options.polling_interval {
+                            Some(interval) => {
+                                future::select(Delay::new(interval), self.options.stop.stopped()).await;
+                            }
+                            None => return None,
+                        }
+                    }
+                    let branching = self.user.is_multi_branching();
+                    self.links = self
+                        .user
+                        .gen_next_msg_ids(branching)
+                        .into_iter()
+                        .rev()
+                        .map(|(_id, cursor)| cursor.link)
+                        .collect();
+                    self.polled = true;
+                    self.found = false;
+                }
+            }
+        }
+    }
+}
+
+impl<Trans: Transport + Clone> User<Trans> {
+    /// Stream of the messages published in the channel, yielded as they are received [Author,
+    /// Subscriber].
+    ///
+    /// The next message of every publisher is polled for in rounds, like `fetch_next_msgs` does.
+    /// Once a round finds no new message the stream either ends or waits for the polling interval
+    /// before the next round. Messages that cannot be handled are yielded as errors once, and only
+    /// retried in the following rounds if the failure is transient, eg. a transport timeout.
+    ///
+    ///   # Arguments
+    ///   * `options` - Polling interval and stop handle of the stream
+    pub fn messages(&mut self, options: MessageStreamOptions) -> impl Stream<Item = Result<UnwrappedMessage>> + '_ {
+        let state = MessageStream {
+            user: self,
+            options,
+            links: Vec::new(),
+            polled: false,
+            found: false,
+            failed: Vec::new(),
+        };
+        stream::unfold(state, |mut state| async move {
+            let item = state.next().await;
+            item.map(|item| (item, state))
+        })
+    }
+}
@@ -3,6 +3,7 @@
 use super::key_store::KeyMap;
 pub use iota_streams_app::transport::tangle::MsgId;
 use iota_streams_app::{
+    identifier::Identifier,
     message::{
         self,
         BinaryBody,
@@ -31,7 +32,11 @@ pub use transport::{
 
 pub use super::ChannelType;
 use super::DefaultF;
-use iota_streams_core::psk;
+use iota_streams_core::{
+    psk,
+    Error,
+    Errors,
+};
 use iota_streams_ddml::link_store::DefaultLinkStore;
 pub use iota_streams_ddml::types::Bytes;
 
@@ -59,6 +64,10 @@ pub type WrapState = message::WrapState<DefaultF, Address>;
 pub type WrappedSequence = super::user::WrappedSequence<DefaultF, Address>;
 /// Ed25519 Public Key
 pub type PublicKey = ed25519::PublicKey;
+pub use crate::message::publisher_certificate::{
+    Certificate,
+    SCOPE_SIGNED_PACKETS,
+};
 
 /// Message type with parsed header.
 pub type Preparsed<'a> = message::PreparsedMessage<'a, DefaultF, Address>;
@@ -77,6 +86,22 @@ pub type LinkStore = DefaultLinkStore<DefaultF, MsgId, MsgInfo>;
 /// Test Transport.
 pub type BucketTransport = transport::BucketTransport<Address, Message>;
 
+/// Local file system Transport.
+#[cfg(feature = "std")]
+pub type FileTransport = transport::FileTransport<Address, Message>;
+
+/// Transport caching the messages received through a wrapped Transport.
+#[cfg(feature = "std")]
+pub type CachingTransport<T> = transport::CachingTransport<T, Address, Message>;
+
+/// Transport recording the calls to a wrapped Transport.
+#[cfg(feature = "std")]
+pub type RecordingTransport<T> = transport::RecordingTransport<T, Address, Message>;
+
+/// Transport replaying a recorded trace.
+#[cfg(feature = "std")]
+pub type ReplayTransport = transport::ReplayTransport<Address, Message>;
+
 /// Transportation trait for Tangle Client implementation
 // TODO: Use trait synonyms `pub Transport = transport::Transport<DefaultF, Address>;`.
 pub trait Transport: transport::Transport<Address, Message> + Clone {}
@@ -107,6 +132,12 @@ pub enum MessageContent {
     Sequence,
     Subscribe,
     Unsubscribe,
+    KeyRotation {
+        pk: PublicKey,
+    },
+    PublisherCertificate {
+        certificate: Certificate,
+    },
     Unreadable,
 }
 
@@ -134,6 +165,14 @@ impl MessageContent {
         }
     }
 
+    pub fn new_key_rotation(pk: PublicKey) -> Self {
+        Self::KeyRotation { pk }
+    }
+
+    pub fn new_publisher_certificate(certificate: Certificate) -> Self {
+        Self::PublisherCertificate { certificate }
+    }
+
     pub fn unreadable() -> Self {
         Self::Unreadable
     }
@@ -142,12 +181,69 @@ impl MessageContent {
 /// Generic unwrapped message type containing possible message contents
 pub type UnwrappedMessage = message::GenericMessage<Address, MessageContent>;
 
+/// Outcome of fetching the next message of a publisher.
+pub enum FetchOutcome {
+    /// Next message has been received and handled.
+    Message(UnwrappedMessage),
+    /// Next message has not been published yet.
+    NotAvailable { publisher: Identifier, link: Address },
+    /// Next message could not be received or handled, eg. because it has been tampered with.
+    Failed(FetchFailure),
+}
+
+/// Failure to receive or handle the next message of a publisher.
+pub struct FetchFailure {
+    /// Publisher of the message.
+    pub publisher: Identifier,
+    /// Link of the message.
+    pub link: Address,
+    /// Error raised by the transport or by handling the message.
+    pub error: Error,
+}
+
+impl FetchFailure {
+    /// Streams error the failure is caused by, eg. `BadMac` or `SignatureMismatch`. `None` if the
+    /// error originates from a dependency, eg. the transport client.
+    pub fn cause(&self) -> Option<&Errors> {
+        self.error.downcast_ref::<Errors>()
+    }
+}
+
+/// Message rejected among several messages published at the same link.
+pub struct RejectedMessage {
+    /// Link the message has been published at.
+    pub link: Address,
+    /// The rejected message.
+    pub message: Message,
+    /// Error raised by handling the message.
+    pub error: Error,
+}
+
+impl RejectedMessage {
+    /// Streams error the message has been rejected for, eg. `BadMac` or `SignatureMismatch`.
+    pub fn cause(&self) -> Option<&Errors> {
+        self.error.downcast_ref::<Errors>()
+    }
+}
+
 /// Generic binary message type for sequence handling
 pub type BinaryMessage = message::GenericMessage<Address, BinaryBody<DefaultF>>;
 
 mod user;
 /// User object storing the Auth/Sub implementation as well as the transport instance
-pub use user::User;
+pub use user::{
+    User,
+    MAX_REJECTED_MESSAGES,
+};
+
+#[cfg(feature = "std")]
+mod messages;
+/// Options of the stream of channel messages.
+#[cfg(feature = "std")]
+pub use messages::{
+    MessageStreamOptions,
+    StopHandle,
+};
 
 mod author;
 /// Tangle-specific Channel Author type.
@@ -157,4 +253,8 @@ mod subscriber;
 /// Tangle-specific Channel Subscriber type.
 pub use subscriber::Subscriber;
 
+mod channel_manager;
+/// Several channels of one Tangle-specific Channel Author.
+pub use channel_manager::ChannelManager;
+
 pub mod test;
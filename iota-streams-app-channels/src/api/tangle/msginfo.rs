@@ -25,6 +25,8 @@ pub enum MsgInfo {
     Subscribe,
     Unsubscribe,
     Sequence,
+    KeyRotation,
+    PublisherCertificate,
 }
 
 // Default instance is required by ddml unwrap logic as unwrap modifies/updates an existing object rather producing a
@@ -45,6 +47,8 @@ impl From<MsgInfo> for u8 {
             MsgInfo::Subscribe => 4,
             MsgInfo::Unsubscribe => 5,
             MsgInfo::Sequence => 6,
+            MsgInfo::KeyRotation => 7,
+            MsgInfo::PublisherCertificate => 8,
         }
     }
 }
@@ -66,6 +70,8 @@ impl TryFrom<u8> for MsgInfo {
             4 => Ok(MsgInfo::Subscribe),
             5 => Ok(MsgInfo::Unsubscribe),
             6 => Ok(MsgInfo::Sequence),
+            7 => Ok(MsgInfo::KeyRotation),
+            8 => Ok(MsgInfo::PublisherCertificate),
             _ => Err(()),
         }
     }
@@ -27,6 +27,9 @@ use iota_streams_core::{
 };
 use iota_streams_core_edsig::signature::ed25519;
 
+#[cfg(feature = "std")]
+use futures::Stream;
+
 /// Subscriber Object. Contains User API.
 pub struct Subscriber<T> {
     user: User<T>,
@@ -48,6 +51,35 @@ impl<Trans> Subscriber<Trans> {
         self.user.get_transport()
     }
 
+    /// Pad sent messages to a uniform length, see [`User::set_uniform_payload_length`].
+    ///
+    /// # Arguments
+    /// * `length` - Size in bytes messages are padded to, zero disables padding
+    /// * `pad_keyloads` - Whether keyloads are padded as well as signed and tagged packets
+    pub fn set_uniform_payload_length(&mut self, length: usize, pad_keyloads: bool) {
+        self.user.set_uniform_payload_length(length, pad_keyloads)
+    }
+
+    /// Include the time of publishing in sent signed and tagged packets. Recipients get it as
+    /// `sender_timestamp` of the unwrapped message.
+    pub fn set_sender_timestamps(&mut self, enabled: bool) {
+        self.user.set_sender_timestamps(enabled)
+    }
+
+    /// Erase the state of a message once a signed or tagged packet linked to it is sent or
+    /// received, see [`User::set_ratchet`].
+    pub fn set_ratchet(&mut self, enabled: bool) {
+        self.user.set_ratchet(enabled)
+    }
+
+    /// Select the codec encoding payloads of sent packets.
+    ///
+    /// # Arguments
+    /// * `encoding` - Name of a registered codec [built-in: utf-8, raw, deflate, zstd, cbor, utf-8-strict]
+    pub fn set_message_encoding(&mut self, encoding: &str) -> Result<()> {
+        self.user.set_message_encoding(encoding)
+    }
+
     /// Returns a boolean representing whether an Announcement message has been processed
     pub fn is_registered(&self) -> bool {
         self.user.is_registered()
@@ -68,6 +100,20 @@ impl<Trans> Subscriber<Trans> {
         self.user.author_public_key()
     }
 
+    /// Pin the public key the channel Author is expected to announce the channel with. Announcements
+    /// with a different key are rejected by `receive_announcement`.
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the Author
+    pub fn pin_author_public_key(&mut self, pk: ed25519::PublicKey) {
+        self.user.pin_author_public_key(pk)
+    }
+
+    /// Former public keys of the Author replaced by key rotations, the announced key first.
+    pub fn author_key_history(&self) -> &[ed25519::PublicKey] {
+        self.user.author_key_history()
+    }
+
     /// Store a PSK in the user instance
     ///
     ///   # Arguments
@@ -105,6 +151,21 @@ impl<Trans> Subscriber<Trans> {
         self.user.is_single_depth()
     }
 
+    /// Return boolean representing whether keyloads of the channel hide their recipients
+    pub fn has_anonymous_keyloads(&self) -> bool {
+        self.user.has_anonymous_keyloads()
+    }
+
+    /// Return boolean representing whether packets are only accepted from certified publishers
+    pub fn requires_certificates(&self) -> bool {
+        self.user.requires_certificates()
+    }
+
+    /// Latest certificate issued by the Author to a publisher
+    pub fn publisher_certificate(&self, publisher: &Identifier) -> Option<&Certificate> {
+        self.user.publisher_certificate(publisher)
+    }
+
     /// Stores the provided link to the internal sequencing state for the provided participant
     /// [Used for multi-branching sequence state updates]
     ///
@@ -139,11 +200,18 @@ impl<Trans> Subscriber<Trans> {
     }
 
     /// Resets the cursor state storage to allow a Subscriber to retrieve all messages in a channel
-    /// from scratch
+    /// from scratch. The Author key goes back to the announced one, and key rotations are verified
+    /// again as they are retrieved.
     pub fn reset_state(&mut self) -> Result<()> {
         self.user.reset_state()
     }
 
+    /// Take the messages rejected since the last call among several messages published at the
+    /// same link, see [`User::take_rejected_messages`].
+    pub fn take_rejected_messages(&mut self) -> Vec<RejectedMessage> {
+        self.user.take_rejected_messages()
+    }
+
     /// Generate a vector containing the next sequenced message identifier for each publishing
     /// participant in the channel
     ///
@@ -175,6 +243,26 @@ impl<Trans> Subscriber<Trans> {
 }
 
 impl<Trans: Transport + Clone> Subscriber<Trans> {
+    /// Generates a new Subscriber implementation from input and rebuilds its state from its
+    /// subscribe message and the channel history, see [`User::recover_subscriber`]. The recovered
+    /// cursors are returned by `fetch_state`.
+    ///
+    ///  # Arguments
+    /// * `seed` - A string slice representing the seed of the user [Characters: A-Z, 9]
+    /// * `announcement` - Address of the Channel Announcement message
+    /// * `subscribe_link` - Address of the Subscribe message sent with the same seed
+    /// * `transport` - Transport object used for sending and receiving
+    pub async fn recover(
+        seed: &str,
+        announcement: &Address,
+        subscribe_link: &Address,
+        transport: Trans,
+    ) -> Result<Self> {
+        let mut subscriber = Subscriber::new(seed, transport);
+        subscriber.user.recover_subscriber(announcement, subscribe_link).await?;
+        Ok(subscriber)
+    }
+
     /// Create and Send a Subscribe message to a Channel app instance.
     ///
     /// # Arguments
@@ -245,6 +333,24 @@ impl<Trans: Transport + Clone> Subscriber<Trans> {
         self.user.receive_keyload(link).await
     }
 
+    /// Receive and process a key rotation message, returning the new Author public key. The
+    /// rotation must be signed with the current Author key.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    pub async fn receive_key_rotation(&mut self, link: &Address) -> Result<ed25519::PublicKey> {
+        self.user.receive_key_rotation(link).await
+    }
+
+    /// Receive and process a publisher certificate message, returning the certificate. It must be
+    /// signed with the current Author key.
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    pub async fn receive_publisher_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        self.user.receive_publisher_certificate(link).await
+    }
+
     /// Receive and process a signed packet message.
     ///
     ///  # Arguments
@@ -274,6 +380,18 @@ impl<Trans: Transport + Clone> Subscriber<Trans> {
         self.user.fetch_next_msgs().await
     }
 
+    /// Tries to retrieve the next message for each user and returns the outcome for every one of
+    /// them, including failures to receive or handle a message.
+    pub async fn fetch_next_outcomes(&mut self) -> Vec<FetchOutcome> {
+        self.user.fetch_next_outcomes().await
+    }
+
+    /// Stream of the messages published in the channel, see [`User::messages`].
+    #[cfg(feature = "std")]
+    pub fn messages(&mut self, options: MessageStreamOptions) -> impl Stream<Item = Result<UnwrappedMessage>> + '_ {
+        self.user.messages(options)
+    }
+
     /// Retrieves the previous message from the message specified (provided the user has access to it)
     pub async fn fetch_prev_msg(&mut self, link: &Address) -> Result<UnwrappedMessage> {
         self.user.fetch_prev_msg(link).await
@@ -1,9 +1,24 @@
 #![allow(non_snake_case)]
-use crate::api::tangle::{
-    Author,
-    Subscriber,
+use crate::api::{
+    tangle::{
+        Author,
+        CachingTransport,
+        ChannelManager,
+        Subscriber,
+    },
+    DefaultF,
+};
+use iota_streams_app::{
+    message::{
+        BinaryBody,
+        HasLink,
+        PCF,
+    },
+    transport::{
+        new_shared_transport,
+        tangle::PAYLOAD_BYTES,
+    },
 };
-use iota_streams_app::message::HasLink;
 
 use iota_streams_core::{
     try_or,
@@ -12,7 +27,11 @@ use iota_streams_core::{
 
 use iota_streams_core::{
     ensure,
-    prelude::string::ToString,
+    prelude::{
+        string::ToString,
+        String,
+        Vec,
+    },
     println,
     Result,
 };
@@ -141,8 +160,1287 @@ pub async fn example<T: Transport + Clone>(transport: T) -> Result<()> {
     Ok(())
 }
 
+/// Announce the channel of `author` and subscribe each of `subscribers` to it, returning the
+/// announcement link and the links of the subscribe messages.
+async fn announce_and_subscribe<TA: Transport + Clone, TS: Transport + Clone>(
+    author: &mut Author<TA>,
+    subscribers: &mut [&mut Subscriber<TS>],
+) -> Result<(Address, Vec<Address>)> {
+    let announcement_link = author.send_announce().await?;
+    let mut subscribe_links = Vec::new();
+    for subscriber in subscribers.iter_mut() {
+        subscriber.receive_announcement(&announcement_link).await?;
+        let subscribe_link = subscriber.send_subscribe(&announcement_link).await?;
+        author.receive_subscribe(&subscribe_link).await?;
+        subscribe_links.push(subscribe_link);
+    }
+    Ok((announcement_link, subscribe_links))
+}
+
+/// Packets larger than a frame are split into several frames and joined back on reception.
+pub async fn multi_frame_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9FRAMES9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9FRAMES9SEED", transport);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let public_payload = Bytes(vec![b'P'; 2 * PAYLOAD_BYTES]);
+    let masked_payload = Bytes(vec![b'M'; 3 * PAYLOAD_BYTES]);
+    let (large_packet_link, _) = author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (_pk, unwrapped_public, unwrapped_masked) = subscriber.receive_signed_packet(&large_packet_link).await?;
+    ensure!(public_payload == unwrapped_public, "bad unwrapped large public payload");
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped large masked payload");
+    Ok(())
+}
+
+#[test]
+fn run_multi_frame_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(multi_frame_example(transport)).unwrap();
+}
+
+/// Packets are padded to the uniform payload length, and packets exceeding it are not sent.
+pub async fn uniform_payload_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9UNIFORM9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9UNIFORM9SEED", transport);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    author.set_uniform_payload_length(PAYLOAD_BYTES, false);
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (padded_packet_link, _) = author
+        .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (unwrapped_public, unwrapped_masked) = subscriber.receive_tagged_packet(&padded_packet_link).await?;
+    ensure!(
+        public_payload == unwrapped_public,
+        "bad unwrapped padded public payload"
+    );
+    ensure!(
+        masked_payload == unwrapped_masked,
+        "bad unwrapped padded masked payload"
+    );
+
+    let large_payload = Bytes(vec![b'L'; 2 * PAYLOAD_BYTES]);
+    ensure!(
+        author
+            .send_tagged_packet(&keyload_link, &large_payload, &large_payload)
+            .await
+            .is_err(),
+        "oversized packet should not fit the uniform payload length"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_uniform_payload_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(uniform_payload_example(transport)).unwrap();
+}
+
+/// Raw payloads are passed through as is, while utf-8-strict payloads must be valid UTF-8.
+pub async fn message_encoding_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9ENCODING9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9ENCODING9SEED", transport);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    ensure!(
+        author.set_message_encoding("unknown").is_err(),
+        "unknown message encoding should be rejected"
+    );
+    author.set_message_encoding("raw")?;
+    let raw_payload = Bytes(vec![0xff, 0xfe, 0x00, 0x80]);
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (raw_packet_link, _) = author
+        .send_signed_packet(&keyload_link, &raw_payload, &masked_payload)
+        .await?;
+    let (_pk, unwrapped_public, unwrapped_masked) = subscriber.receive_signed_packet(&raw_packet_link).await?;
+    ensure!(raw_payload == unwrapped_public, "bad unwrapped raw public payload");
+    ensure!(masked_payload == unwrapped_masked, "bad unwrapped raw masked payload");
+
+    author.set_message_encoding("utf-8-strict")?;
+    ensure!(
+        author
+            .send_signed_packet(&keyload_link, &raw_payload, &masked_payload)
+            .await
+            .is_err(),
+        "binary payload should be rejected by utf-8-strict"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_message_encoding_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(message_encoding_example(transport)).unwrap();
+}
+
+/// Signed packets carry the time they were sent at while sender timestamps are enabled.
+pub async fn sender_timestamp_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9TIMESTAMP9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9TIMESTAMP9SEED", transport);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let payload = Bytes("TIMESTAMPPAYLOAD".as_bytes().to_vec());
+    author.set_sender_timestamps(true);
+    let (timestamped_link, _) = author.send_signed_packet(&keyload_link, &payload, &payload).await?;
+    author.set_sender_timestamps(false);
+    let (plain_link, _) = author.send_signed_packet(&keyload_link, &payload, &payload).await?;
+
+    let msg = subscriber.receive_msg(&timestamped_link).await?;
+    ensure!(msg.sender_timestamp.is_some(), "sender timestamp is lost");
+    ensure!(
+        matches!(msg.body, MessageContent::SignedPacket { .. }),
+        "timestamped packet is not a signed packet"
+    );
+    let msg = subscriber.receive_msg(&plain_link).await?;
+    ensure!(
+        msg.sender_timestamp.is_none(),
+        "sender timestamp should only be sent while enabled"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_sender_timestamp_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(sender_timestamp_example(transport)).unwrap();
+}
+
+/// Fetch outcomes distinguish messages not published yet from messages that cannot be handled.
+pub async fn fetch_outcomes_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9OUTCOMES9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9OUTCOMES9SEED", transport);
+
+    let announcement_link = author.send_announce().await?;
+    subscriber.receive_announcement(&announcement_link).await?;
+    ensure!(
+        subscriber
+            .fetch_next_outcomes()
+            .await
+            .iter()
+            .all(|outcome| matches!(outcome, FetchOutcome::NotAvailable { .. })),
+        "nothing has been published yet"
+    );
+
+    // Subscriber is not a recipient of the keyload and cannot unwrap the packet linked to it
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    let payload = Bytes("OUTCOMES".as_bytes().to_vec());
+    let (packet_link, _) = author.send_tagged_packet(&keyload_link, &payload, &payload).await?;
+
+    let outcomes = subscriber.fetch_next_outcomes().await;
+    ensure!(
+        outcomes
+            .iter()
+            .any(|outcome| matches!(outcome, FetchOutcome::Message(msg) if msg.link == keyload_link)),
+        "expected keyload"
+    );
+    let outcomes = subscriber.fetch_next_outcomes().await;
+    ensure!(
+        outcomes
+            .iter()
+            .any(|outcome| matches!(outcome, FetchOutcome::Failed(failure) if failure.link == packet_link)),
+        "expected failure to handle tagged packet"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_fetch_outcomes_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(fetch_outcomes_example(transport)).unwrap();
+}
+
+/// Keyloads for a named group include its known members and report the others.
+pub async fn group_keyload_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    use iota_streams_core::psk::{
+        psk_from_seed,
+        pskid_from_psk,
+    };
+
+    let mut author = Author::new("AUTHOR9GROUP9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9GROUP9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9GROUP9SEED", transport.clone());
+    let stranger = Subscriber::new("STRANGER9GROUP9SEED", transport.clone());
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    let psk = psk_from_seed("A group pre shared key".as_bytes());
+    let pskid = pskid_from_psk(&psk);
+    author.store_psk(pskid, psk)?;
+
+    let stranger_id = Identifier::from(*stranger.get_public_key());
+    author.store_group(
+        "ops",
+        vec![(*subscriberA.get_public_key()).into(), pskid.into(), stranger_id],
+    );
+
+    // Groups survive export
+    let authordump = author.export("pwdAuthor").await?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuthor", transport).await?;
+    ensure!(author.group_names() == vec!["ops".to_string()], "expected stored group");
+    ensure!(author.group_members("ops")?.len() == 3, "expected all group members");
+
+    let (keyload_link, _, missing) = author.send_keyload_for_group(&announcement_link, "ops").await?;
+    ensure!(missing == vec![stranger_id], "unknown member should be reported");
+    ensure!(
+        subscriberA.receive_keyload(&keyload_link).await?,
+        "group member should access keyload"
+    );
+    ensure!(
+        !subscriberB.receive_keyload(&keyload_link).await?,
+        "subscriber outside of the group should not access keyload"
+    );
+
+    author.store_group("strangers", vec![stranger_id]);
+    let e = author
+        .send_keyload_for_group(&announcement_link, "strangers")
+        .await
+        .err();
+    ensure!(
+        matches!(
+            e.as_ref().and_then(|e| e.downcast_ref::<Errors>()),
+            Some(GroupMembersNotFound(..))
+        ),
+        "keyload for a group without known members should fail"
+    );
+
+    author.remove_group("ops")?;
+    ensure!(
+        author.send_keyload_for_group(&announcement_link, "ops").await.is_err(),
+        "removed group should not be found"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_group_keyload_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(group_keyload_example(transport)).unwrap();
+}
+
+/// Keyloads for the key tree are readable by its current members only.
+pub async fn key_tree_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9TREE9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9TREE9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9TREE9SEED", transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9TREE9SEED", transport.clone());
+
+    let (announcement_link, _) =
+        announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB, &mut subscriberC]).await?;
+    let idA = Identifier::from(*subscriberA.get_public_key());
+    let idB = Identifier::from(*subscriberB.get_public_key());
+    author.add_tree_member(&idA)?;
+    author.add_tree_member(&idB)?;
+    ensure!(
+        author.add_tree_member(&idA).is_err(),
+        "member should not be added twice"
+    );
+
+    // The key tree survives export
+    let authordump = author.export("pwdAuthor").await?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuthor", transport).await?;
+    ensure!(author.tree_members() == vec![idA, idB], "expected tree members");
+
+    let (keyload_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        subscriberA.receive_keyload(&keyload_link).await?,
+        "tree member should access keyload"
+    );
+    ensure!(
+        subscriberB.receive_keyload(&keyload_link).await?,
+        "tree member should access keyload"
+    );
+    ensure!(
+        !subscriberC.receive_keyload(&keyload_link).await?,
+        "subscriber outside of the tree should not access keyload"
+    );
+
+    author.remove_tree_member(&idB)?;
+    let (rekey_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        subscriberA.receive_keyload(&rekey_link).await?,
+        "remaining member should access the rekey"
+    );
+    ensure!(
+        !subscriberB.receive_keyload(&rekey_link).await?,
+        "removed member should not access the rekey"
+    );
+
+    // A member missing a keyload of the tree catches up with a resync
+    let idC = Identifier::from(*subscriberC.get_public_key());
+    author.add_tree_member(&idC)?;
+    let (added_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        subscriberA.receive_keyload(&added_link).await?,
+        "remaining member should access the keyload"
+    );
+    let (missed_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        !subscriberC.receive_keyload(&missed_link).await?,
+        "member missing a keyload should not access the following ones"
+    );
+    let (resync_link, _) = author.send_tree_resync(&announcement_link, &idC).await?;
+    ensure!(
+        subscriberC.receive_keyload(&resync_link).await? && subscriberA.receive_keyload(&resync_link).await?,
+        "members should access the resync"
+    );
+    let (next_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        subscriberC.receive_keyload(&next_link).await?,
+        "resynced member should access the following keyloads"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_key_tree_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(key_tree_example(transport)).unwrap();
+}
+
+/// Recipients of anonymous keyloads find their slot by trial decryption.
+pub async fn anonymous_keyload_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    use iota_streams_core::psk::{
+        psk_from_seed,
+        pskid_from_psk,
+    };
+
+    let mut author = Author::new("AUTHOR9ANON9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9ANON9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9ANON9SEED", transport.clone());
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9ANON9SEED", transport);
+    author.set_anonymous_keyloads(true);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    subscriberC.receive_announcement(&announcement_link).await?;
+    ensure!(
+        subscriberA.has_anonymous_keyloads(),
+        "option should be learnt from the announcement"
+    );
+
+    let psk = psk_from_seed("An anonymous pre shared key".as_bytes());
+    let pskid = pskid_from_psk(&psk);
+    author.store_psk(pskid, psk)?;
+    subscriberC.store_psk(pskid, psk)?;
+
+    let idA = Identifier::from(*subscriberA.get_public_key());
+    let idC = Identifier::from(pskid);
+    let (keyload_link, _) = author.send_keyload(&announcement_link, vec![&idA, &idC]).await?;
+    ensure!(
+        subscriberA.receive_keyload(&keyload_link).await?,
+        "recipient should find its slot"
+    );
+    ensure!(
+        subscriberC.receive_keyload(&keyload_link).await?,
+        "pre shared key holder should find its slot"
+    );
+    ensure!(
+        !subscriberB.receive_keyload(&keyload_link).await?,
+        "subscriber without a slot should not access keyload"
+    );
+    let known = |subscriber: &Subscriber<T>, id: &Identifier| -> Result<bool> {
+        let id = hex::encode(id.to_bytes());
+        Ok(subscriber.fetch_state()?.iter().any(|(known, _)| *known == id))
+    };
+    ensure!(
+        known(&subscriberA, &idC)? && known(&subscriberC, &idA)?,
+        "recipients should learn the other recipients from the masked identifiers"
+    );
+    ensure!(
+        !known(&subscriberB, &idC)?,
+        "subscriber without a slot should not learn the recipients"
+    );
+
+    // Key updates of the key tree are anonymous as well
+    author.add_tree_member(&idA)?;
+    let (tree_link, _) = author.send_keyload_for_tree(&announcement_link).await?;
+    ensure!(
+        subscriberA.receive_keyload(&tree_link).await?,
+        "tree member should access keyload"
+    );
+    ensure!(
+        !subscriberB.receive_keyload(&tree_link).await?,
+        "subscriber outside of the tree should not access keyload"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_anonymous_keyload_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(anonymous_keyload_example(transport)).unwrap();
+}
+
+/// In ratchet mode the states packets are linked to are erased once the packets are committed.
+pub async fn ratchet_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9RATCHET9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9RATCHET9SEED", transport.clone());
+    author.set_ratchet(true);
+    subscriber.set_ratchet(true);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (first_link, _) = author
+        .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (second_link, _) = author
+        .send_tagged_packet(&first_link, &public_payload, &masked_payload)
+        .await?;
+    ensure!(
+        author
+            .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+            .await
+            .is_err(),
+        "packets should not be linked to an erased state"
+    );
+
+    let (_, unwrapped) = subscriber.receive_tagged_packet(&first_link).await?;
+    ensure!(unwrapped == masked_payload, "expected masked payload");
+    let (_, unwrapped) = subscriber.receive_tagged_packet(&second_link).await?;
+    ensure!(unwrapped == masked_payload, "expected masked payload");
+    ensure!(
+        subscriber.receive_tagged_packet(&first_link).await.is_err(),
+        "state of the keyload should be erased"
+    );
+
+    // Branches of multi branch channels share their keyload, which is thus kept
+    let mut author = Author::new("AUTHOR9MULTI9RATCHET9SEED", ChannelType::MultiBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9MULTI9RATCHET9SEED", transport);
+    author.set_ratchet(true);
+    subscriber.set_ratchet(true);
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+    let (author_link, _) = author
+        .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (subscriber_link, _) = subscriber
+        .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (_, unwrapped) = author.receive_tagged_packet(&subscriber_link).await?;
+    ensure!(unwrapped == masked_payload, "branch of the subscriber should be read");
+    let (_, unwrapped) = subscriber.receive_tagged_packet(&author_link).await?;
+    ensure!(unwrapped == masked_payload, "branch of the author should be read");
+    Ok(())
+}
+
+#[test]
+fn run_ratchet_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(ratchet_example(transport)).unwrap();
+}
+
+/// Subscribers follow the chain of rotations of the Author key.
+pub async fn key_rotation_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9ROTATION9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9ROTATION9SEED", transport.clone());
+    let announced_pk = *author.get_public_key();
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    let (packet_link, _) = author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let (rotation_link, _) = author.send_key_rotation(&packet_link, "AUTHOR9ROTATED9SEED").await?;
+    let rotated_pk = *author.get_public_key();
+    ensure!(rotated_pk != announced_pk, "author key should be rotated");
+    let (keyload_link, _) = author.send_keyload_for_everyone(&rotation_link).await?;
+    author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+
+    // Fresh sync from the announcement
+    let mut signed_packets = 0;
+    loop {
+        let msgs = subscriber.fetch_next_msgs().await;
+        if msgs.is_empty() {
+            break;
+        }
+        signed_packets += msgs
+            .iter()
+            .filter(|msg| matches!(msg.body, MessageContent::SignedPacket { .. }))
+            .count();
+    }
+    ensure!(
+        signed_packets == 2,
+        "packets on both sides of the rotation should be read"
+    );
+    ensure!(
+        subscriber.author_public_key() == Some(&rotated_pk),
+        "subscriber should follow the rotation"
+    );
+    ensure!(
+        subscriber.author_key_history() == [announced_pk],
+        "announced key should be recorded"
+    );
+    let e = subscriber.receive_key_rotation(&rotation_link).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(KeyRotationSignerMismatch(..))),
+        "rotation should be signed with the current author key"
+    );
+
+    // Subscribers that only read the announcement encrypt for the announced key
+    let mut late_subscriber = Subscriber::new("SUBSCRIBER9LATE9ROTATION9SEED", transport.clone());
+    late_subscriber.receive_announcement(&announcement_link).await?;
+    let subscribe_link = late_subscriber.send_subscribe(&announcement_link).await?;
+    author.receive_subscribe(&subscribe_link).await?;
+
+    // The rotated key cannot be derived without its seed
+    let e = Author::recover(
+        "AUTHOR9ROTATION9SEED",
+        &announcement_link,
+        ChannelType::SingleBranch,
+        transport.clone(),
+    )
+    .await
+    .err();
+    ensure!(
+        matches!(
+            e.as_ref().and_then(|e| e.downcast_ref::<Errors>()),
+            Some(KeyRotationKeyNotFound(..))
+        ),
+        "recovery should fail without the rotation seeds"
+    );
+
+    // The recovered Author signs with the rotated key
+    let mut recovered = Author::recover_rotated(
+        "AUTHOR9ROTATION9SEED",
+        &["AUTHOR9ROTATED9SEED"],
+        &announcement_link,
+        ChannelType::SingleBranch,
+        transport,
+    )
+    .await?;
+    ensure!(
+        *recovered.get_public_key() == rotated_pk,
+        "recovered author should take up the rotated key"
+    );
+    recovered
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let msgs = subscriber.fetch_next_msgs().await;
+    ensure!(
+        msgs.iter()
+            .any(|msg| matches!(msg.body, MessageContent::SignedPacket { pk, .. } if pk == rotated_pk)),
+        "packet of the recovered author should be read"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_key_rotation_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(key_rotation_example(transport)).unwrap();
+}
+
+/// Packets of publishers other than the Author are only accepted while certified.
+pub async fn publisher_certificate_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9CERTIFICATE9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9CERTIFICATE9SEED", transport);
+    author.set_certified_publishers(true);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    ensure!(
+        subscriber.requires_certificates(),
+        "option should be learned from the announcement"
+    );
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (signed_link, _) = subscriber
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    let e = subscriber
+        .send_tagged_packet(&keyload_link, &public_payload, &masked_payload)
+        .await
+        .unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(TaggedPacketNotCertifiable)),
+        "tagged packets cannot be certified"
+    );
+    let e = author.receive_signed_packet(&signed_link).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(PublisherNotCertified(..))),
+        "packet of an uncertified publisher should be rejected"
+    );
+
+    let id = Identifier::from(*subscriber.get_public_key());
+    let (certificate_link, _) = author
+        .send_publisher_certificate(&keyload_link, Certificate::new(id, SCOPE_SIGNED_PACKETS, 0))
+        .await?;
+    let certificate = subscriber.receive_publisher_certificate(&certificate_link).await?;
+    ensure!(
+        certificate.publisher == id && certificate.scope == SCOPE_SIGNED_PACKETS,
+        "subscriber should read its certificate"
+    );
+    let (_, unwrapped_public, _) = author.receive_signed_packet(&signed_link).await?;
+    ensure!(unwrapped_public == public_payload, "expected public payload");
+
+    // Expired certificates are rejected as well, and so are expiring ones at an unknown time
+    let expiring = Certificate::new(id, SCOPE_SIGNED_PACKETS, u64::MAX);
+    ensure!(
+        !expiring.allows(SCOPE_SIGNED_PACKETS, 0),
+        "unknown time should be out of an expiring certificate"
+    );
+    author
+        .send_publisher_certificate(&certificate_link, Certificate::new(id, SCOPE_SIGNED_PACKETS, 1))
+        .await?;
+    ensure!(
+        author.receive_signed_packet(&signed_link).await.is_err(),
+        "certificate should be expired"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_publisher_certificate_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(publisher_certificate_example(transport)).unwrap();
+}
+
+/// Channels of one seed are told apart by their application instance.
+pub async fn channel_manager_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut manager = ChannelManager::new("AUTHOR9MANAGER9SEED", transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9MANAGER9SEED", transport.clone());
+
+    let first_link = manager
+        .create_channel(0, ChannelType::SingleBranch)?
+        .send_announce()
+        .await?;
+    let second_link = manager
+        .create_channel(1, ChannelType::SingleBranch)?
+        .send_announce()
+        .await?;
+    ensure!(
+        first_link.appinst != second_link.appinst,
+        "channels should have distinct application instances"
+    );
+    let e = manager.create_channel(1, ChannelType::SingleBranch).unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(ChannelAlreadyManaged(1))),
+        "channel index should not be reused"
+    );
+
+    subscriber.receive_announcement(&second_link).await?;
+    let subscribe_link = subscriber.send_subscribe(&second_link).await?;
+    let channel = manager.channel_mut(1).unwrap();
+    channel.receive_subscribe(&subscribe_link).await?;
+    let (keyload_link, _) = channel.send_keyload_for_everyone(&second_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (packet_link, _) = subscriber
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+
+    // The state of all channels survives the bundle
+    let bundle = manager.export("BUNDLE9PASSWORD").await?;
+    let mut manager = ChannelManager::import(&bundle, "BUNDLE9PASSWORD", transport.clone()).await?;
+    ensure!(manager.channel_indices() == vec![0, 1], "channels should be imported");
+    let msg = manager.receive_msg(&packet_link).await?;
+    ensure!(
+        matches!(msg.body, MessageContent::SignedPacket { .. }),
+        "packet should be routed to its channel"
+    );
+
+    let mut stranger = Author::new("STRANGER9SEED", ChannelType::SingleBranch, transport);
+    let stranger_link = stranger.send_announce().await?;
+    let e = manager.receive_msg(&stranger_link).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(ChannelNotManaged(..))),
+        "messages of other channels should not be routed"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_channel_manager_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(channel_manager_example(transport)).unwrap();
+}
+
+/// Whether two states hold the same cursors, whatever their order.
+fn same_cursors(mut state: Vec<(String, Cursor<Address>)>, mut other: Vec<(String, Cursor<Address>)>) -> bool {
+    state.sort_by(|a, b| a.0.cmp(&b.0));
+    other.sort_by(|a, b| a.0.cmp(&b.0));
+    state.len() == other.len()
+        && state
+            .iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.0 == b.0 && a.1.link == b.1.link && a.1.seq_no == b.1.seq_no)
+}
+
+/// The state of an Author is recovered from its seed by walking the channel.
+pub async fn author_recovery_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9RECOVERY9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9RECOVERY9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9RECOVERY9SEED", transport.clone());
+
+    let (announcement_link, subscribe_links) =
+        announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    let subscribeB_link = subscribe_links[1];
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriberA.receive_keyload(&keyload_link).await?;
+    subscriberB.receive_keyload(&keyload_link).await?;
+    let (packet_link, _) = author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    subscriberA.sync_state().await;
+    subscriberA
+        .send_tagged_packet(&packet_link, &public_payload, &masked_payload)
+        .await?;
+    let unsubscribe_link = subscriberB.send_unsubscribe(&subscribeB_link).await?;
+    author.receive_unsubscribe(&unsubscribe_link).await?;
+    author.sync_state().await;
+
+    let recovered = Author::recover(
+        "AUTHOR9RECOVERY9SEED",
+        &announcement_link,
+        ChannelType::SingleBranch,
+        transport.clone(),
+    )
+    .await?;
+    ensure!(
+        same_cursors(author.fetch_state()?, recovered.fetch_state()?),
+        "recovered cursors should match"
+    );
+    ensure!(
+        recovered.unsubscriptions().len() == 1,
+        "unsubscription should be recovered"
+    );
+
+    let e = Author::recover("OTHER9SEED", &announcement_link, ChannelType::SingleBranch, transport)
+        .await
+        .unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(AnnouncementRecoveryMismatch(..))),
+        "announcement of another seed should be reported"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_author_recovery_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(author_recovery_example(transport)).unwrap();
+}
+
+/// A Subscriber recovered from its seed reads the channel and unsubscribes like the lost instance.
+pub async fn subscriber_recovery_example<T: Transport + Clone>(mut transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9SUBRECOVERY9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9SUBRECOVERY9SEED", transport.clone());
+
+    let (announcement_link, subscribe_links) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let subscribe_link = subscribe_links[0];
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    subscriber.sync_state().await;
+
+    let mut recovered = Subscriber::recover(
+        "SUBSCRIBER9SUBRECOVERY9SEED",
+        &announcement_link,
+        &subscribe_link,
+        transport.clone(),
+    )
+    .await?;
+    ensure!(
+        same_cursors(subscriber.fetch_state()?, recovered.fetch_state()?),
+        "recovered cursors should match"
+    );
+
+    let unsubscribe_link = recovered.send_unsubscribe(&subscribe_link).await?;
+    author.receive_unsubscribe(&unsubscribe_link).await?;
+    ensure!(
+        author.unsubscriptions().len() == 1,
+        "unsubscription of the recovered subscriber should be accepted"
+    );
+
+    let e = Subscriber::recover("OTHER9SEED", &announcement_link, &subscribe_link, transport.clone())
+        .await
+        .unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(SubscribeRecoveryMismatch(..))),
+        "subscribe message of another seed should be reported"
+    );
+
+    // A message that cannot be handled fails the recovery instead of ending it
+    let author_id = Identifier::from(*author.get_public_key());
+    let packet_link = author
+        .gen_next_msg_ids(false)
+        .into_iter()
+        .find(|(id, _)| *id == author_id)
+        .map(|(_, cursor)| cursor.link)
+        .expect("author should have a cursor");
+    let spam = BinaryMessage::new(packet_link, keyload_link, BinaryBody::from(vec![0_u8; 64]));
+    transport.send_message(&Message::new(spam)).await?;
+    ensure!(
+        Subscriber::recover(
+            "SUBSCRIBER9SUBRECOVERY9SEED",
+            &announcement_link,
+            &subscribe_link,
+            transport
+        )
+        .await
+        .is_err(),
+        "unreadable message should fail the recovery"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_subscriber_recovery_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(subscriber_recovery_example(transport)).unwrap();
+}
+
+/// Messages spammed at the link of a genuine message are rejected in favour of the genuine one.
+pub async fn duplicate_message_example<T: Transport + Clone>(mut transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9DUPLICATE9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9DUPLICATE9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9DUPLICATE9SEED", transport.clone());
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriberA.receive_keyload(&keyload_link).await?;
+
+    // Spam is published at the link of the next packet of the Author before the packet itself
+    let author_id = Identifier::from(*author.get_public_key());
+    let packet_link = author
+        .gen_next_msg_ids(false)
+        .into_iter()
+        .find(|(id, _)| *id == author_id)
+        .map(|(_, cursor)| cursor.link)
+        .expect("author should have a cursor");
+    let spam = BinaryMessage::new(packet_link, keyload_link, BinaryBody::from(vec![0_u8; 64]));
+    transport.send_message(&Message::new(spam)).await?;
+
+    let public_payload = Bytes("PUBLICPAYLOAD".as_bytes().to_vec());
+    let masked_payload = Bytes("MASKEDPAYLOAD".as_bytes().to_vec());
+    let (link, _) = author
+        .send_signed_packet(&keyload_link, &public_payload, &masked_payload)
+        .await?;
+    ensure!(link == packet_link, "packet should be published at the spammed link");
+
+    let (_, unwrapped_public, unwrapped_masked) = subscriberA.receive_signed_packet(&packet_link).await?;
+    ensure!(
+        public_payload == unwrapped_public && masked_payload == unwrapped_masked,
+        "genuine packet should be accepted"
+    );
+    let rejected = subscriberA.take_rejected_messages();
+    ensure!(
+        rejected.len() == 1 && rejected[0].link == packet_link,
+        "spam should be rejected"
+    );
+    ensure!(
+        subscriberA.take_rejected_messages().is_empty(),
+        "rejections are reported once"
+    );
+
+    let msgs = subscriberB.fetch_next_msgs().await;
+    ensure!(msgs.iter().any(|msg| msg.link == keyload_link), "expected keyload");
+    let msgs = subscriberB.fetch_next_msgs().await;
+    ensure!(
+        msgs.iter().any(|msg| msg.link == packet_link
+            && matches!(&msg.body, MessageContent::SignedPacket { masked_payload: m, .. } if *m == masked_payload)),
+        "genuine packet should be fetched"
+    );
+    ensure!(
+        subscriberB.take_rejected_messages().len() == 1,
+        "spam should be rejected"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_duplicate_message_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(duplicate_message_example(transport)).unwrap();
+}
+
+/// Frames spammed at the link of a continuation frame are rejected in favour of the genuine frame.
+pub async fn duplicate_frame_example<T: Transport + Clone>(mut transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9FRAME9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9FRAME9SEED", transport.clone());
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    // A well-formed frame is published at the link of the second frame of the next packet of the
+    // Author before the packet itself
+    let author_id = Identifier::from(*author.get_public_key());
+    let packet_link = author
+        .gen_next_msg_ids(false)
+        .into_iter()
+        .find(|(id, _)| *id == author_id)
+        .map(|(_, cursor)| cursor.link)
+        .expect("author should have a cursor");
+    let mut link_gen = LinkGen::default();
+    link_gen.reset(announcement_link);
+    let frame_link = link_gen.frame_link_from(&packet_link, 2);
+    let mut spam = PCF::new_inter_frame()
+        .with_payload_frame_num(2)?
+        .wrap_frame::<DefaultF>()?;
+    spam.extend_from_slice(&[0_u8; 64]);
+    let spam = BinaryMessage::new(frame_link, packet_link, BinaryBody::from(spam));
+    transport.send_message(&Message::new(spam)).await?;
+
+    let payload = Bytes(vec![b'F'; 3 * PAYLOAD_BYTES]);
+    author.send_signed_packet(&keyload_link, &payload, &payload).await?;
+    let (_, public, masked) = subscriber.receive_signed_packet(&packet_link).await?;
+    ensure!(
+        public == payload && masked == payload,
+        "packet should be joined with the genuine frame"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_duplicate_frame_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(duplicate_frame_example(transport)).unwrap();
+}
+
+/// Spam cached ahead of a genuine message is refreshed once it is rejected, so that the genuine
+/// message published later by another party is still received.
+pub async fn stale_cache_example() -> Result<()> {
+    let mut tangle = new_shared_transport(BucketTransport::new());
+    let transport = new_shared_transport(CachingTransport::new(tangle.clone(), 16));
+    let mut author = Author::new("AUTHOR9STALE9SEED", ChannelType::SingleBranch, tangle.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9STALE9SEED", transport.clone());
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    subscriber.receive_keyload(&keyload_link).await?;
+
+    let author_id = Identifier::from(*author.get_public_key());
+    let packet_link = author
+        .gen_next_msg_ids(false)
+        .into_iter()
+        .find(|(id, _)| *id == author_id)
+        .map(|(_, cursor)| cursor.link)
+        .expect("author should have a cursor");
+    let spam = BinaryMessage::new(packet_link, keyload_link, BinaryBody::from(vec![0_u8; 64]));
+    tangle.send_message(&Message::new(spam)).await?;
+    ensure!(
+        subscriber.receive_tagged_packet(&packet_link).await.is_err(),
+        "spam alone should be rejected"
+    );
+
+    let payload = Bytes("STALEPAYLOAD".as_bytes().to_vec());
+    author.send_tagged_packet(&keyload_link, &payload, &payload).await?;
+    let (public, masked) = subscriber.receive_tagged_packet(&packet_link).await?;
+    ensure!(
+        public == payload && masked == payload,
+        "genuine packet should be received past the cached spam"
+    );
+    ensure!(
+        subscriber.take_rejected_messages().len() == 1,
+        "spam should be rejected once the genuine packet is found"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_stale_cache_scenario() {
+    smol::block_on(stale_cache_example()).unwrap();
+}
+
+/// Revoking a subscriber rekeys the channel and reports the branches it can still read.
+pub async fn rekey_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9REKEY9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9REKEY9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9REKEY9SEED", transport.clone());
+    author.set_rekey_on_removal(true);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    ensure!(
+        author.default_link_to() == Some(announcement_link),
+        "expected announcement"
+    );
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    ensure!(author.default_link_to() == Some(keyload_link), "expected keyload");
+    ensure!(author.compromised_branches().is_empty(), "nothing is revoked yet");
+
+    let (rekey_link, _) = author
+        .revoke_subscriber(*subscriberA.get_public_key())
+        .await?
+        .expect("revoking should send a fresh keyload");
+    ensure!(
+        author.default_link_to() == Some(rekey_link),
+        "default link should move to the rekey"
+    );
+    ensure!(
+        author.compromised_branches() == vec![(keyload_link, vec![Identifier::from(*subscriberA.get_public_key())])],
+        "keyload including revoked subscriber should be reported"
+    );
+
+    subscriberA.receive_keyload(&keyload_link).await?;
+    subscriberB.receive_keyload(&keyload_link).await?;
+    ensure!(
+        !subscriberA.receive_keyload(&rekey_link).await?,
+        "revoked subscriber should not access the rekey"
+    );
+    ensure!(
+        subscriberB.receive_keyload(&rekey_link).await?,
+        "remaining subscriber should access the rekey"
+    );
+
+    // Removing a subscriber without revoking it does not publish anything
+    author.remove_subscriber(*subscriberB.get_public_key())?;
+    let latest_link = author.default_link_to();
+    ensure!(
+        latest_link == Some(rekey_link),
+        "removing a subscriber should not send a fresh keyload"
+    );
+
+    // Pending subscribers never received a keyload, leaving does not rekey
+    author.set_subscription_approval(true);
+    let mut subscriberC = Subscriber::new("SUBSCRIBERC9REKEY9SEED", transport);
+    subscriberC.receive_announcement(&announcement_link).await?;
+    let subscribe_link = subscriberC.send_subscribe(&announcement_link).await?;
+    author.receive_subscribe(&subscribe_link).await?;
+    let unsubscribe_link = subscriberC.send_unsubscribe(&subscribe_link).await?;
+    author.receive_unsubscribe(&unsubscribe_link).await?;
+    ensure!(
+        author.default_link_to() == latest_link,
+        "leaving a pending subscription should not rekey"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_rekey_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(rekey_example(transport)).unwrap();
+}
+
+/// Unsubscriptions must be linked to the subscribe message of the subscriber.
+pub async fn unsubscribe_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9UNSUBSCRIBE9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9UNSUBSCRIBE9SEED", transport);
+
+    let (announcement_link, subscribe_links) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let subscribe_link = subscribe_links[0];
+
+    let unsubscribe_link = subscriber.send_unsubscribe(&announcement_link).await?;
+    let e = author.receive_unsubscribe(&unsubscribe_link).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(UnsubscribeLinkMismatch(..))),
+        "unsubscribe not linked to the subscribe message should be rejected"
+    );
+    ensure!(author.unsubscriptions().is_empty(), "subscriber should not have left");
+
+    let unsubscribe_link = subscriber.send_unsubscribe(&subscribe_link).await?;
+    author.receive_unsubscribe(&unsubscribe_link).await?;
+    let unsubscriptions = author.unsubscriptions();
+    ensure!(
+        unsubscriptions.len() == 1 && unsubscriptions[0].0 == *subscriber.get_public_key(),
+        "subscriber should have left"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_unsubscribe_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(unsubscribe_example(transport)).unwrap();
+}
+
+/// Announcements are only accepted from the pinned author key.
+pub async fn pinned_author_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9PINNED9SEED", ChannelType::SingleBranch, transport.clone());
+    let impostor = Author::new("IMPOSTOR9PINNED9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9PINNED9SEED", transport);
+    let announcement_link = author.send_announce().await?;
+
+    subscriber.pin_author_public_key(*impostor.get_public_key());
+    let e = subscriber.receive_announcement(&announcement_link).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(AuthorSigPkMismatch(..))),
+        "announcement by another key should be rejected"
+    );
+
+    subscriber.pin_author_public_key(*author.get_public_key());
+    subscriber.receive_announcement(&announcement_link).await?;
+    ensure!(
+        subscriber.author_public_key() == Some(author.get_public_key()),
+        "subscriber should trust the pinned key"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_pinned_author_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(pinned_author_example(transport)).unwrap();
+}
+
+/// Only accepted subscribers are included in keyloads for everyone.
+pub async fn subscription_approval_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    let mut author = Author::new("AUTHOR9APPROVAL9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriberA = Subscriber::new("SUBSCRIBERA9APPROVAL9SEED", transport.clone());
+    let mut subscriberB = Subscriber::new("SUBSCRIBERB9APPROVAL9SEED", transport.clone());
+    author.set_subscription_approval(true);
+
+    let (announcement_link, subscribe_links) =
+        announce_and_subscribe(&mut author, &mut [&mut subscriberA, &mut subscriberB]).await?;
+    let (subscribeA_link, subscribeB_link) = (subscribe_links[0], subscribe_links[1]);
+
+    // Pending subscriptions survive export
+    let authordump = author.export("pwdAuthor").await?;
+    let mut author = Author::import(authordump.as_ref(), "pwdAuthor", transport).await?;
+    let pending = author.pending_subscriptions();
+    ensure!(
+        pending
+            == vec![
+                (*subscriberA.get_public_key(), subscribeA_link),
+                (*subscriberB.get_public_key(), subscribeB_link),
+            ],
+        "expected both subscriptions to be pending"
+    );
+
+    author.accept_subscription(subscriberA.get_public_key())?;
+    author.reject_subscription(subscriberB.get_public_key())?;
+    ensure!(
+        author.pending_subscriptions().is_empty(),
+        "no subscription should be pending"
+    );
+    ensure!(
+        author.accept_subscription(subscriberB.get_public_key()).is_err(),
+        "rejected subscription cannot be accepted"
+    );
+
+    let (keyload_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+    ensure!(
+        subscriberA.receive_keyload(&keyload_link).await?,
+        "accepted subscriber should access keyload"
+    );
+    ensure!(
+        !subscriberB.receive_keyload(&keyload_link).await?,
+        "rejected subscriber should not access keyload"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_subscription_approval_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(subscription_approval_example(transport)).unwrap();
+}
+
+/// Stream the messages of a channel until caught up, then stop a polling stream.
+#[cfg(feature = "std")]
+pub async fn stream_example<T: Transport + Clone>(transport: T) -> Result<()> {
+    use futures::{
+        FutureExt as _,
+        StreamExt as _,
+    };
+    use iota_streams_core::prelude::{
+        Box,
+        Vec,
+    };
+
+    let mut author = Author::new("AUTHOR9STREAM9SEED", ChannelType::SingleBranch, transport.clone());
+    let mut subscriber = Subscriber::new("SUBSCRIBER9STREAM9SEED", transport);
+
+    let (announcement_link, _) = announce_and_subscribe(&mut author, &mut [&mut subscriber]).await?;
+    let (mut last_link, _) = author.send_keyload_for_everyone(&announcement_link).await?;
+
+    let payloads = ["STREAM1", "STREAM2", "STREAM3"];
+    for payload in payloads.iter() {
+        let payload = Bytes(payload.as_bytes().to_vec());
+        last_link = author.send_tagged_packet(&last_link, &payload, &payload).await?.0;
+    }
+
+    let msgs: Vec<_> = subscriber.messages(MessageStreamOptions::default()).collect().await;
+    ensure!(msgs.len() == 1 + payloads.len(), "expected keyload and all packets");
+    for (msg, payload) in msgs[1..].iter().zip(payloads.iter()) {
+        match msg {
+            Ok(UnwrappedMessage {
+                body: MessageContent::TaggedPacket { masked_payload, .. },
+                ..
+            }) => ensure!(masked_payload.0 == payload.as_bytes(), "bad streamed payload"),
+            _ => ensure!(false, "expected tagged packet"),
+        }
+    }
+
+    // Polling stream waits for the next round once no new message is found, until stopped
+    let stop = StopHandle::new();
+    let options = MessageStreamOptions::default()
+        .with_polling_interval(core::time::Duration::from_secs(60))
+        .with_stop_handle(stop.clone());
+    let mut stream = Box::pin(subscriber.messages(options));
+    let payload = Bytes("STREAM4".as_bytes().to_vec());
+    author.send_tagged_packet(&last_link, &payload, &payload).await?;
+    match stream.next().await {
+        Some(Ok(UnwrappedMessage {
+            body: MessageContent::TaggedPacket { masked_payload, .. },
+            ..
+        })) => ensure!(masked_payload.0 == payload.0, "bad polled payload"),
+        _ => ensure!(false, "expected polled packet"),
+    }
+    ensure!(
+        stream.next().now_or_never().is_none(),
+        "stream should wait for the next round"
+    );
+    stop.stop();
+    ensure!(
+        stream.next().await.is_none(),
+        "stream should end once stopped without waiting for the polling interval"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn run_stream_scenario() {
+    let transport = new_shared_transport(BucketTransport::new());
+    smol::block_on(stream_example(transport)).unwrap();
+}
+
 #[test]
 fn run_basic_scenario() {
     let transport = iota_streams_app::transport::new_shared_transport(crate::api::tangle::BucketTransport::new());
     assert!(dbg!(smol::block_on(example(transport)).is_ok()));
 }
+
+#[test]
+fn run_basic_scenario_file_transport() {
+    let dir = std::env::temp_dir().join(format!("iota-streams-file-transport-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let transport = crate::api::tangle::FileTransport::new(&dir);
+    smol::block_on(example(transport)).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -4,10 +4,19 @@ use iota_streams_app::{
         HasLink as _,
         LinkGenerator,
     },
+    transport::{
+        is_message_not_found,
+        tangle::{
+            timestamp_now,
+            PAYLOAD_BYTES,
+        },
+    },
 };
 use iota_streams_core::{
     err,
     prelude::{
+        HashMap,
+        String,
         ToString,
         Vec,
     },
@@ -18,10 +27,14 @@ use iota_streams_core::{
     },
     try_or,
     unwrap_or_break,
+    Error,
     Errors::{
+        AnnouncementRecoveryMismatch,
         ChannelDuplication,
         ChannelNotSingleDepth,
+        MessageLinkNotFoundInTangle,
         NoPreviousMessage,
+        SubscribeRecoveryMismatch,
         UnknownMsgType,
         UserNotRegistered,
     },
@@ -36,13 +49,109 @@ use crate::{
 
 type UserImp = api::user::User<DefaultF, Address, LinkGen, LinkStore, KeyStore>;
 
+/// Handle the messages published at `$link` in turn with `$handle` until one of them is accepted.
+/// Once every candidate failed, the link is refreshed in case the transport served stale candidates,
+/// and the candidates not tried yet are handled in turn. Failed candidates are recorded with
+/// [`User::reject_candidate`] but for the last one, whose error is returned if none is accepted.
+macro_rules! handle_candidates {
+    ($user:ident, $link:expr, |$msg:ident| $handle:expr) => {{
+        let link: &Address = $link;
+        let mut candidates = $user.transport.recv_messages(link).await?;
+        let mut failed: Vec<(Message, Error)> = Vec::new();
+        let mut accepted = None;
+        let mut refreshed = false;
+        loop {
+            for $msg in candidates {
+                if failed.iter().any(|(tried, _)| tried.binary == $msg.binary) {
+                    continue;
+                }
+                let candidate = $msg.clone();
+                match $handle {
+                    Err(error) => failed.push((candidate, error)),
+                    result => {
+                        accepted = Some(result);
+                        break;
+                    }
+                }
+            }
+            if accepted.is_some() || refreshed || failed.is_empty() {
+                break;
+            }
+            refreshed = true;
+            candidates = match $user.transport.refresh_messages(link).await {
+                Ok(candidates) => candidates,
+                // The error of the candidates prevails over the one of the refresh
+                Err(_) => break,
+            };
+        }
+        let outcome = match accepted {
+            Some(result) => result,
+            None => match failed.pop() {
+                Some((_, error)) => Err(error),
+                None => err!(MessageLinkNotFoundInTangle(link.to_string())),
+            },
+        };
+        for (candidate, error) in failed {
+            $user.reject_candidate(link, candidate, error);
+        }
+        outcome
+    }};
+}
+
+/// Handle the message `$msg` joined with each combination of the messages published at the links of
+/// its continuation frames in turn with `$handle` until one of them is accepted, see
+/// [`User::recv_frames`]. The error of the last one is returned if none is accepted.
+macro_rules! handle_frames {
+    ($user:ident, $msg:expr, |$joined:ident| $handle:expr) => {{
+        let msg: Message = $msg;
+        let link = msg.binary.link.to_string();
+        match $user.recv_frames(msg).await {
+            Ok(joined) => {
+                let mut outcome = None;
+                for $joined in joined {
+                    let result = $handle;
+                    let accepted = result.is_ok();
+                    outcome = Some(result);
+                    if accepted {
+                        break;
+                    }
+                }
+                outcome.unwrap_or_else(|| err!(MessageLinkNotFoundInTangle(link)))
+            }
+            Err(e) => Err(e),
+        }
+    }};
+}
+
+/// Signing key pair of a user generated from `seed`.
+fn sig_keypair(seed: &str) -> ed25519::Keypair {
+    let nonce = "TANGLEUSERNONCE".as_bytes().to_vec();
+    ed25519::Keypair::generate(&mut prng::Rng::new(
+        prng::from_seed("IOTA Streams Channels user sig keypair", seed),
+        nonce,
+    ))
+}
+
 const ENCODING: &str = "utf-8";
-const PAYLOAD_LENGTH: usize = 32_000;
+/// Messages are not padded by default.
+const PAYLOAD_LENGTH: usize = 0;
+/// Number of rejected messages kept until they are taken.
+pub const MAX_REJECTED_MESSAGES: usize = 64;
+/// Maximum number of combinations of the messages published at the links of the continuation frames
+/// of a message that are joined to it.
+pub const MAX_JOINED_FRAMES: usize = 16;
 
 /// Baseline User api object. Contains the api user implementation as well as the transport object
 pub struct User<Trans> {
     pub user: UserImp,
     pub transport: Trans,
+    /// Include the time of publishing in sent signed and tagged packets.
+    pub sender_timestamps: bool,
+    /// Publish a fresh keyload for the remaining recipients when a subscriber is removed.
+    pub rekey_on_removal: bool,
+    /// Messages rejected among several messages published at the same link, at most
+    /// [`MAX_REJECTED_MESSAGES`].
+    pub rejected_messages: Vec<RejectedMessage>,
 }
 
 impl<Trans> User<Trans> {
@@ -54,20 +163,65 @@ impl<Trans> User<Trans> {
     /// * `transport` - Transport object used for sending and receiving
     pub fn new(seed: &str, channel_type: ChannelType, transport: Trans) -> Self {
         let nonce = "TANGLEUSERNONCE".as_bytes().to_vec();
-        let user = UserImp::gen(
+        let mut user = UserImp::gen(
             prng::from_seed("IOTA Streams Channels user sig keypair", seed),
             nonce,
             channel_type,
             ENCODING.as_bytes().to_vec(),
             PAYLOAD_LENGTH,
         );
-        Self { user, transport }
+        user.frame_length = PAYLOAD_BYTES;
+        Self {
+            user,
+            transport,
+            sender_timestamps: false,
+            rekey_on_removal: false,
+            rejected_messages: Vec::new(),
+        }
     }
 
     pub fn get_transport(&self) -> &Trans {
         &self.transport
     }
 
+    /// Pad signed and tagged packets, and keyloads if `pad_keyloads` is set, to `length` bytes.
+    /// Zero length disables padding.
+    pub fn set_uniform_payload_length(&mut self, length: usize, pad_keyloads: bool) {
+        self.user.uniform_payload_length = length;
+        self.user.pad_keyloads = pad_keyloads;
+    }
+
+    /// Include the time of publishing, as asserted by this user, in sent signed and tagged packets.
+    pub fn set_sender_timestamps(&mut self, enabled: bool) {
+        self.sender_timestamps = enabled;
+    }
+
+    /// Select the codec encoding payloads of sent packets by name, eg. `raw` or `deflate`.
+    pub fn set_message_encoding(&mut self, encoding: &str) -> Result<()> {
+        self.user.payload_codecs.by_name(encoding.as_bytes())?;
+        self.user.message_encoding = encoding.as_bytes().to_vec();
+        Ok(())
+    }
+
+    /// Publish a fresh keyload for everyone when a subscriber is revoked or unsubscribes [Author].
+    pub fn set_rekey_on_removal(&mut self, enabled: bool) {
+        self.rekey_on_removal = enabled;
+    }
+
+    /// Hold received subscriptions until they are accepted instead of registering subscribers
+    /// right away [Author].
+    pub fn set_subscription_approval(&mut self, required: bool) {
+        self.user.require_subscription_approval = required;
+    }
+
+    /// Erase the state of a message once a signed or tagged packet linked to it is sent or
+    /// received. Earlier messages of a branch are then protected from a compromised user state, but
+    /// each packet must be linked to the latest message of its branch. Only single branch channels
+    /// are ratcheted.
+    pub fn set_ratchet(&mut self, enabled: bool) {
+        self.user.ratchet = enabled;
+    }
+
     // Attributes
 
     /// Fetch the Address (application instance) of the channel.
@@ -85,6 +239,19 @@ impl<Trans> User<Trans> {
         self.user.author_public_key()
     }
 
+    /// Pin the public key the channel Author is expected to announce the channel with [Subscriber].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the Author
+    pub fn pin_author_public_key(&mut self, pk: ed25519::PublicKey) {
+        self.user.pinned_author_sig_pk = Some(pk);
+    }
+
+    /// Former public keys of the Author replaced by key rotations, the announced key first
+    pub fn author_key_history(&self) -> &[ed25519::PublicKey] {
+        self.user.author_key_history()
+    }
+
     /// Return boolean representing the sequencing nature of the channel
     pub fn is_multi_branching(&self) -> bool {
         self.user.is_multi_branching()
@@ -95,6 +262,34 @@ impl<Trans> User<Trans> {
         self.user.is_single_depth()
     }
 
+    /// Return boolean representing whether keyloads of the channel hide their recipients
+    pub fn has_anonymous_keyloads(&self) -> bool {
+        self.user.has_anonymous_keyloads()
+    }
+
+    /// Hide the recipients of keyloads behind anonymous slots that they trial-decrypt. Subscribers
+    /// learn the option from the announcement, so it must be selected before `send_announce`.
+    pub fn set_anonymous_keyloads(&mut self, anonymous: bool) {
+        self.user.set_anonymous_keyloads(anonymous)
+    }
+
+    /// Return boolean representing whether packets are only accepted from certified publishers
+    pub fn requires_certificates(&self) -> bool {
+        self.user.requires_certificates()
+    }
+
+    /// Only accept signed packets from publishers certified by the Author, tagged packets are
+    /// rejected. Subscribers learn the option from the announcement, so it must be selected before
+    /// `send_announce`.
+    pub fn set_certified_publishers(&mut self, required: bool) {
+        self.user.set_certified_publishers(required)
+    }
+
+    /// Latest certificate issued by the Author to a publisher
+    pub fn publisher_certificate(&self, publisher: &Identifier) -> Option<&Certificate> {
+        self.user.publisher_certificate(publisher)
+    }
+
     /// Fetch the user ed25519 public key
     pub fn get_public_key(&self) -> &PublicKey {
         &self.user.sig_kp.public
@@ -173,9 +368,16 @@ impl<Trans> User<Trans> {
         self.user.export(flag, pwd).await
     }
     pub async fn import(bytes: &[u8], flag: u8, pwd: &str, tsp: Trans) -> Result<Self> {
-        UserImp::import(bytes, flag, pwd).await.map(|u| Self {
-            user: u,
+        // States exported before frame lengths were exported keep the frame length of new users
+        let mut user = UserImp::default();
+        user.frame_length = PAYLOAD_BYTES;
+        user.import_state(bytes, flag, pwd).await?;
+        Ok(Self {
+            user,
             transport: tsp,
+            sender_timestamps: false,
+            rekey_on_removal: false,
+            rejected_messages: Vec::new(),
         })
     }
 
@@ -212,6 +414,110 @@ impl<Trans> User<Trans> {
         self.user.remove_subscriber(pk)
     }
 
+    /// Link new messages are attached to by default: the latest keyload published by the user, or the
+    /// announcement if none [Author].
+    pub fn default_link_to(&self) -> Option<Address> {
+        self.user
+            .latest_keyload()
+            .or_else(|| self.user.appinst.as_ref())
+            .copied()
+    }
+
+    /// Links of the keyloads that included recipients removed since, together with those recipients.
+    /// Branches attached to them can still be read by the removed recipients [Author].
+    pub fn compromised_branches(&self) -> Vec<(Address, Vec<Identifier>)> {
+        self.user
+            .compromised_keyloads()
+            .into_iter()
+            .map(|(link, ids)| (*link, ids))
+            .collect()
+    }
+
+    /// Store a named group of keyload recipients, replacing the group with the same name if any
+    /// [Author]
+    ///
+    ///   # Arguments
+    ///   * `name` - Name of the group
+    ///   * `members` - Identifiers of the group members
+    pub fn store_group(&mut self, name: &str, members: Vec<Identifier>) {
+        self.user.store_group(name, members)
+    }
+
+    /// Remove a named group of keyload recipients [Author]
+    pub fn remove_group(&mut self, name: &str) -> Result<()> {
+        self.user.remove_group(name)
+    }
+
+    /// Members of a named group of keyload recipients [Author]
+    pub fn group_members(&self, name: &str) -> Result<Vec<Identifier>> {
+        self.user.group_members(name).map(|members| members.to_vec())
+    }
+
+    /// Names of the stored groups of keyload recipients [Author]
+    pub fn group_names(&self) -> Vec<String> {
+        self.user.group_names().into_iter().map(String::from).collect()
+    }
+
+    /// Add a subscriber or PSK to the key tree shared by group keyloads [Author]
+    ///
+    ///   # Arguments
+    ///   * `id` - Identifier of a known subscriber or stored PSK
+    pub fn add_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        self.user.add_tree_member(id)
+    }
+
+    /// Remove a subscriber or PSK from the key tree [Author]. It cannot read the keyloads sent for the
+    /// tree afterwards.
+    pub fn remove_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        self.user.remove_tree_member(id)
+    }
+
+    /// Members of the key tree [Author]
+    pub fn tree_members(&self) -> Vec<Identifier> {
+        self.user.tree_members().into_iter().copied().collect()
+    }
+
+    /// Subscriptions awaiting approval: subscriber public key and link to the subscribe message
+    pub fn pending_subscriptions(&self) -> Vec<(PublicKey, Address)> {
+        self.user.pending_subscriptions().to_vec()
+    }
+
+    /// Accept a pending subscription, registering its subscriber
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the pending subscriber
+    pub fn accept_subscription(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.accept_subscription(pk)
+    }
+
+    /// Reject a pending subscription, its subscriber is not registered
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the pending subscriber
+    pub fn reject_subscription(&mut self, pk: &PublicKey) -> Result<()> {
+        self.user.reject_subscription(pk)
+    }
+
+    /// Take the messages rejected since the last call, eg. messages spammed at the link of a
+    /// genuine message or failing its checks in favour of another message at the same link. Only
+    /// the latest [`MAX_REJECTED_MESSAGES`] rejections are kept between calls.
+    pub fn take_rejected_messages(&mut self) -> Vec<RejectedMessage> {
+        core::mem::take(&mut self.rejected_messages)
+    }
+
+    /// Record a message rejected among several messages published at `link`, dropping the oldest
+    /// rejection if too many were not taken.
+    fn reject_candidate(&mut self, link: &Address, message: Message, error: Error) {
+        if self.rejected_messages.len() >= MAX_REJECTED_MESSAGES {
+            self.rejected_messages.remove(0);
+        }
+        self.rejected_messages.push(RejectedMessage {
+            link: link.clone(),
+            message,
+            error,
+        });
+    }
+
     /// Consume a binary sequence message and return the derived message link
     async fn process_sequence(&mut self, msg: BinaryMessage, store: bool) -> Result<Address> {
         let unwrapped = self.user.handle_sequence(msg, MsgInfo::Sequence, store).await?;
@@ -236,6 +542,7 @@ impl<Trans: Transport + Clone> User<Trans> {
                 WrappedMessage {
                     message,
                     wrapped: wrapped_state,
+                    ..
                 },
             ) => {
                 self.transport.send_message(&Message::new(message)).await?;
@@ -253,9 +560,18 @@ impl<Trans: Transport + Clone> User<Trans> {
         }
     }
 
+    /// Send a wrapped message followed by its continuation frames, if any
+    async fn send_frames(&mut self, message: BinaryMessage, frames: Vec<BinaryMessage>) -> Result<()> {
+        self.transport.send_message(&Message::new(message)).await?;
+        for frame in frames {
+            self.transport.send_message(&Message::new(frame)).await?;
+        }
+        Ok(())
+    }
+
     /// Send a message without using sequencing logic. Reserved for Announce and Subscribe messages
     async fn send_message(&mut self, msg: WrappedMessage, info: MsgInfo) -> Result<Address> {
-        self.transport.send_message(&Message::new(msg.message)).await?;
+        self.send_frames(msg.message, msg.frames).await?;
         self.commit_wrapped(msg.wrapped, info)
     }
 
@@ -272,7 +588,7 @@ impl<Trans: Transport + Clone> User<Trans> {
         info: MsgInfo,
     ) -> Result<(Address, Option<Address>)> {
         // Send & commit original message
-        self.transport.send_message(&Message::new(msg.message)).await?;
+        self.send_frames(msg.message, msg.frames).await?;
         let msg_link = self.commit_wrapped(msg.wrapped, info)?;
 
         // Send & commit associated sequence message
@@ -285,7 +601,10 @@ impl<Trans: Transport + Clone> User<Trans> {
     pub async fn send_announce(&mut self) -> Result<Address> {
         let msg = self.user.announce().await?;
         try_or!(
-            self.transport.recv_message(&msg.message.link).await.is_err(),
+            self.transport
+                .recv_messages(&msg.message.link)
+                .await
+                .map_or(true, |msgs| msgs.is_empty()),
             ChannelDuplication
         )?;
         self.send_message(msg, MsgInfo::Announce).await
@@ -303,9 +622,16 @@ impl<Trans: Transport + Clone> User<Trans> {
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.sign_packet(link_to, public_payload, masked_payload).await?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket)
-            .await
+        let timestamp = self.sender_timestamp();
+        let msg = self
+            .user
+            .sign_packet(link_to, public_payload, masked_payload, timestamp)
+            .await?;
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::SignedPacket)
+            .await?;
+        self.user.advance_ratchet(link_to.rel());
+        Ok(links)
     }
 
     /// Create and send a tagged packet [Author, Subscriber].
@@ -320,9 +646,16 @@ impl<Trans: Transport + Clone> User<Trans> {
         public_payload: &Bytes,
         masked_payload: &Bytes,
     ) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.tag_packet(link_to, public_payload, masked_payload).await?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket)
-            .await
+        let timestamp = self.sender_timestamp();
+        let msg = self
+            .user
+            .tag_packet(link_to, public_payload, masked_payload, timestamp)
+            .await?;
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::TaggedPacket)
+            .await?;
+        self.user.advance_ratchet(link_to.rel());
+        Ok(links)
     }
 
     /// Create and send a new keyload for a list of subscribers [Author].
@@ -334,8 +667,8 @@ impl<Trans: Transport + Clone> User<Trans> {
     where
         I: IntoIterator<Item = &'a Identifier>,
     {
-        let msg = self.user.share_keyload(link_to, keys).await?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload).await
+        let (msg, recipients) = self.user.share_keyload(link_to, keys).await?;
+        self.send_keyload_message(msg, link_to, recipients).await
     }
 
     /// Create and send keyload for all subscribed subscribers [Author].
@@ -343,8 +676,99 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link_to` - Address of the message the keyload will be attached to
     pub async fn send_keyload_for_everyone(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
-        let msg = self.user.share_keyload_for_everyone(link_to).await?;
-        self.send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload).await
+        let (msg, recipients) = self.user.share_keyload_for_everyone(link_to).await?;
+        self.send_keyload_message(msg, link_to, recipients).await
+    }
+
+    /// Create and send a keyload for the members of a named group [Author]. Members that are not
+    /// known subscribers or PSKs cannot be included and are returned along with the links, none of
+    /// them being known is an error.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `name` - Name of the group of recipients
+    pub async fn send_keyload_for_group(
+        &mut self,
+        link_to: &Address,
+        name: &str,
+    ) -> Result<(Address, Option<Address>, Vec<Identifier>)> {
+        let (msg, recipients, missing) = self.user.share_keyload_for_group(link_to, name).await?;
+        let (link, seq_link) = self.send_keyload_message(msg, link_to, recipients).await?;
+        Ok((link, seq_link, missing))
+    }
+
+    /// Create and send a keyload for the members of the key tree [Author]. The keyload carries the
+    /// node keys replaced since the last one, a logarithmic number per member added or removed
+    /// meanwhile, instead of one key per member.
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    pub async fn send_keyload_for_tree(&mut self, link_to: &Address) -> Result<(Address, Option<Address>)> {
+        let (msg, members) = self.user.share_keyload_for_tree(link_to).await?;
+        self.send_tree_keyload(msg, link_to, members).await
+    }
+
+    /// Create and send a keyload for the members of the key tree which also delivers the node keys
+    /// on the path of a member again, so that a member that missed keyloads of the tree catches up
+    /// [Author].
+    ///
+    ///  # Arguments
+    ///  * `link_to` - Address of the message the keyload will be attached to
+    ///  * `id` - Identifier of the member of the key tree
+    pub async fn send_tree_resync(&mut self, link_to: &Address, id: &Identifier) -> Result<(Address, Option<Address>)> {
+        let (msg, members) = self.user.share_tree_resync(link_to, id).await?;
+        self.send_tree_keyload(msg, link_to, members).await
+    }
+
+    /// Send a keyload of the key tree and record it once sent.
+    async fn send_tree_keyload(
+        &mut self,
+        msg: WrappedMessage,
+        link_to: &Address,
+        members: Vec<Identifier>,
+    ) -> Result<(Address, Option<Address>)> {
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload)
+            .await?;
+        self.user.record_tree_keyload(links.0.clone(), members);
+        Ok(links)
+    }
+
+    /// Send a keyload and record it together with its recipients once sent.
+    async fn send_keyload_message(
+        &mut self,
+        msg: WrappedMessage,
+        link_to: &Address,
+        recipients: Vec<Identifier>,
+    ) -> Result<(Address, Option<Address>)> {
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::Keyload)
+            .await?;
+        self.user.record_keyload(links.0.clone(), recipients);
+        Ok(links)
+    }
+
+    /// Create and send a keyload for all subscribed subscribers attached to the default link, moving
+    /// the default link to it [Author].
+    pub async fn rekey(&mut self) -> Result<(Address, Option<Address>)> {
+        match self.default_link_to() {
+            Some(link_to) => self.send_keyload_for_everyone(&link_to).await,
+            None => err!(UserNotRegistered),
+        }
+    }
+
+    /// Remove a Subscriber and, if `rekey_on_removal` is set, send a fresh keyload excluding them
+    /// [Author].
+    ///
+    ///   # Arguments
+    ///   * `pk` - ed25519 public key of the subscriber
+    pub async fn revoke_subscriber(&mut self, pk: PublicKey) -> Result<Option<(Address, Option<Address>)>> {
+        self.user.remove_subscriber(pk)?;
+        if self.rekey_on_removal {
+            self.rekey().await.map(Some)
+        } else {
+            Ok(None)
+        }
     }
 
     /// Create and Send a Subscribe message to a Channel app instance [Subscriber].
@@ -365,6 +789,41 @@ impl<Trans: Transport + Clone> User<Trans> {
         self.send_message(msg, MsgInfo::Unsubscribe).await
     }
 
+    /// Create and send a KeyRotation message replacing the Author signing key with the key pair
+    /// derived from `seed`, signed with the current key [Author].
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the message the key rotation will be attached to
+    /// * `seed` - A string slice representing the seed of the new key pair [Characters: A-Z, 9]
+    pub async fn send_key_rotation(&mut self, link_to: &Address, seed: &str) -> Result<(Address, Option<Address>)> {
+        let new_sig_kp = sig_keypair(seed);
+        let msg = self.user.key_rotation(link_to, &new_sig_kp.public).await?;
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::KeyRotation)
+            .await?;
+        self.user.rotate_author_key(new_sig_kp)?;
+        Ok(links)
+    }
+
+    /// Create and send a PublisherCertificate message granting a publisher the rights of
+    /// `certificate`, replacing its former certificate [Author].
+    ///
+    /// # Arguments
+    /// * `link_to` - Address of the message the certificate will be attached to
+    /// * `certificate` - Publisher, scope and expiry of the rights
+    pub async fn send_publisher_certificate(
+        &mut self,
+        link_to: &Address,
+        certificate: Certificate,
+    ) -> Result<(Address, Option<Address>)> {
+        let msg = self.user.certify_publisher(link_to, &certificate).await?;
+        let links = self
+            .send_message_sequenced(msg, link_to.rel(), MsgInfo::PublisherCertificate)
+            .await?;
+        self.user.store_certificate(certificate);
+        Ok(links)
+    }
+
     // Receive
 
     /// Receive and process a sequence message [Author, Subscriber].
@@ -372,13 +831,12 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_sequence(&mut self, link: &Address) -> Result<Address> {
-        let msg = self.transport.recv_message(link).await?;
         if let Some(_addr) = &self.user.appinst {
-            let seq_msg = self
+            let seq_msg = handle_candidates!(self, link, |msg| self
                 .user
                 .handle_sequence(msg.binary, MsgInfo::Sequence, true)
-                .await?
-                .body;
+                .await)?
+            .body;
             let msg_id = self.user.link_gen.link_from(
                 seq_msg.id.to_bytes(),
                 Cursor::new_at(&seq_msg.ref_link, 0, seq_msg.seq_num.0 as u32),
@@ -395,12 +853,11 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_signed_packet(&mut self, link: &Address) -> Result<(PublicKey, Bytes, Bytes)> {
-        let msg = self.transport.recv_message(link).await?;
-        // TODO: msg.timestamp is lost
-        let m = self
+        // Use `receive_message` to get the timestamps along with the content
+        let m = handle_candidates!(self, link, |msg| handle_frames!(self, msg, |msg| self
             .user
-            .handle_signed_packet(msg.binary, MsgInfo::SignedPacket)
-            .await?;
+            .handle_signed_packet(msg.binary, MsgInfo::SignedPacket, msg.timestamp)
+            .await))?;
         Ok(m.body)
     }
 
@@ -409,11 +866,10 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_tagged_packet(&mut self, link: &Address) -> Result<(Bytes, Bytes)> {
-        let msg = self.transport.recv_message(link).await?;
-        let m = self
+        let m = handle_candidates!(self, link, |msg| handle_frames!(self, msg, |msg| self
             .user
             .handle_tagged_packet(msg.binary, MsgInfo::TaggedPacket)
-            .await?;
+            .await))?;
         Ok(m.body)
     }
 
@@ -422,19 +878,33 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_subscribe(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link).await?;
         // TODO: Timestamp is lost.
-        self.user.handle_subscribe(msg.binary, MsgInfo::Subscribe).await
+        handle_candidates!(self, link, |msg| self
+            .user
+            .handle_subscribe(msg.binary, MsgInfo::Subscribe)
+            .await)
     }
 
-    /// Receive and process an unsubscribe message [Author].
+    /// Receive and process an unsubscribe message [Author]. The unsubscription is recorded with the
+    /// timestamp of the message, and a fresh keyload is sent if `rekey_on_removal` is set and the
+    /// subscriber was registered, pending subscribers never received a keyload.
     ///
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_unsubscribe(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link).await?;
-        // TODO: Timestamp is lost.
-        self.user.handle_unsubscribe(msg.binary, MsgInfo::Unsubscribe).await
+        let registered = handle_candidates!(self, link, |msg| self
+            .user
+            .handle_unsubscribe(msg.binary, MsgInfo::Unsubscribe, msg.timestamp)
+            .await)?;
+        if registered && self.rekey_on_removal {
+            self.rekey().await?;
+        }
+        Ok(())
+    }
+
+    /// Subscribers that left the channel together with the timestamp of their unsubscription [Author]
+    pub fn unsubscriptions(&self) -> Vec<(PublicKey, u64)> {
+        self.user.unsubscriptions().to_vec()
     }
 
     /// Receive and Process an announcement message [Subscriber].
@@ -442,8 +912,34 @@ impl<Trans: Transport + Clone> User<Trans> {
     /// # Arguments
     /// * `link_to` - Address of the Channel Announcement message
     pub async fn receive_announcement(&mut self, link: &Address) -> Result<()> {
-        let msg = self.transport.recv_message(link).await?;
-        self.user.handle_announcement(msg.binary, MsgInfo::Announce).await
+        handle_candidates!(self, link, |msg| self
+            .user
+            .handle_announcement(msg.binary, MsgInfo::Announce)
+            .await)
+    }
+
+    /// Receive and process a key rotation message, returning the new Author public key [Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    pub async fn receive_key_rotation(&mut self, link: &Address) -> Result<PublicKey> {
+        let m = handle_candidates!(self, link, |msg| self
+            .user
+            .handle_key_rotation(msg.binary, MsgInfo::KeyRotation)
+            .await)?;
+        Ok(m.body)
+    }
+
+    /// Receive and process a publisher certificate message [Author, Subscriber].
+    ///
+    ///  # Arguments
+    ///  * `link` - Address of the message to be processed
+    pub async fn receive_publisher_certificate(&mut self, link: &Address) -> Result<Certificate> {
+        let m = handle_candidates!(self, link, |msg| self
+            .user
+            .handle_publisher_certificate(msg.binary, MsgInfo::PublisherCertificate)
+            .await)?;
+        Ok(m.body)
     }
 
     /// Receive and process a keyload message [Subscriber].
@@ -451,8 +947,10 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///  # Arguments
     ///  * `link` - Address of the message to be processed
     pub async fn receive_keyload(&mut self, link: &Address) -> Result<bool> {
-        let msg = self.transport.recv_message(link).await?;
-        let m = self.user.handle_keyload(msg.binary, MsgInfo::Keyload).await?;
+        let m = handle_candidates!(self, link, |msg| self
+            .user
+            .handle_keyload(msg.binary, MsgInfo::Keyload)
+            .await)?;
         Ok(m.body)
     }
 
@@ -463,17 +961,30 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///   * `link` - Address of the message to be processed
     ///   * `pk` - Optional ed25519 Public Key of the sending participant. None if unknown
     pub async fn receive_message(&mut self, link: &Address) -> Result<UnwrappedMessage> {
-        let msg = self.transport.recv_message(link).await?;
-        self.handle_message(msg, true).await
+        handle_candidates!(self, link, |msg| self.handle_message(msg, true).await)
     }
 
     /// Retrieves the next message for each user (if present in transport layer) and returns them [Author, Subscriber]
     pub async fn fetch_next_msgs(&mut self) -> Vec<UnwrappedMessage> {
+        self.fetch_next_outcomes()
+            .await
+            .into_iter()
+            .filter_map(|outcome| match outcome {
+                FetchOutcome::Message(msg) => Some(msg),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Tries to retrieve the next message for each user and returns the outcome for every one of them:
+    /// the message, that it is not published yet, or why it could not be received or handled
+    /// [Author, Subscriber]
+    pub async fn fetch_next_outcomes(&mut self) -> Vec<FetchOutcome> {
         let ids = self.user.gen_next_msg_ids(self.user.is_multi_branching());
-        let mut msgs = Vec::new();
+        let mut outcomes = Vec::with_capacity(ids.len());
 
         for (
-            _pk,
+            publisher,
             Cursor {
                 link,
                 branch_no: _,
@@ -481,15 +992,14 @@ impl<Trans: Transport + Clone> User<Trans> {
             },
         ) in ids
         {
-            let msg = self.transport.recv_message(&link).await;
-
-            if let Ok(msg) = msg {
-                if let Ok(msg) = self.handle_message(msg, true).await {
-                    msgs.push(msg);
-                }
-            }
+            let outcome = match self.receive_message(&link).await {
+                Ok(msg) => FetchOutcome::Message(msg),
+                Err(error) if is_message_not_found(&error) => FetchOutcome::NotAvailable { publisher, link },
+                Err(error) => FetchOutcome::Failed(FetchFailure { publisher, link, error }),
+            };
+            outcomes.push(outcome);
         }
-        msgs
+        outcomes
     }
 
     /// Retrieves the previous message from the message specified (provided the user has access to it) [Author,
@@ -539,49 +1049,268 @@ impl<Trans: Transport + Clone> User<Trans> {
     ///
     /// # Arguments
     /// * `msg` - Binary message of unknown type
-    pub async fn handle_message(&mut self, mut msg0: Message, store: bool) -> Result<UnwrappedMessage> {
-        let mut sequenced = false;
+    pub async fn handle_message(&mut self, msg: Message, store: bool) -> Result<UnwrappedMessage> {
+        let content_type = msg.binary.parse_header().await?.header.content_type;
+        if content_type != message::SEQUENCE {
+            return self.handle_content(msg).await;
+        }
+        let msg_link = self.process_sequence(msg.binary, store).await?;
+        // Packets referenced by a sequence message are reported as unreadable rather than failing
+        let mut unreadable = None;
+        let handled = handle_candidates!(self, &msg_link, |msg| {
+            unreadable = unreadable_packet(&msg).await;
+            self.handle_content(msg).await
+        });
+        match (handled, unreadable) {
+            (Err(_), Some(unreadable)) => Ok(unreadable),
+            (handled, _) => handled,
+        }
+    }
+
+    /// Handle a message other than a sequence message according to its content type.
+    async fn handle_content(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        handle_frames!(self, msg, |msg| self.handle_joined_content(msg).await)
+    }
+
+    /// Handle a message other than a sequence message whose frames are joined according to its
+    /// content type.
+    async fn handle_joined_content(&mut self, msg: Message) -> Result<UnwrappedMessage> {
+        // Forget TangleMessage, keeping the timestamp for the unwrapped message
+        let timestamp = msg.timestamp;
+        let msg = msg.binary;
+        let preparsed = msg.parse_header().await?;
+        match preparsed.header.content_type {
+            message::SIGNED_PACKET => {
+                let m = self
+                    .user
+                    .handle_signed_packet(msg, MsgInfo::SignedPacket, timestamp)
+                    .await?;
+                Ok(
+                    m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked))
+                        .with_timestamp(timestamp),
+                )
+            }
+            message::TAGGED_PACKET => {
+                let m = self.user.handle_tagged_packet(msg, MsgInfo::TaggedPacket).await?;
+                Ok(
+                    m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked))
+                        .with_timestamp(timestamp),
+                )
+            }
+            message::KEYLOAD => {
+                // So long as the unwrap has not failed, we will return a blank object to
+                // inform the user that a message was present, even if the use wasn't part of
+                // the keyload itself. This is to prevent sequencing failures
+                let m = self.user.handle_keyload(msg, MsgInfo::Keyload).await?;
+                // TODO: Verify content, whether user is allowed or not!
+                Ok(m.map(|_allowed| MessageContent::new_keyload())
+                    .with_timestamp(timestamp))
+            }
+            message::KEY_ROTATION => {
+                let m = self.user.handle_key_rotation(msg, MsgInfo::KeyRotation).await?;
+                Ok(m.map(MessageContent::new_key_rotation).with_timestamp(timestamp))
+            }
+            message::PUBLISHER_CERTIFICATE => {
+                let m = self
+                    .user
+                    .handle_publisher_certificate(msg, MsgInfo::PublisherCertificate)
+                    .await?;
+                Ok(m.map(MessageContent::new_publisher_certificate)
+                    .with_timestamp(timestamp))
+            }
+            unknown_content => err!(UnknownMsgType(unknown_content)),
+        }
+    }
+
+    /// Rebuild the state of the Author from the channel published at `announcement` [Author].
+    ///
+    /// The announcement must be the one derived from the seed of the user. Keyloads and packets are
+    /// then read publisher by publisher, restoring the link store and the publishers listed in the
+    /// keyloads, and the first message that cannot be received or handled fails the recovery. The
+    /// subscribe and unsubscribe messages of those publishers are looked up at the addresses
+    /// derived from their public keys. Only subscribers listed in keyloads are restored, pending
+    /// subscriptions and subscribers that were not sent a keyload yet are lost. Branches of
+    /// keyloads that do not include the Author cannot be restored. Key rotations of the Author are
+    /// taken up with the key pairs derived from `rotation_seeds`, a rotation to a key that is not
+    /// derived from them fails with `KeyRotationKeyNotFound`. Returns the cursors of the recovered
+    /// state.
+    ///
+    ///  # Arguments
+    ///  * `announcement` - Address of the announcement of the channel
+    ///  * `rotation_seeds` - Seeds of the key pairs introduced by `send_key_rotation`, in any order
+    pub async fn recover_channel(
+        &mut self,
+        announcement: &Address,
+        rotation_seeds: &[&str],
+    ) -> Result<Vec<(Identifier, Cursor<Address>)>> {
+        self.user
+            .set_rotation_keys(rotation_seeds.iter().map(|seed| sig_keypair(seed)).collect());
+        let ann = self.user.announce().await?;
+        let retrieved = self.transport.recv_messages(announcement).await?;
+        try_or!(
+            retrieved.iter().any(|msg| msg.binary == ann.message),
+            AnnouncementRecoveryMismatch(announcement.to_string())
+        )?;
+        self.user.commit_wrapped(ann.wrapped, MsgInfo::Announce)?;
+
+        // Sequence numbers following the last packet of each publisher of a single branch channel
+        let branching = self.user.is_multi_branching();
+        let mut next_seq_nos = HashMap::new();
         loop {
-            // Forget TangleMessage and timestamp
-            let msg = msg0.binary;
-            let preparsed = msg.parse_header().await?;
-            let link = preparsed.header.link;
-            let prev_link = Address::try_from_bytes(&preparsed.header.previous_msg_link.0)?;
-            match preparsed.header.content_type {
-                message::SIGNED_PACKET => match self.user.handle_signed_packet(msg, MsgInfo::SignedPacket).await {
-                    Ok(m) => {
-                        return Ok(m.map(|(pk, public, masked)| MessageContent::new_signed_packet(pk, public, masked)))
+            let ids = self.user.gen_next_msg_ids(branching);
+            let mut received = false;
+            for outcome in self.fetch_next_outcomes().await {
+                match outcome {
+                    FetchOutcome::Message(msg) => {
+                        received = true;
+                        if let Some((id, cursor)) = ids.iter().find(|(_, cursor)| cursor.link == msg.link) {
+                            next_seq_nos.insert(*id, cursor.seq_no + 1);
+                        }
                     }
-                    Err(e) => match sequenced {
-                        true => return Ok(UnwrappedMessage::new(link, prev_link, MessageContent::unreadable())),
-                        false => return Err(e),
-                    },
-                },
-                message::TAGGED_PACKET => match self.user.handle_tagged_packet(msg, MsgInfo::TaggedPacket).await {
-                    Ok(m) => return Ok(m.map(|(public, masked)| MessageContent::new_tagged_packet(public, masked))),
-                    Err(e) => match sequenced {
-                        true => return Ok(UnwrappedMessage::new(link, prev_link, MessageContent::unreadable())),
-                        false => return Err(e),
-                    },
-                },
-                message::KEYLOAD => {
-                    // So long as the unwrap has not failed, we will return a blank object to
-                    // inform the user that a message was present, even if the use wasn't part of
-                    // the keyload itself. This is to prevent sequencing failures
-                    let m = self.user.handle_keyload(msg, MsgInfo::Keyload).await?;
-                    // TODO: Verify content, whether user is allowed or not!
-                    let u = m.map(|_allowed| MessageContent::new_keyload());
-                    return Ok(u);
+                    FetchOutcome::NotAvailable { .. } => {}
+                    FetchOutcome::Failed(failure) => return Err(failure.error),
                 }
-                message::SEQUENCE => {
-                    let msg_link = self.process_sequence(msg, store).await?;
-                    let msg = self.transport.recv_message(&msg_link).await?;
-                    sequenced = true;
-                    msg0 = msg;
+            }
+            if !received {
+                break;
+            }
+        }
+
+        let state = self.user.fetch_state()?;
+        let last_seq_no = state.iter().map(|(_, cursor)| cursor.seq_no).max().unwrap_or(0);
+        let own_id = Identifier::from(self.user.sig_kp.public);
+        for (id, cursor) in state {
+            let pk = match id {
+                Identifier::EdPubKey(pk) if id != own_id => pk.0,
+                _ => continue,
+            };
+            let subscribe_link = self.user.subscribe_link(&pk)?;
+            if let Some(msg) = self.probe_message(&subscribe_link).await? {
+                self.user.recover_subscription(msg.binary, MsgInfo::Subscribe).await?;
+                // Unsubscribe messages carry the sequence number of the subscriber at the time, which
+                // follows its last packet. Cursors of multi branch channels track it, whereas those of
+                // single branch channels follow the whole channel.
+                let first_seq_no = if branching {
+                    cursor.seq_no
+                } else {
+                    next_seq_nos.get(&id).copied().unwrap_or(0)
+                };
+                for seq_no in first_seq_no..=last_seq_no {
+                    let link = self
+                        .user
+                        .link_gen
+                        .link_from(pk, Cursor::new_at(&subscribe_link.msgid, 0, seq_no));
+                    if let Some(msg) = self.probe_message(&link).await? {
+                        self.user
+                            .handle_unsubscribe(msg.binary, MsgInfo::Unsubscribe, msg.timestamp)
+                            .await?;
+                        break;
+                    }
+                }
+            }
+        }
+        self.user.fetch_state()
+    }
+
+    /// Rebuild the state of a Subscriber from its subscribe message at `subscribe_link` [Subscriber].
+    ///
+    /// The announcement is processed first. The subscribe message is then reproduced with the keys
+    /// derived from the seed of the user, it must match the published message, and the channel
+    /// history is read afterwards, the first message that cannot be received or handled fails the
+    /// recovery. Returns the cursors of the recovered state.
+    ///
+    ///  # Arguments
+    ///  * `announcement` - Address of the announcement of the channel
+    ///  * `subscribe_link` - Address of the subscribe message sent by the user
+    pub async fn recover_subscriber(
+        &mut self,
+        announcement: &Address,
+        subscribe_link: &Address,
+    ) -> Result<Vec<(Identifier, Cursor<Address>)>> {
+        self.receive_announcement(announcement).await?;
+        let sub = self.user.subscribe(announcement).await?;
+        let retrieved = self.transport.recv_messages(subscribe_link).await?;
+        try_or!(
+            retrieved.iter().any(|msg| msg.binary == sub.message),
+            SubscribeRecoveryMismatch(subscribe_link.to_string())
+        )?;
+        self.user.commit_wrapped(sub.wrapped, MsgInfo::Subscribe)?;
+
+        loop {
+            let mut received = false;
+            for outcome in self.fetch_next_outcomes().await {
+                match outcome {
+                    FetchOutcome::Message(_) => received = true,
+                    FetchOutcome::NotAvailable { .. } => {}
+                    FetchOutcome::Failed(failure) => return Err(failure.error),
                 }
-                unknown_content => return err!(UnknownMsgType(unknown_content)),
+            }
+            if !received {
+                break;
             }
         }
+        self.user.fetch_state()
+    }
+
+    /// Receive the message at `link`, none if it has not been published.
+    async fn probe_message(&mut self, link: &Address) -> Result<Option<Message>> {
+        match self.transport.recv_message(link).await {
+            Ok(msg) => Ok(Some(msg)),
+            Err(error) if is_message_not_found(&error) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn sender_timestamp(&self) -> Option<u64> {
+        if self.sender_timestamps {
+            Some(timestamp_now())
+        } else {
+            None
+        }
+    }
+
+    /// Receive the continuation frames of a message split into several frames and join them to the
+    /// message. Several messages may be published at the link of a frame, the message is joined with
+    /// each combination of the well-formed ones, up to [`MAX_JOINED_FRAMES`] combinations, for the
+    /// caller to keep the joined message that is accepted. Messages consisting of a single frame are
+    /// returned as is.
+    async fn recv_frames(&mut self, msg: Message) -> Result<Vec<Message>> {
+        let frame_count = msg.binary.payload_frame_count().await?;
+        if frame_count <= 1 {
+            return Ok(vec![msg]);
+        }
+        let mut combinations = vec![Vec::new()];
+        for frame_num in 2..=frame_count {
+            let link = self.user.link_gen.frame_link_from(&msg.binary.link, frame_num);
+            let candidates = self.transport.recv_messages(&link).await?;
+            let mut frames: Vec<BinaryMessage> = candidates
+                .iter()
+                .filter(|frame| frame.binary.is_frame(frame_num, frame_count))
+                .map(|frame| frame.binary.clone())
+                .collect();
+            if frames.is_empty() {
+                // None of the candidates is a frame, joining the first one reports why
+                frames = candidates.into_iter().take(1).map(|frame| frame.binary).collect();
+            }
+            combinations = combinations
+                .iter()
+                .flat_map(|combination| {
+                    frames.iter().map(move |frame| {
+                        let mut combination: Vec<BinaryMessage> = combination.clone();
+                        combination.push(frame.clone());
+                        combination
+                    })
+                })
+                .take(MAX_JOINED_FRAMES)
+                .collect();
+        }
+        let mut joined = Vec::with_capacity(combinations.len());
+        for frames in combinations {
+            let mut msg = msg.clone();
+            msg.binary.join_frames(frames).await?;
+            joined.push(msg);
+        }
+        Ok(joined)
     }
 
     /// Get the previous msg link and msg type from header of message and return in a tuple alongside
@@ -613,10 +1342,24 @@ impl<Trans: Transport + Clone> User<Trans> {
                 let seq_no = self.user.fetch_anchor()?.seq_no;
                 let cursor = Cursor::new_at(anchor_link.rel(), 0, msg_num + seq_no);
                 let link = self.user.link_gen.link_from(pk.as_ref(), cursor);
-                let msg = self.transport.recv_message(&link).await?;
-                self.handle_message(msg, false).await
+                handle_candidates!(self, &link, |msg| self.handle_message(msg, false).await)
             }
             None => err(UserNotRegistered),
         }
     }
 }
+
+/// Unreadable message in place of the signed or tagged packet `msg`, none for other messages.
+async fn unreadable_packet(msg: &Message) -> Option<UnwrappedMessage> {
+    let header = msg.binary.parse_header().await.ok()?.header;
+    match header.content_type {
+        message::SIGNED_PACKET | message::TAGGED_PACKET => {
+            let prev_link = Address::try_from_bytes(&header.previous_msg_link.0).ok()?;
+            Some(
+                UnwrappedMessage::new(header.link, prev_link, MessageContent::unreadable())
+                    .with_timestamp(msg.timestamp),
+            )
+        }
+        _ => None,
+    }
+}
@@ -21,7 +21,10 @@ use iota_streams_core::{
     async_trait,
     err,
     prelude::{
-        string::ToString,
+        string::{
+            String,
+            ToString,
+        },
         typenum::U32,
         Box,
         Vec,
@@ -57,6 +60,7 @@ use iota_streams_ddml::{
 use crate::{
     api::{
         key_store::*,
+        key_tree::KeyTree,
         ChannelType,
     },
     message::*,
@@ -67,6 +71,16 @@ use core::borrow::BorrowMut;
 const ANN_MESSAGE_NUM: u32 = 0;
 const SUB_MESSAGE_NUM: u32 = 0;
 const SEQ_MESSAGE_NUM: u32 = 1;
+/// Number of the latest keyloads kept together with their recipients.
+pub const MAX_KEYLOADS: usize = 64;
+/// Version of the exported user state, bumped whenever its fields change:
+///
+/// * 1 -- adds the keyload and subscription settings, the subscription and keyload records, the key tree, the Author
+///   key history and the publisher certificates.
+/// * 2 -- adds the frame length.
+/// * 3 -- adds the pinned Author public key.
+/// * 4 -- adds the key pairs introduced by key rotations of the Author.
+const EXPORT_VERSION: u8 = 4;
 
 /// Sequence wrapping object
 ///
@@ -121,12 +135,29 @@ where
     /// Own x25519 key pair corresponding to Ed25519 keypair.
     pub(crate) ke_kp: (x25519::StaticSecret, x25519::PublicKey),
 
+    /// Former x25519 secrets of the Author replaced by key rotations, subscribe messages may still
+    /// be encrypted for the former keys.
+    pub(crate) former_ke_sks: Vec<x25519::StaticSecret>,
+
+    /// Key pairs introduced by key rotations of the Author, taken up when the rotations are read
+    /// again while recovering the channel.
+    pub(crate) rotation_sig_kps: Vec<ed25519::Keypair>,
+
     /// Users' trusted public keys together with additional sequencing info: (msgid, seq_no).
     pub(crate) key_store: Keys,
 
     /// Author's Ed25519 public key.
     pub(crate) author_sig_pk: Option<ed25519::PublicKey>,
 
+    /// Former Ed25519 public keys of the Author replaced by key rotations, the announced key first.
+    pub(crate) author_key_history: Vec<ed25519::PublicKey>,
+
+    /// Latest certificates issued by the Author, one per publisher.
+    pub(crate) certificates: Vec<publisher_certificate::Certificate>,
+
+    /// Author's Ed25519 public key the announcement is expected to be signed with, if pinned.
+    pub(crate) pinned_author_sig_pk: Option<ed25519::PublicKey>,
+
     /// Link generator.
     pub(crate) link_gen: LG,
 
@@ -142,10 +173,55 @@ where
 
     pub use_psk: bool,
 
+    /// Name of the codec encoding payloads of sent packets, empty selects `utf-8`.
     pub message_encoding: Vec<u8>,
 
+    /// Codecs available for encoding sent and decoding received payloads.
+    pub payload_codecs: PayloadCodecs,
+
+    /// Length signed and tagged packets are padded to with masked filler. Zero disables padding.
     pub uniform_payload_length: usize,
 
+    /// Pad keyloads to `uniform_payload_length` as well.
+    pub pad_keyloads: bool,
+
+    /// Hold received subscriptions until they are accepted instead of registering subscribers
+    /// right away.
+    pub require_subscription_approval: bool,
+
+    /// Erase the spongos state a signed or tagged packet is linked to once the packet is committed.
+    pub ratchet: bool,
+
+    /// Subscriptions awaiting approval: subscriber public key and link to the subscribe message.
+    pub(crate) pending_subscriptions: Vec<(ed25519::PublicKey, Link)>,
+
+    /// Links to the subscribe messages of registered subscribers, unsubscribe messages must be
+    /// linked to them.
+    pub(crate) subscribe_links: Vec<(ed25519::PublicKey, Link)>,
+
+    /// Subscribers that left the channel together with the timestamp of their unsubscription.
+    pub(crate) unsubscriptions: Vec<(ed25519::PublicKey, u64)>,
+
+    /// Latest keyloads published by the user together with their recipients, at most
+    /// [`MAX_KEYLOADS`].
+    pub(crate) keyloads: Vec<(Link, Vec<Identifier>)>,
+
+    /// Identifiers removed from the key store, keyloads including them are compromised.
+    pub(crate) revoked_ids: Vec<Identifier>,
+
+    /// Named groups of keyload recipients.
+    pub(crate) groups: Vec<(String, Vec<Identifier>)>,
+
+    /// Key tree of the recipients of group keyloads.
+    pub(crate) key_tree: KeyTree,
+
+    /// Keys of the nodes of the Author's key tree learned from keyloads.
+    pub(crate) node_keys: Vec<keyload::NodeKey>,
+
+    /// Maximum length of a message frame; larger signed and tagged packets are split into
+    /// several frames. Zero disables splitting.
+    pub frame_length: usize,
+
     /// Anchor message for the channel (can either be an announcement or keyload) - For single depth
     pub anchor: Option<Cursor<Link>>,
 }
@@ -169,15 +245,33 @@ where
             _phantom: PhantomData,
             sig_kp,
             ke_kp,
+            former_ke_sks: Vec::new(),
+            rotation_sig_kps: Vec::new(),
 
             key_store: Keys::default(),
             author_sig_pk: None,
+            author_key_history: Vec::new(),
+            certificates: Vec::new(),
+            pinned_author_sig_pk: None,
             link_gen: LG::default(),
             link_store: LS::default(),
             appinst: None,
             flags: 0,
             message_encoding: Vec::new(),
+            payload_codecs: PayloadCodecs::default(),
             uniform_payload_length: 0,
+            pad_keyloads: false,
+            require_subscription_approval: false,
+            ratchet: false,
+            pending_subscriptions: Vec::new(),
+            subscribe_links: Vec::new(),
+            unsubscriptions: Vec::new(),
+            keyloads: Vec::new(),
+            revoked_ids: Vec::new(),
+            groups: Vec::new(),
+            key_tree: KeyTree::new(),
+            node_keys: Vec::new(),
+            frame_length: 0,
             use_psk: false,
             anchor: None,
         }
@@ -215,15 +309,33 @@ where
             _phantom: PhantomData,
             sig_kp,
             ke_kp,
+            former_ke_sks: Vec::new(),
+            rotation_sig_kps: Vec::new(),
 
             key_store: Keys::default(),
             author_sig_pk: None,
+            author_key_history: Vec::new(),
+            certificates: Vec::new(),
+            pinned_author_sig_pk: None,
             link_gen: LG::default(),
             link_store: LS::default(),
             appinst: None,
             flags,
             message_encoding,
+            payload_codecs: PayloadCodecs::default(),
             uniform_payload_length,
+            pad_keyloads: false,
+            require_subscription_approval: false,
+            ratchet: false,
+            pending_subscriptions: Vec::new(),
+            subscribe_links: Vec::new(),
+            unsubscriptions: Vec::new(),
+            keyloads: Vec::new(),
+            revoked_ids: Vec::new(),
+            groups: Vec::new(),
+            key_tree: KeyTree::new(),
+            node_keys: Vec::new(),
+            frame_length: 0,
             use_psk: false,
             anchor: None,
         }
@@ -257,11 +369,30 @@ where
     pub fn reset_state(&mut self) -> Result<()> {
         match &self.appinst {
             Some(appinst) => {
+                // Subscribers go back to the announced Author key, rotations are verified again while syncing
+                let rewound_author_key = match (self.author_sig_pk, self.author_key_history.first()) {
+                    (Some(author_sig_pk), Some(announced_sig_pk)) if author_sig_pk != self.sig_kp.public => {
+                        Some((Identifier::from(author_sig_pk), *announced_sig_pk))
+                    }
+                    _ => None,
+                };
                 let mut key_store = Keys::default();
                 for (id, _cursor) in self.key_store.iter() {
-                    key_store.insert_cursor(*id, Cursor::new_at(appinst.rel().clone(), 0, 2_u32))?;
+                    let id = match rewound_author_key {
+                        Some((author_id, announced_sig_pk)) if *id == author_id => Identifier::from(announced_sig_pk),
+                        _ => *id,
+                    };
+                    key_store.insert_cursor(id, Cursor::new_at(appinst.rel().clone(), 0, 2_u32))?;
                 }
                 self.key_store = key_store;
+                if let Some((_, announced_sig_pk)) = rewound_author_key {
+                    self.author_sig_pk = Some(announced_sig_pk);
+                    self.author_key_history.clear();
+                }
+                // Certificates are received again while syncing as well
+                if self.author_sig_pk != Some(self.sig_kp.public) {
+                    self.certificates.clear();
+                }
                 self.link_store = LS::default();
 
                 self.link_gen.reset(appinst.clone());
@@ -276,6 +407,23 @@ where
         wrapped.commit(&mut self.link_store, info)
     }
 
+    /// Erase the spongos state a committed signed or tagged packet was linked to, if `ratchet` is
+    /// set.
+    ///
+    /// The spongos state of a packet is a one-way function of the state it is linked to, since
+    /// committing the state erases its outer part. Once the previous state is erased, a leaked state
+    /// does not reveal the packets preceding it in the branch. Packets can no longer be linked to
+    /// an erased state though, each packet must be linked to the latest message of its branch.
+    ///
+    /// Only single branch channels are ratcheted. Single depth channels link every packet to their
+    /// anchor, and the publishers of multi branch channels link their packets to a keyload shared
+    /// with the other branches, which must not be erased by the first packet read.
+    pub fn advance_ratchet(&mut self, link_to: &<Link as HasLink>::Rel) {
+        if self.ratchet && !self.is_multi_branching() && !self.is_single_depth() {
+            self.link_store.erase(link_to);
+        }
+    }
+
     /// Prepare Announcement message.
     pub fn prepare_announcement(&self) -> Result<PreparedMessage<F, Link, announce::ContentWrap<F>>> {
         // Create HDF for the first message in the channel.
@@ -325,15 +473,20 @@ where
 
         let unwrapped = self.unwrap_announcement(preparsed).await?;
         let link = unwrapped.link.clone();
+        let sig_pk = &unwrapped.pcf.content.sig_pk;
+        if let Some(pinned_sig_pk) = &self.pinned_author_sig_pk {
+            try_or!(
+                pinned_sig_pk == sig_pk,
+                AuthorSigPkMismatch(hex::encode(pinned_sig_pk.as_bytes()), hex::encode(sig_pk.as_bytes()))
+            )?;
+        }
+        try_or!(
+            self.link_gen.is_announcement_link(&link, sig_pk),
+            AnnouncementLinkMismatch(link.base().to_string(), hex::encode(sig_pk.as_bytes()))
+        )?;
         let content = unwrapped.commit(&mut self.link_store, info)?;
         // TODO: check commit after message is done / before joined
 
-        // TODO: Verify trust to Author's public key?
-        // At the moment the Author is trusted unconditionally.
-
-        // TODO: Verify appinst (address) == public key.
-        // At the moment the Author is free to choose any address, not tied to PK.
-
         let cursor = Cursor::new_at(link.rel().clone(), 0, 2_u32);
         self.key_store
             .insert_cursor(Identifier::EdPubKey(content.sig_pk.into()), cursor.clone())?;
@@ -365,10 +518,19 @@ where
                     .with_payload_length(1)?
                     .with_seq_num(SUB_MESSAGE_NUM)
                     .with_identifier(&self.sig_kp.public.into());
-                let unsubscribe_key = NBytes::from(prng::random_key());
+                // Keys are derived from the secret key, the subscribe message of the channel can thus
+                // be reproduced from the seed of the subscriber.
+                let prng = prng::Prng::<F>::init_with_seed(self.sig_kp.secret.as_bytes());
+                let link_bytes = link_to.to_bytes();
+                let unsubscribe_key =
+                    NBytes::<U32>(prng.gen_arr([&b"subscribe unsubscribe key"[..], &link_bytes].concat()));
+                let mut ephemeral_ke_sk = [0_u8; 32];
+                ephemeral_ke_sk
+                    .copy_from_slice(&prng.gen_n([&b"subscribe ephemeral key"[..], &link_bytes].concat(), 32));
                 let content = subscribe::ContentWrap {
                     link: link_to.rel(),
                     unsubscribe_key,
+                    ephemeral_ke_sk: x25519::StaticSecret::from(ephemeral_ke_sk),
                     subscriber_sig_kp: &self.sig_kp,
                     author_ke_pk,
                     _phantom: PhantomData,
@@ -400,27 +562,142 @@ where
 
     /// Get public payload, decrypt masked payload and verify MAC.
     pub async fn handle_subscribe(&mut self, msg: BinaryMessage<F, Link>, info: LS::Info) -> Result<()> {
-        let preparsed = msg.parse_header().await?;
+        // The subscriber is checked before committing, so that a rejected subscription leaves no state
+        let (subscriber_sig_pk, link) = self.commit_subscribe(msg, info, Self::check_new_subscriber).await?;
+        if self.require_subscription_approval {
+            self.queue_subscription(subscriber_sig_pk, link)
+        } else {
+            self.register_subscription(subscriber_sig_pk, link)
+        }
+    }
+
+    /// Check that a subscriber can subscribe to the channel, ie. that it is not registered yet.
+    fn check_new_subscriber(&self, pk: &ed25519::PublicKey) -> Result<()> {
+        match &self.appinst {
+            None => err!(UserNotRegistered),
+            Some(ref_link) if self.key_store.contains(&(*pk).into()) => err!(UserAlreadyRegistered(
+                hex::encode(pk.as_bytes()),
+                ref_link.base().to_string()
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Unwrap a subscribe message and commit it once its subscriber public key passes `check`,
+    /// returning the subscriber public key and the link of the message.
+    async fn commit_subscribe(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: LS::Info,
+        check: fn(&Self, &ed25519::PublicKey) -> Result<()>,
+    ) -> Result<(ed25519::PublicKey, Link)> {
+        let link = msg.link.clone();
         // TODO: check content type
 
-        let content = self
-            // We need to borrow self.ke_kp.0 at this scope
-            // to leverage https://doc.rust-lang.org/nomicon/borrow-splitting.html
-            .unwrap_subscribe(preparsed, &self.ke_kp.0)
-            .await?
-            .commit(&mut self.link_store, info)?;
-        // TODO: trust content.subscriber_sig_pk
-        // TODO: remove unused unsubscribe_key because it is unnecessary for verification anymore
-        let subscriber_sig_pk = content.subscriber_sig_pk;
-        self.insert_subscriber(subscriber_sig_pk)
+        // Subscribers may encrypt for a former key of the Author until they read its rotation, the
+        // current key is tried first. We need to borrow self.ke_kp.0 and self.former_ke_sks at this
+        // scope to leverage https://doc.rust-lang.org/nomicon/borrow-splitting.html
+        let mut unwrapped = err!(AuthorExchangeKeyNotFound);
+        for ke_sk in core::iter::once(&self.ke_kp.0).chain(self.former_ke_sks.iter().rev()) {
+            let preparsed = msg.parse_header().await?;
+            unwrapped = self.unwrap_subscribe(preparsed, ke_sk).await;
+            if unwrapped.is_ok() {
+                break;
+            }
+        }
+        let unwrapped = unwrapped?;
+        check(self, &unwrapped.pcf.content.subscriber_sig_pk)?;
+        let content = unwrapped.commit(&mut self.link_store, info)?;
+        Ok((content.subscriber_sig_pk, link))
+    }
+
+    /// Restore a subscription while recovering the Author state. Unlike `handle_subscribe`, the
+    /// subscriber may already be known from the keyloads of the channel and is registered without
+    /// approval. Returns the subscriber public key.
+    pub async fn recover_subscription(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: LS::Info,
+    ) -> Result<ed25519::PublicKey> {
+        // Known subscribers are expected, they are only registered again if missing
+        let (pk, link) = self.commit_subscribe(msg, info, |_, _| Ok(())).await?;
+        if !self.key_store.contains(&pk.into()) {
+            self.insert_subscriber(pk)?;
+        }
+        self.subscribe_links.retain(|(p, _)| *p != pk);
+        self.subscribe_links.push((pk, link));
+        Ok(pk)
+    }
+
+    /// Link of the subscribe message of a subscriber, derived from its public key.
+    pub fn subscribe_link(&self, pk: &ed25519::PublicKey) -> Result<Link> {
+        match &self.appinst {
+            Some(appinst) => Ok(self
+                .link_gen
+                .link_from(pk, Cursor::new_at(appinst.rel(), 0, SUB_MESSAGE_NUM))),
+            None => err!(UserNotRegistered),
+        }
+    }
+
+    /// Register subscriber and remember its subscribe message for verifying its unsubscription.
+    fn register_subscription(&mut self, pk: ed25519::PublicKey, link: Link) -> Result<()> {
+        self.insert_subscriber(pk)?;
+        self.subscribe_links.push((pk, link));
+        self.unsubscriptions.retain(|(p, _)| *p != pk);
+        Ok(())
+    }
+
+    fn queue_subscription(&mut self, pk: ed25519::PublicKey, link: Link) -> Result<()> {
+        match &self.appinst {
+            None => err!(UserNotRegistered),
+            Some(ref_link) if self.key_store.contains(&pk.into()) => err!(UserAlreadyRegistered(
+                hex::encode(pk.as_bytes()),
+                ref_link.base().to_string()
+            )),
+            // A repeated subscription only refreshes the link of the pending one
+            Some(_) => {
+                match self.pending_subscriptions.iter_mut().find(|(p, _)| *p == pk) {
+                    Some(pending) => pending.1 = link,
+                    None => self.pending_subscriptions.push((pk, link)),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Subscriptions awaiting approval.
+    pub fn pending_subscriptions(&self) -> &[(ed25519::PublicKey, Link)] {
+        &self.pending_subscriptions
+    }
+
+    fn take_pending_subscription(&mut self, pk: &ed25519::PublicKey) -> Result<(ed25519::PublicKey, Link)> {
+        match self.pending_subscriptions.iter().position(|(p, _)| p == pk) {
+            Some(idx) => Ok(self.pending_subscriptions.remove(idx)),
+            None => err!(SubscriptionNotPending(hex::encode(pk.as_bytes()))),
+        }
+    }
+
+    /// Register the subscriber of a pending subscription.
+    pub fn accept_subscription(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        let (pk, link) = self.take_pending_subscription(pk)?;
+        self.register_subscription(pk, link)
+    }
+
+    /// Discard a pending subscription, the subscriber is not registered.
+    pub fn reject_subscription(&mut self, pk: &ed25519::PublicKey) -> Result<()> {
+        self.take_pending_subscription(pk).map(|_| ())
     }
 
     pub fn insert_subscriber(&mut self, pk: ed25519::PublicKey) -> Result<()> {
         match (!self.key_store.contains(&pk.into()), &self.appinst) {
             (_, None) => err!(UserNotRegistered),
-            (true, Some(ref_link)) => self
-                .key_store
-                .insert_cursor(pk.into(), Cursor::new_at(ref_link.rel().clone(), 0, SEQ_MESSAGE_NUM)),
+            (true, Some(ref_link)) => {
+                self.key_store
+                    .insert_cursor(pk.into(), Cursor::new_at(ref_link.rel().clone(), 0, SEQ_MESSAGE_NUM))?;
+                let id = Identifier::from(pk);
+                self.revoked_ids.retain(|revoked| *revoked != id);
+                Ok(())
+            }
             (false, Some(ref_link)) => err!(UserAlreadyRegistered(
                 hex::encode(pk.as_bytes()),
                 ref_link.base().to_string()
@@ -470,13 +747,49 @@ where
     }
 
     /// Confirm unsubscription request ownership and remove subscriber.
-    pub async fn handle_unsubscribe(&mut self, msg: BinaryMessage<F, Link>, info: LS::Info) -> Result<()> {
+    ///
+    /// Unsubscribe message must be linked to the subscribe message of the subscriber, whose spongos
+    /// state is only known to the subscriber and the Author as it is keyed with the `unsubscribe_key`.
+    /// Subscribers inserted without a subscribe message are only checked to be registered. The
+    /// unsubscription is recorded with `timestamp`. Returns whether a registered subscriber was
+    /// removed, as opposed to a pending subscription.
+    pub async fn handle_unsubscribe(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: LS::Info,
+        timestamp: u64,
+    ) -> Result<bool> {
         let preparsed = msg.parse_header().await?;
-        let content = self
-            .unwrap_unsubscribe(preparsed)
-            .await?
-            .commit(&mut self.link_store, info)?;
-        self.remove_subscriber(content.sig_pk)
+        let unwrapped = self.unwrap_unsubscribe(preparsed).await?;
+        let sig_pk = unwrapped.pcf.content.sig_pk;
+        let subscribe_link = self
+            .subscribe_links
+            .iter()
+            .chain(self.pending_subscriptions.iter())
+            .find(|(pk, _)| *pk == sig_pk)
+            .map(|(_, link)| link.rel());
+        match subscribe_link {
+            Some(subscribe_link) => try_or!(
+                *subscribe_link == unwrapped.pcf.content.link,
+                UnsubscribeLinkMismatch(hex::encode(sig_pk.as_bytes()))
+            )?,
+            // Subscribers stored with `insert_subscriber` have no subscribe message to check
+            None => try_or!(self.key_store.contains(&sig_pk.into()), UserNotRegistered)?,
+        }
+        unwrapped.commit(&mut self.link_store, info)?;
+
+        let registered = self.take_pending_subscription(&sig_pk).is_err();
+        if registered {
+            self.remove_subscriber(sig_pk)?;
+        }
+        self.unsubscriptions.retain(|(pk, _)| *pk != sig_pk);
+        self.unsubscriptions.push((sig_pk, timestamp));
+        Ok(registered)
+    }
+
+    /// Subscribers that left the channel together with the timestamp of their unsubscription.
+    pub fn unsubscriptions(&self) -> &[(ed25519::PublicKey, u64)] {
+        &self.unsubscriptions
     }
 
     pub fn remove_subscriber(&mut self, pk: ed25519::PublicKey) -> Result<()> {
@@ -484,12 +797,258 @@ where
         match self.key_store.contains(&id) {
             true => {
                 self.key_store.remove(&id);
-                Ok(())
+                self.subscribe_links.retain(|(p, _)| *p != pk);
+                self.revoke(id)
             }
             false => err(UserNotRegistered),
         }
     }
 
+    /// Prepare KeyRotation message.
+    pub fn prepare_key_rotation<'a>(
+        &'a self,
+        link_to: &'a Link,
+        new_sig_pk: &'a ed25519::PublicKey,
+    ) -> Result<PreparedMessage<F, Link, key_rotation::ContentWrap<'a, F, Link>>> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), KeyRotationNotAuthor)?;
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(self.sig_kp.public, Cursor::new_at(link_to.rel(), 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_previous_msg_link(Bytes(link_to.to_bytes()))
+                    .with_content_type(KEY_ROTATION)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no)
+                    .with_identifier(&self.sig_kp.public.into());
+                let content = key_rotation::ContentWrap {
+                    link: link_to.rel(),
+                    new_sig_pk,
+                    sig_kp: &self.sig_kp,
+                    _phantom: PhantomData,
+                };
+                Ok(PreparedMessage::new(header, content))
+            }
+            None => err!(SeqNumRetrievalFailure),
+        }
+    }
+
+    /// Create a KeyRotation message introducing a new Author public key, signed with the current
+    /// key. The key pair is replaced with `rotate_author_key` once the message is sent.
+    pub async fn key_rotation(
+        &self,
+        link_to: &Link,
+        new_sig_pk: &ed25519::PublicKey,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_key_rotation(link_to, new_sig_pk)?
+            .wrap(&self.link_store)
+            .await
+    }
+
+    /// Replace the Author key pair with the one introduced by a sent KeyRotation message. The former
+    /// x25519 secret is kept for the subscriptions still encrypted for it.
+    pub fn rotate_author_key(&mut self, new_sig_kp: ed25519::Keypair) -> Result<()> {
+        try_or!(self.author_sig_pk == Some(self.sig_kp.public), KeyRotationNotAuthor)?;
+        self.replace_author_key(new_sig_kp.public)?;
+        let ke_kp = core::mem::replace(&mut self.ke_kp, x25519::keypair_from_ed25519(&new_sig_kp));
+        self.former_ke_sks.push(ke_kp.0);
+        self.sig_kp = new_sig_kp;
+        Ok(())
+    }
+
+    /// Provide the key pairs introduced by the key rotations of the Author, in any order, before
+    /// recovering the channel. The Author takes them up as it reads its rotations again.
+    pub fn set_rotation_keys(&mut self, sig_kps: Vec<ed25519::Keypair>) {
+        self.rotation_sig_kps = sig_kps;
+    }
+
+    pub async fn unwrap_key_rotation<'a>(
+        &self,
+        preparsed: PreparsedMessage<'_, F, Link>,
+    ) -> Result<UnwrappedMessage<F, Link, key_rotation::ContentUnwrap<F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = key_rotation::ContentUnwrap::default();
+        preparsed.unwrap(&self.link_store, content).await
+    }
+
+    /// Verify the KeyRotation message is signed with the current Author key and replace it with the
+    /// key it introduces. The Author reading its own rotation again, eg. while recovering the channel,
+    /// takes up the key pair introduced among the ones provided with `set_rotation_keys`. Returns the
+    /// new Author public key.
+    pub async fn handle_key_rotation(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: LS::Info,
+    ) -> Result<GenericMessage<Link, ed25519::PublicKey>> {
+        let preparsed = msg.parse_header().await?;
+        let prev_link = Link::try_from_bytes(&preparsed.header.previous_msg_link.0)?;
+        let seq_no = preparsed.header.seq_num;
+        let unwrapped = self.unwrap_key_rotation(preparsed).await?;
+        let sig_pk = &unwrapped.pcf.content.sig_pk;
+        match &self.author_sig_pk {
+            Some(author_sig_pk) => try_or!(
+                author_sig_pk == sig_pk,
+                KeyRotationSignerMismatch(hex::encode(sig_pk.as_bytes()), hex::encode(author_sig_pk.as_bytes()))
+            )?,
+            None => return err!(AuthorSigKeyNotFound),
+        }
+        let new_sig_kp = if *sig_pk == self.sig_kp.public {
+            let new_sig_pk = unwrapped.pcf.content.new_sig_pk;
+            match self.rotation_sig_kps.iter().position(|kp| kp.public == new_sig_pk) {
+                Some(i) => Some(self.rotation_sig_kps.remove(i)),
+                None => return err!(KeyRotationKeyNotFound(hex::encode(new_sig_pk.as_bytes()))),
+            }
+        } else {
+            None
+        };
+        let content = unwrapped.commit(&mut self.link_store, info)?;
+        match new_sig_kp {
+            Some(new_sig_kp) => self.rotate_author_key(new_sig_kp)?,
+            None => self.replace_author_key(content.new_sig_pk)?,
+        }
+        if !self.is_multi_branching() {
+            let link = if self.is_single_depth() {
+                self.fetch_anchor()?.link.rel().clone()
+            } else {
+                msg.link.rel().clone()
+            };
+            self.store_state_for_all(link, seq_no.0 as u32 + 1)?;
+        }
+        Ok(GenericMessage::new(msg.link, prev_link, content.new_sig_pk))
+    }
+
+    /// Replace the Author public key, moving the sequencing state of the former key to the new one.
+    fn replace_author_key(&mut self, new_sig_pk: ed25519::PublicKey) -> Result<()> {
+        match self.author_sig_pk {
+            Some(author_sig_pk) => {
+                let author_id = Identifier::from(author_sig_pk);
+                if let Some(cursor) = self.key_store.get(&author_id).cloned() {
+                    self.key_store.remove(&author_id);
+                    self.key_store.insert_cursor(Identifier::from(new_sig_pk), cursor)?;
+                }
+                self.author_key_history.push(author_sig_pk);
+                self.author_sig_pk = Some(new_sig_pk);
+                Ok(())
+            }
+            None => err!(AuthorSigKeyNotFound),
+        }
+    }
+
+    /// Former public keys of the Author replaced by key rotations, the announced key first.
+    pub fn author_key_history(&self) -> &[ed25519::PublicKey] {
+        &self.author_key_history
+    }
+
+    /// Prepare PublisherCertificate message.
+    pub fn prepare_publisher_certificate<'a>(
+        &'a self,
+        link_to: &'a Link,
+        certificate: &'a publisher_certificate::Certificate,
+    ) -> Result<PreparedMessage<F, Link, publisher_certificate::ContentWrap<'a, F, Link>>> {
+        try_or!(
+            self.author_sig_pk == Some(self.sig_kp.public),
+            PublisherCertificateNotAuthor
+        )?;
+        match self.get_seq_no() {
+            Some(seq_no) => {
+                let msg_link = self
+                    .link_gen
+                    .link_from(self.sig_kp.public, Cursor::new_at(link_to.rel(), 0, seq_no));
+                let header = HDF::new(msg_link)
+                    .with_previous_msg_link(Bytes(link_to.to_bytes()))
+                    .with_content_type(PUBLISHER_CERTIFICATE)?
+                    .with_payload_length(1)?
+                    .with_seq_num(seq_no)
+                    .with_identifier(&self.sig_kp.public.into());
+                let content = publisher_certificate::ContentWrap {
+                    link: link_to.rel(),
+                    certificate,
+                    sig_kp: &self.sig_kp,
+                    _phantom: PhantomData,
+                };
+                Ok(PreparedMessage::new(header, content))
+            }
+            None => err!(SeqNumRetrievalFailure),
+        }
+    }
+
+    /// Create a PublisherCertificate message granting a publisher the rights of `certificate`. The
+    /// certificate is stored with `store_certificate` once the message is sent.
+    pub async fn certify_publisher(
+        &self,
+        link_to: &Link,
+        certificate: &publisher_certificate::Certificate,
+    ) -> Result<WrappedMessage<F, Link>> {
+        self.prepare_publisher_certificate(link_to, certificate)?
+            .wrap(&self.link_store)
+            .await
+    }
+
+    pub async fn unwrap_publisher_certificate<'a>(
+        &self,
+        preparsed: PreparsedMessage<'_, F, Link>,
+        author_sig_pk: &'a ed25519::PublicKey,
+    ) -> Result<UnwrappedMessage<F, Link, publisher_certificate::ContentUnwrap<'a, F, Link>>> {
+        self.ensure_appinst(&preparsed)?;
+        let content = publisher_certificate::ContentUnwrap::new(author_sig_pk);
+        preparsed.unwrap(&self.link_store, content).await
+    }
+
+    /// Verify the PublisherCertificate message is signed by the Author and store the certificate.
+    pub async fn handle_publisher_certificate(
+        &mut self,
+        msg: BinaryMessage<F, Link>,
+        info: LS::Info,
+    ) -> Result<GenericMessage<Link, publisher_certificate::Certificate>> {
+        let author_sig_pk = match self.author_sig_pk {
+            Some(pk) => pk,
+            None => return err!(AuthorSigKeyNotFound),
+        };
+        let preparsed = msg.parse_header().await?;
+        let prev_link = Link::try_from_bytes(&preparsed.header.previous_msg_link.0)?;
+        let seq_no = preparsed.header.seq_num;
+        let unwrapped = self.unwrap_publisher_certificate(preparsed, &author_sig_pk).await?;
+        let content = unwrapped.commit(&mut self.link_store, info)?;
+        let certificate = match content.certificate {
+            Some(certificate) => certificate,
+            None => return err!(MessageBuildFailure),
+        };
+        self.store_certificate(certificate);
+        if !self.is_multi_branching() {
+            let link = if self.is_single_depth() {
+                self.fetch_anchor()?.link.rel().clone()
+            } else {
+                msg.link.rel().clone()
+            };
+            self.store_state_for_all(link, seq_no.0 as u32 + 1)?;
+        }
+        Ok(GenericMessage::new(msg.link, prev_link, certificate))
+    }
+
+    /// Store a certificate, replacing the former certificate of the same publisher.
+    pub fn store_certificate(&mut self, certificate: publisher_certificate::Certificate) {
+        self.certificates.retain(|c| c.publisher != certificate.publisher);
+        self.certificates.push(certificate);
+    }
+
+    /// Latest certificate issued to a publisher.
+    pub fn publisher_certificate(&self, publisher: &Identifier) -> Option<&publisher_certificate::Certificate> {
+        self.certificates.iter().find(|c| &c.publisher == publisher)
+    }
+
+    /// Check the publisher of a packet received at `timestamp` is certified for `scope`, if the
+    /// channel requires certificates. The Author needs no certificate.
+    fn ensure_certified(&self, publisher: &Identifier, scope: u8, timestamp: u64) -> Result<()> {
+        if !self.requires_certificates() || self.author_sig_pk.map(Identifier::from).as_ref() == Some(publisher) {
+            return Ok(());
+        }
+        match self.publisher_certificate(publisher) {
+            Some(certificate) if certificate.allows(scope, timestamp) => Ok(()),
+            _ => err!(PublisherNotCertified(hex::encode(publisher.to_bytes()))),
+        }
+    }
+
     fn do_prepare_keyload<'a>(
         &'a self,
         header: HDF<Link>,
@@ -503,6 +1062,10 @@ where
             nonce,
             key,
             keys,
+            updates: &[],
+            group_key: None,
+            anonymous: self.has_anonymous_keyloads(),
+            padding: Padding::default(),
             sig_kp: &self.sig_kp,
             _phantom: PhantomData,
         };
@@ -558,18 +1121,142 @@ where
     }
 
     /// Create keyload message with a new session key shared with recipients
-    /// identified by pre-shared key IDs and by Ed25519 public keys.
-    pub async fn share_keyload<'a, I>(&mut self, link_to: &Link, keys: I) -> Result<WrappedMessage<F, Link>>
+    /// identified by pre-shared key IDs and by Ed25519 public keys. The recipients are returned
+    /// alongside the message, to be recorded with `record_keyload` once the message is sent.
+    pub async fn share_keyload<'a, I>(
+        &mut self,
+        link_to: &Link,
+        keys: I,
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>)>
     where
         I: IntoIterator<Item = &'a Identifier>,
     {
-        self.prepare_keyload(link_to, keys)?.wrap(&self.link_store).await
+        let mut prepared = self.prepare_keyload(link_to, keys)?;
+        if self.pad_keyloads {
+            prepared.content.content.padding = self.uniform_padding(&prepared).await?;
+        }
+        let recipients = prepared.content.content.keys.iter().map(|(id, _)| **id).collect();
+        let wrapped = prepared.wrap(&self.link_store).await?;
+        Ok((wrapped, recipients))
+    }
+
+    /// Create keyload message with a new session key shared with the members of a named group.
+    /// Members unknown to the key store cannot be included. The recipients and the members left out
+    /// are returned alongside the message. Fails before wrapping the message if none of the members
+    /// are known.
+    pub async fn share_keyload_for_group(
+        &mut self,
+        link_to: &Link,
+        name: &str,
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>, Vec<Identifier>)> {
+        let (known, missing): (Vec<Identifier>, Vec<Identifier>) = self
+            .group_members(name)?
+            .iter()
+            .copied()
+            .partition(|id| !self.key_store.filter(core::iter::once(id)).is_empty());
+        try_or!(!known.is_empty(), GroupMembersNotFound(name.to_string()))?;
+        let (wrapped, recipients) = self.share_keyload(link_to, known.iter()).await?;
+        Ok((wrapped, recipients, missing))
+    }
+
+    /// Record a sent keyload together with its recipients, only the latest [`MAX_KEYLOADS`]
+    /// keyloads are kept.
+    pub fn record_keyload(&mut self, link: Link, recipients: Vec<Identifier>) {
+        if self.keyloads.len() >= MAX_KEYLOADS {
+            self.keyloads.remove(0);
+        }
+        self.keyloads.push((link, recipients));
+    }
+
+    /// Record a sent keyload of the key tree, see `record_keyload`. The node keys it carries are
+    /// no longer pending.
+    pub fn record_tree_keyload(&mut self, link: Link, recipients: Vec<Identifier>) {
+        self.key_tree.clear_pending_updates();
+        self.record_keyload(link, recipients);
+    }
+
+    /// Add a recipient to the key tree, its pre-shared key or public key must be in the key store.
+    pub fn add_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        match self.key_store.filter(core::iter::once(id)).pop() {
+            Some((id, store_id)) => {
+                let id = *id;
+                self.key_tree.add::<F>(id, store_id)
+            }
+            None => err!(RecipientKeyNotFound(hex::encode(id.to_bytes()))),
+        }
+    }
+
+    /// Remove a recipient from the key tree.
+    pub fn remove_tree_member(&mut self, id: &Identifier) -> Result<()> {
+        self.key_tree.remove::<F>(id)
+    }
+
+    /// Members of the key tree.
+    pub fn tree_members(&self) -> Vec<&Identifier> {
+        self.key_tree.members().collect()
+    }
+
+    /// Create keyload message with a new session key shared with the members of the key tree.
+    ///
+    /// The session key is encapsulated under the key of the root of the tree, along with the node keys
+    /// replaced since the last such keyload. Members must handle these keyloads in order to keep
+    /// their node keys up to date. The members are returned alongside the message, to be recorded
+    /// with `record_tree_keyload` once the message is sent.
+    pub async fn share_keyload_for_tree(
+        &mut self,
+        link_to: &Link,
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>)> {
+        let updates = self.key_tree.pending_updates().to_vec();
+        self.share_tree_keyload(link_to, &updates).await
+    }
+
+    /// Create keyload message with a new session key shared with the members of the key tree, which
+    /// also delivers the node keys on the path of member `id` again, see `share_keyload_for_tree`.
+    /// A member that missed keyloads of the tree cannot derive the current keys of the tree until it
+    /// handles such a keyload.
+    pub async fn share_tree_resync(
+        &mut self,
+        link_to: &Link,
+        id: &Identifier,
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>)> {
+        let store_id = match self.key_store.filter(core::iter::once(id)).pop() {
+            Some((_, store_id)) => store_id,
+            None => return err!(RecipientKeyNotFound(hex::encode(id.to_bytes()))),
+        };
+        let mut updates = self.key_tree.resync_updates(id, store_id)?;
+        updates.extend(self.key_tree.pending_updates().iter().cloned());
+        self.share_tree_keyload(link_to, &updates).await
+    }
+
+    async fn share_tree_keyload(
+        &mut self,
+        link_to: &Link,
+        updates: &[keyload::KeyUpdate],
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>)> {
+        let mut prepared = self.prepare_keyload(link_to, core::iter::empty::<&Identifier>())?;
+        prepared.content.content.updates = updates;
+        prepared.content.content.group_key = self.key_tree.group_key();
+        if self.pad_keyloads {
+            prepared.content.content.padding = self.uniform_padding(&prepared).await?;
+        }
+        let recipients = self.key_tree.members().copied().collect();
+        let wrapped = prepared.wrap(&self.link_store).await?;
+        Ok((wrapped, recipients))
     }
 
     /// Create keyload message with a new session key shared with all Subscribers
-    /// known to Author.
-    pub async fn share_keyload_for_everyone(&mut self, link_to: &Link) -> Result<WrappedMessage<F, Link>> {
-        self.prepare_keyload_for_everyone(link_to)?.wrap(&self.link_store).await
+    /// known to Author. The recipients are returned alongside the message, see `share_keyload`.
+    pub async fn share_keyload_for_everyone(
+        &mut self,
+        link_to: &Link,
+    ) -> Result<(WrappedMessage<F, Link>, Vec<Identifier>)> {
+        let mut prepared = self.prepare_keyload_for_everyone(link_to)?;
+        if self.pad_keyloads {
+            prepared.content.content.padding = self.uniform_padding(&prepared).await?;
+        }
+        let recipients = prepared.content.content.keys.iter().map(|(id, _)| **id).collect();
+        let wrapped = prepared.wrap(&self.link_store).await?;
+        Ok((wrapped, recipients))
     }
 
     pub async fn unwrap_keyload<'a>(
@@ -583,7 +1270,22 @@ where
     > {
         self.ensure_appinst(&preparsed)?;
         if let Some(author_sig_pk) = author_sig_pk {
-            let content = keyload::ContentUnwrap::new(keys_lookup, own_keys, author_sig_pk);
+            let ke_kp = own_keys.1;
+            let mut content = keyload::ContentUnwrap::new(keys_lookup, own_keys, author_sig_pk)
+                .with_version(preparsed.header.version)
+                .with_node_keys(self.node_keys.clone());
+            if self.has_anonymous_keyloads() {
+                let psks = self
+                    .key_store
+                    .keys()
+                    .into_iter()
+                    .filter_map(|(id, psk)| match id {
+                        Identifier::PskId(_) => Some(Psk::clone_from_slice(&psk)),
+                        Identifier::EdPubKey(_) => None,
+                    })
+                    .collect();
+                content = content.with_anonymous_slots(&ke_kp.0, psks);
+            }
             preparsed.unwrap(&self.link_store, content).await
         } else {
             err!(AuthorSigKeyNotFound)
@@ -616,6 +1318,7 @@ where
             // Unwrapped nonce and key in content are not used explicitly.
             // The resulting spongos state is joined into a protected message state.
             let content = unwrapped.commit(&mut self.link_store, info)?;
+            self.node_keys = content.node_keys;
             (GenericMessage::new(msg.link.clone(), prev_link, true), content.key_ids)
         } else {
             (
@@ -670,6 +1373,8 @@ where
                     link: link_to.rel(),
                     public_payload,
                     masked_payload,
+                    timestamp: None,
+                    padding: Padding::default(),
                     sig_kp: &self.sig_kp,
                     _phantom: PhantomData,
                 };
@@ -679,15 +1384,24 @@ where
         }
     }
 
-    /// Create a signed message with public and masked payload.
+    /// Create a signed message with public and masked payload, and optionally the time of publishing
+    /// in milliseconds since the Unix epoch.
     pub async fn sign_packet(
         &mut self,
         link_to: &Link,
         public_payload: &Bytes,
         masked_payload: &Bytes,
+        timestamp: Option<u64>,
     ) -> Result<WrappedMessage<F, Link>> {
-        self.prepare_signed_packet(link_to, public_payload, masked_payload)?
-            .wrap(&self.link_store)
+        let codec = self.payload_codec()?;
+        let public_payload = codec.encode(public_payload)?;
+        let masked_payload = codec.encode(masked_payload)?;
+        let mut prepared = self.prepare_signed_packet(link_to, &public_payload, &masked_payload)?;
+        prepared.header.encoding = codec.id();
+        prepared.content.content.timestamp = timestamp;
+        prepared.content.content.padding = self.uniform_padding(&prepared).await?;
+        prepared
+            .wrap_frames(&self.link_store, &self.link_gen, self.frame_length)
             .await
     }
 
@@ -696,24 +1410,38 @@ where
         preparsed: PreparsedMessage<'a, F, Link>,
     ) -> Result<UnwrappedMessage<F, Link, signed_packet::ContentUnwrap<F, Link>>> {
         self.ensure_appinst(&preparsed)?;
-        let content = signed_packet::ContentUnwrap::default();
+        let mut content = signed_packet::ContentUnwrap::default();
+        content.version = preparsed.header.version;
         preparsed.unwrap(&self.link_store, content).await
     }
 
-    /// Verify new Author's MSS public key and update Author's MSS public key.
+    /// Verify the signature of a packet received at `timestamp`, and the certificate of its
+    /// publisher if the channel requires certificates.
     pub async fn handle_signed_packet(
         &'_ mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
+        timestamp: u64,
     ) -> Result<GenericMessage<Link, (ed25519::PublicKey, Bytes, Bytes)>> {
         // TODO: pass author_pk to unwrap
         let preparsed = msg.parse_header().await?;
         let prev_link = Link::try_from_bytes(&preparsed.header.previous_msg_link.0)?;
         let seq_no = preparsed.header.seq_num;
-        let content = self
-            .unwrap_signed_packet(preparsed)
-            .await?
-            .commit(&mut self.link_store, info)?;
+        let encoding = preparsed.header.encoding;
+        let mut unwrapped = self.unwrap_signed_packet(preparsed).await?;
+        {
+            let codec = self.payload_codecs.by_id(encoding)?;
+            let content = &mut unwrapped.pcf.content;
+            content.public_payload = codec.decode(&content.public_payload)?;
+            content.masked_payload = codec.decode(&content.masked_payload)?;
+        }
+        self.ensure_certified(
+            &unwrapped.pcf.content.sig_pk.into(),
+            publisher_certificate::SCOPE_SIGNED_PACKETS,
+            timestamp,
+        )?;
+        let content = unwrapped.commit(&mut self.link_store, info)?;
+        self.advance_ratchet(prev_link.rel());
         if !self.is_multi_branching() {
             let link = if self.is_single_depth() {
                 self.fetch_anchor()?.link.rel().clone()
@@ -724,7 +1452,7 @@ where
         }
 
         let body = (content.sig_pk, content.public_payload, content.masked_payload);
-        Ok(GenericMessage::new(msg.link, prev_link, body))
+        Ok(GenericMessage::new(msg.link, prev_link, body).with_sender_timestamp(content.timestamp))
     }
 
     /// Prepare TaggedPacket message.
@@ -734,6 +1462,7 @@ where
         public_payload: &'a Bytes,
         masked_payload: &'a Bytes,
     ) -> Result<PreparedMessage<F, Link, tagged_packet::ContentWrap<'a, F, Link>>> {
+        try_or!(!self.requires_certificates(), TaggedPacketNotCertifiable)?;
         let identifier = self.get_identifier()?;
         match self.get_seq_no() {
             Some(seq_no) => {
@@ -750,6 +1479,8 @@ where
                     link: link_to.rel(),
                     public_payload,
                     masked_payload,
+                    timestamp: None,
+                    padding: Padding::default(),
                     _phantom: PhantomData,
                 };
                 Ok(PreparedMessage::new(header, content))
@@ -758,6 +1489,26 @@ where
         }
     }
 
+    /// Padding extending a prepared message to `uniform_payload_length`, none if padding is disabled.
+    async fn uniform_padding<Content>(&self, prepared: &PreparedMessage<F, Link, Content>) -> Result<Padding>
+    where
+        Content: ContentSizeof<F>,
+    {
+        if self.uniform_payload_length == 0 {
+            return Ok(Padding::default());
+        }
+        Padding::to_length(prepared.sizeof().await?, self.uniform_payload_length)
+    }
+
+    /// Codec encoding payloads of sent packets, selected by `message_encoding`.
+    fn payload_codec(&self) -> Result<&dyn PayloadCodec> {
+        if self.message_encoding.is_empty() {
+            self.payload_codecs.by_id(UTF8)
+        } else {
+            self.payload_codecs.by_name(&self.message_encoding)
+        }
+    }
+
     fn get_identifier(&self) -> Result<Identifier> {
         if self.use_psk {
             match self.key_store.get_next_pskid() {
@@ -769,16 +1520,25 @@ where
         }
     }
 
-    /// Create a tagged (ie. MACed) message with public and masked payload.
+    /// Create a tagged (ie. MACed) message with public and masked payload, and optionally the time of
+    /// publishing in milliseconds since the Unix epoch.
     /// Tagged messages must be linked to a secret spongos state, ie. keyload or a message linked to keyload.
     pub async fn tag_packet(
         &self,
         link_to: &Link,
         public_payload: &Bytes,
         masked_payload: &Bytes,
+        timestamp: Option<u64>,
     ) -> Result<WrappedMessage<F, Link>> {
-        self.prepare_tagged_packet(link_to, public_payload, masked_payload)?
-            .wrap(&self.link_store)
+        let codec = self.payload_codec()?;
+        let public_payload = codec.encode(public_payload)?;
+        let masked_payload = codec.encode(masked_payload)?;
+        let mut prepared = self.prepare_tagged_packet(link_to, &public_payload, &masked_payload)?;
+        prepared.header.encoding = codec.id();
+        prepared.content.content.timestamp = timestamp;
+        prepared.content.content.padding = self.uniform_padding(&prepared).await?;
+        prepared
+            .wrap_frames(&self.link_store, &self.link_gen, self.frame_length)
             .await
     }
 
@@ -787,23 +1547,32 @@ where
         preparsed: PreparsedMessage<'_, F, Link>,
     ) -> Result<UnwrappedMessage<F, Link, tagged_packet::ContentUnwrap<F, Link>>> {
         self.ensure_appinst(&preparsed)?;
-        let content = tagged_packet::ContentUnwrap::default();
+        let mut content = tagged_packet::ContentUnwrap::default();
+        content.version = preparsed.header.version;
         preparsed.unwrap(&self.link_store, content).await
     }
 
-    /// Get public payload, decrypt masked payload and verify MAC.
+    /// Get public payload, decrypt masked payload and verify MAC of a packet. Tagged packets are not
+    /// signed, so they are rejected if the channel requires certificates.
     pub async fn handle_tagged_packet(
         &mut self,
         msg: BinaryMessage<F, Link>,
         info: <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info,
     ) -> Result<GenericMessage<Link, (Bytes, Bytes)>> {
+        try_or!(!self.requires_certificates(), TaggedPacketNotCertifiable)?;
         let preparsed = msg.parse_header().await?;
         let prev_link = Link::try_from_bytes(&preparsed.header.previous_msg_link.0)?;
         let seq_no = preparsed.header.seq_num;
-        let content = self
-            .unwrap_tagged_packet(preparsed)
-            .await?
-            .commit(&mut self.link_store, info)?;
+        let encoding = preparsed.header.encoding;
+        let mut unwrapped = self.unwrap_tagged_packet(preparsed).await?;
+        {
+            let codec = self.payload_codecs.by_id(encoding)?;
+            let content = &mut unwrapped.pcf.content;
+            content.public_payload = codec.decode(&content.public_payload)?;
+            content.masked_payload = codec.decode(&content.masked_payload)?;
+        }
+        let content = unwrapped.commit(&mut self.link_store, info)?;
+        self.advance_ratchet(prev_link.rel());
         if !self.is_multi_branching() {
             let link = if self.is_single_depth() {
                 self.fetch_anchor()?.link.rel().clone()
@@ -814,7 +1583,7 @@ where
         }
 
         let body = (content.public_payload, content.masked_payload);
-        Ok(GenericMessage::new(msg.link, prev_link, body))
+        Ok(GenericMessage::new(msg.link, prev_link, body).with_sender_timestamp(content.timestamp))
     }
 
     pub fn prepare_sequence<'a>(
@@ -942,7 +1711,39 @@ where
     }
 
     pub fn is_single_depth(&self) -> bool {
-        self.flags == 2
+        self.flags & announce::CHANNEL_TYPE_MASK == 2
+    }
+
+    /// Whether keyloads of the channel hide their recipients.
+    pub fn has_anonymous_keyloads(&self) -> bool {
+        (self.flags & announce::FLAG_ANONYMOUS_KEYLOADS) != 0
+    }
+
+    /// Hide the recipients of keyloads behind anonymous slots that they trial-decrypt. Subscribers
+    /// learn the option from the announcement, so it must be selected before announcing the channel.
+    /// Only recipients learn the identifiers of the other recipients, masked with the session key.
+    pub fn set_anonymous_keyloads(&mut self, anonymous: bool) {
+        if anonymous {
+            self.flags |= announce::FLAG_ANONYMOUS_KEYLOADS;
+        } else {
+            self.flags &= !announce::FLAG_ANONYMOUS_KEYLOADS;
+        }
+    }
+
+    /// Whether signed and tagged packets are only accepted from publishers certified by the Author.
+    pub fn requires_certificates(&self) -> bool {
+        (self.flags & announce::FLAG_CERTIFIED_PUBLISHERS) != 0
+    }
+
+    /// Only accept signed packets from publishers certified by the Author, tagged packets are
+    /// rejected as they do not authenticate their publisher. Subscribers learn the option from the
+    /// announcement, so it must be selected before announcing the channel.
+    pub fn set_certified_publishers(&mut self, certified: bool) {
+        if certified {
+            self.flags |= announce::FLAG_CERTIFIED_PUBLISHERS;
+        } else {
+            self.flags &= !announce::FLAG_CERTIFIED_PUBLISHERS;
+        }
     }
 
     // TODO: own seq_no should be stored outside of pk_store to avoid lookup and Option
@@ -977,6 +1778,8 @@ where
                         Some(psk),
                         Cursor::new_at(appinst.rel().clone(), 0, 2_u32),
                     )?;
+                    let id = Identifier::from(pskid);
+                    self.revoked_ids.retain(|revoked| *revoked != id);
                     self.use_psk = use_psk;
                     Ok(())
                 } else {
@@ -992,12 +1795,80 @@ where
         match self.key_store.contains(&id) {
             true => {
                 self.key_store.remove(&id);
-                Ok(())
+                self.revoke(id)
             }
             false => err(UserNotRegistered),
         }
     }
 
+    fn revoke(&mut self, id: Identifier) -> Result<()> {
+        if !self.revoked_ids.contains(&id) {
+            self.revoked_ids.push(id);
+        }
+        if self.key_tree.contains(&id) {
+            self.key_tree.remove::<F>(&id)?;
+        }
+        Ok(())
+    }
+
+    /// Keyloads that included identifiers removed since, together with those identifiers, among the
+    /// latest [`MAX_KEYLOADS`] keyloads. Messages linked to these keyloads can still be read by the
+    /// removed recipients.
+    pub fn compromised_keyloads(&self) -> Vec<(&Link, Vec<Identifier>)> {
+        self.keyloads
+            .iter()
+            .filter_map(|(link, recipients)| {
+                let revoked: Vec<Identifier> = recipients
+                    .iter()
+                    .filter(|id| self.revoked_ids.contains(id))
+                    .copied()
+                    .collect();
+                if revoked.is_empty() {
+                    None
+                } else {
+                    Some((link, revoked))
+                }
+            })
+            .collect()
+    }
+
+    /// Link of the last published keyload.
+    pub fn latest_keyload(&self) -> Option<&Link> {
+        self.keyloads.last().map(|(link, _)| link)
+    }
+
+    /// Store a named group of keyload recipients, replacing the group with the same name if any.
+    pub fn store_group(&mut self, name: &str, members: Vec<Identifier>) {
+        match self.groups.iter_mut().find(|(n, _)| n == name) {
+            Some(group) => group.1 = members,
+            None => self.groups.push((name.to_string(), members)),
+        }
+    }
+
+    /// Remove a named group of keyload recipients.
+    pub fn remove_group(&mut self, name: &str) -> Result<()> {
+        match self.groups.iter().position(|(n, _)| n == name) {
+            Some(idx) => {
+                self.groups.remove(idx);
+                Ok(())
+            }
+            None => err!(GroupNotFound(name.to_string())),
+        }
+    }
+
+    /// Members of a named group of keyload recipients.
+    pub fn group_members(&self, name: &str) -> Result<&[Identifier]> {
+        match self.groups.iter().find(|(n, _)| n == name) {
+            Some((_, members)) => Ok(members),
+            None => err!(GroupNotFound(name.to_string())),
+        }
+    }
+
+    /// Names of the stored groups of keyload recipients.
+    pub fn group_names(&self) -> Vec<&str> {
+        self.groups.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
     fn gen_next_msg_id(
         ids: &mut Vec<(Identifier, Cursor<Link>)>,
         link_gen: &LG,
@@ -1101,7 +1972,10 @@ where
         ctx.mask(<&NBytes<U32>>::from(&self.sig_kp.secret.as_bytes()[..]))?
             .absorb(Uint8(self.flags))?
             .absorb(<&Bytes>::from(&self.message_encoding))?
-            .absorb(Uint64(self.uniform_payload_length as u64))?;
+            .absorb(Uint64(self.uniform_payload_length as u64))?
+            .absorb(Uint8(self.pad_keyloads as u8))?
+            .absorb(Uint8(self.require_subscription_approval as u8))?
+            .absorb(Uint8(self.ratchet as u8))?;
 
         let oneof_appinst = Uint8(if self.appinst.is_some() { 1 } else { 0 });
         ctx.absorb(&oneof_appinst)?;
@@ -1135,6 +2009,74 @@ where
                 .absorb(Uint32(cursor.branch_no))?
                 .absorb(Uint32(cursor.seq_no))?;
         }
+
+        ctx.absorb(Size(self.pending_subscriptions.len()))?;
+        for (pk, link) in self.pending_subscriptions.iter() {
+            ctx.absorb(pk)?.absorb(<&Fallback<Link>>::from(link))?;
+        }
+
+        ctx.absorb(Size(self.subscribe_links.len()))?;
+        for (pk, link) in self.subscribe_links.iter() {
+            ctx.absorb(pk)?.absorb(<&Fallback<Link>>::from(link))?;
+        }
+
+        ctx.absorb(Size(self.unsubscriptions.len()))?;
+        for (pk, timestamp) in self.unsubscriptions.iter() {
+            ctx.absorb(pk)?.absorb(Uint64(*timestamp))?;
+        }
+
+        ctx.absorb(Size(self.keyloads.len()))?;
+        for (link, recipients) in self.keyloads.iter() {
+            ctx.absorb(<&Fallback<Link>>::from(link))?
+                .absorb(Size(recipients.len()))?;
+            for id in recipients {
+                id.sizeof(ctx).await?;
+            }
+        }
+
+        ctx.absorb(Size(self.revoked_ids.len()))?;
+        for id in self.revoked_ids.iter() {
+            id.sizeof(ctx).await?;
+        }
+
+        ctx.absorb(Size(self.groups.len()))?;
+        for (name, members) in self.groups.iter() {
+            ctx.absorb(&Bytes(name.as_bytes().to_vec()))?
+                .absorb(Size(members.len()))?;
+            for id in members {
+                id.sizeof(ctx).await?;
+            }
+        }
+
+        self.key_tree.sizeof(ctx).await?;
+        ctx.absorb(Size(self.node_keys.len()))?;
+        for node in self.node_keys.iter() {
+            node.sizeof(ctx).await?;
+        }
+        ctx.absorb(Size(self.author_key_history.len()))?;
+        for pk in self.author_key_history.iter() {
+            ctx.absorb(pk)?;
+        }
+        ctx.absorb(Size(self.former_ke_sks.len()))?;
+        for ke_sk in self.former_ke_sks.iter() {
+            ctx.mask(<&NBytes<U32>>::from(&ke_sk.to_bytes()[..]))?;
+        }
+        ctx.absorb(Size(self.certificates.len()))?;
+        for certificate in self.certificates.iter() {
+            certificate.publisher.sizeof(ctx).await?;
+            ctx.absorb(Uint8(certificate.scope))?
+                .absorb(Uint64(certificate.expiry))?;
+        }
+        ctx.absorb(Uint64(self.frame_length as u64))?;
+        let oneof_pinned_author_sig_pk = Uint8(if self.pinned_author_sig_pk.is_some() { 1 } else { 0 });
+        ctx.absorb(&oneof_pinned_author_sig_pk)?;
+        if let Some(ref pinned_author_sig_pk) = self.pinned_author_sig_pk {
+            ctx.absorb(pinned_author_sig_pk)?;
+        }
+        ctx.absorb(Size(self.rotation_sig_kps.len()))?;
+        for sig_kp in self.rotation_sig_kps.iter() {
+            ctx.mask(<&NBytes<U32>>::from(&sig_kp.secret.as_bytes()[..]))?;
+        }
         ctx.commit()?.squeeze(Mac(32))?;
         Ok(ctx)
     }
@@ -1161,7 +2103,10 @@ where
         ctx.mask(<&NBytes<U32>>::from(&self.sig_kp.secret.as_bytes()[..]))?
             .absorb(Uint8(self.flags))?
             .absorb(<&Bytes>::from(&self.message_encoding))?
-            .absorb(Uint64(self.uniform_payload_length as u64))?;
+            .absorb(Uint64(self.uniform_payload_length as u64))?
+            .absorb(Uint8(self.pad_keyloads as u8))?
+            .absorb(Uint8(self.require_subscription_approval as u8))?
+            .absorb(Uint8(self.ratchet as u8))?;
 
         let oneof_appinst = Uint8(if self.appinst.is_some() { 1 } else { 0 });
         ctx.absorb(&oneof_appinst)?;
@@ -1195,6 +2140,74 @@ where
                 .absorb(Uint32(cursor.branch_no))?
                 .absorb(Uint32(cursor.seq_no))?;
         }
+
+        ctx.absorb(Size(self.pending_subscriptions.len()))?;
+        for (pk, link) in self.pending_subscriptions.iter() {
+            ctx.absorb(pk)?.absorb(<&Fallback<Link>>::from(link))?;
+        }
+
+        ctx.absorb(Size(self.subscribe_links.len()))?;
+        for (pk, link) in self.subscribe_links.iter() {
+            ctx.absorb(pk)?.absorb(<&Fallback<Link>>::from(link))?;
+        }
+
+        ctx.absorb(Size(self.unsubscriptions.len()))?;
+        for (pk, timestamp) in self.unsubscriptions.iter() {
+            ctx.absorb(pk)?.absorb(Uint64(*timestamp))?;
+        }
+
+        ctx.absorb(Size(self.keyloads.len()))?;
+        for (link, recipients) in self.keyloads.iter() {
+            ctx.absorb(<&Fallback<Link>>::from(link))?
+                .absorb(Size(recipients.len()))?;
+            for id in recipients {
+                id.wrap(store.borrow(), ctx.borrow_mut()).await?;
+            }
+        }
+
+        ctx.absorb(Size(self.revoked_ids.len()))?;
+        for id in self.revoked_ids.iter() {
+            id.wrap(store.borrow(), ctx.borrow_mut()).await?;
+        }
+
+        ctx.absorb(Size(self.groups.len()))?;
+        for (name, members) in self.groups.iter() {
+            ctx.absorb(&Bytes(name.as_bytes().to_vec()))?
+                .absorb(Size(members.len()))?;
+            for id in members {
+                id.wrap(store.borrow(), ctx.borrow_mut()).await?;
+            }
+        }
+
+        self.key_tree.wrap(store.borrow(), ctx.borrow_mut()).await?;
+        ctx.absorb(Size(self.node_keys.len()))?;
+        for node in self.node_keys.iter() {
+            node.wrap(store.borrow(), ctx.borrow_mut()).await?;
+        }
+        ctx.absorb(Size(self.author_key_history.len()))?;
+        for pk in self.author_key_history.iter() {
+            ctx.absorb(pk)?;
+        }
+        ctx.absorb(Size(self.former_ke_sks.len()))?;
+        for ke_sk in self.former_ke_sks.iter() {
+            ctx.mask(<&NBytes<U32>>::from(&ke_sk.to_bytes()[..]))?;
+        }
+        ctx.absorb(Size(self.certificates.len()))?;
+        for certificate in self.certificates.iter() {
+            certificate.publisher.wrap(store, ctx).await?;
+            ctx.absorb(Uint8(certificate.scope))?
+                .absorb(Uint64(certificate.expiry))?;
+        }
+        ctx.absorb(Uint64(self.frame_length as u64))?;
+        let oneof_pinned_author_sig_pk = Uint8(if self.pinned_author_sig_pk.is_some() { 1 } else { 0 });
+        ctx.absorb(&oneof_pinned_author_sig_pk)?;
+        if let Some(ref pinned_author_sig_pk) = self.pinned_author_sig_pk {
+            ctx.absorb(pinned_author_sig_pk)?;
+        }
+        ctx.absorb(Size(self.rotation_sig_kps.len()))?;
+        for sig_kp in self.rotation_sig_kps.iter() {
+            ctx.mask(<&NBytes<U32>>::from(&sig_kp.secret.as_bytes()[..]))?;
+        }
         ctx.commit()?.squeeze(Mac(32))?;
         Ok(ctx)
     }
@@ -1218,16 +2231,50 @@ where
         store: &Store,
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        self.unwrap_state(EXPORT_VERSION, store, ctx).await
+    }
+}
+
+impl<F, Link, LG, LS, Keys> User<F, Link, LG, LS, Keys>
+where
+    F: PRP,
+    Link: HasLink + AbsorbExternalFallback<F> + AbsorbFallback<F>,
+    <Link as HasLink>::Base: Eq + fmt::Debug + fmt::Display,
+    <Link as HasLink>::Rel: Eq + fmt::Debug + SkipFallback<F> + AbsorbFallback<F>,
+    LG: LinkGenerator<Link>,
+    LS: LinkStore<F, <Link as HasLink>::Rel> + Default,
+    <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: Default + AbsorbFallback<F>,
+    Keys: KeyStore<Cursor<<Link as HasLink>::Rel>, F> + Default,
+{
+    /// Unwrap a user state exported with `version`, fields added since are left unchanged.
+    async fn unwrap_state<'c, Store, IS>(
+        &mut self,
+        version: u8,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>>
+    where
+        Store: LinkStore<F, <Link as HasLink>::Rel>,
+        IS: io::IStream,
+    {
         let mut sig_sk_bytes = NBytes::<U32>::default();
         let mut flags = Uint8(0);
         let mut message_encoding = Bytes::new();
         let mut uniform_payload_length = Uint64(0);
+        let mut pad_keyloads = Uint8(0);
+        let mut require_subscription_approval = Uint8(0);
+        let mut ratchet = Uint8(0);
         ctx
             //.absorb(&self.sig_kp.public)
             .mask(&mut sig_sk_bytes)?
             .absorb(&mut flags)?
             .absorb(&mut message_encoding)?
             .absorb(&mut uniform_payload_length)?;
+        if version >= 1 {
+            ctx.absorb(&mut pad_keyloads)?
+                .absorb(&mut require_subscription_approval)?
+                .absorb(&mut ratchet)?;
+        }
 
         let mut oneof_appinst = Uint8(0);
         ctx.absorb(&mut oneof_appinst)?
@@ -1280,6 +2327,144 @@ where
             key_store.insert_cursor(id, Cursor::new_at(link.0, branch_no.0, seq_no.0))?;
         }
 
+        let mut pending_subscriptions = Vec::new();
+        let mut subscribe_links = Vec::new();
+        let mut unsubscriptions = Vec::new();
+        let mut keyloads = Vec::new();
+        let mut revoked_ids = Vec::new();
+        let mut groups = Vec::new();
+        let mut key_tree = KeyTree::new();
+        let mut node_keys = Vec::new();
+        let mut author_key_history = Vec::new();
+        let mut former_ke_sks = Vec::new();
+        let mut certificates = Vec::new();
+        if version >= 1 {
+            let mut repeated_pending = Size(0);
+            ctx.absorb(&mut repeated_pending)?;
+            for _ in 0..repeated_pending.0 {
+                let mut pk = ed25519::PublicKey::default();
+                let mut link = Link::default();
+                ctx.absorb(&mut pk)?.absorb(<&mut Fallback<Link>>::from(&mut link))?;
+                pending_subscriptions.push((pk, link));
+            }
+
+            let mut repeated_subscribe_links = Size(0);
+            ctx.absorb(&mut repeated_subscribe_links)?;
+            for _ in 0..repeated_subscribe_links.0 {
+                let mut pk = ed25519::PublicKey::default();
+                let mut link = Link::default();
+                ctx.absorb(&mut pk)?.absorb(<&mut Fallback<Link>>::from(&mut link))?;
+                subscribe_links.push((pk, link));
+            }
+
+            let mut repeated_unsubscriptions = Size(0);
+            ctx.absorb(&mut repeated_unsubscriptions)?;
+            for _ in 0..repeated_unsubscriptions.0 {
+                let mut pk = ed25519::PublicKey::default();
+                let mut timestamp = Uint64(0);
+                ctx.absorb(&mut pk)?.absorb(&mut timestamp)?;
+                unsubscriptions.push((pk, timestamp.0));
+            }
+
+            let mut repeated_keyloads = Size(0);
+            ctx.absorb(&mut repeated_keyloads)?;
+            for _ in 0..repeated_keyloads.0 {
+                let mut link = Link::default();
+                let mut repeated_recipients = Size(0);
+                ctx.absorb(<&mut Fallback<Link>>::from(&mut link))?
+                    .absorb(&mut repeated_recipients)?;
+                let mut recipients = Vec::new();
+                for _ in 0..repeated_recipients.0 {
+                    let (id, _) = Identifier::unwrap_new(store, ctx).await?;
+                    recipients.push(id);
+                }
+                keyloads.push((link, recipients));
+            }
+
+            let mut repeated_revoked_ids = Size(0);
+            ctx.absorb(&mut repeated_revoked_ids)?;
+            for _ in 0..repeated_revoked_ids.0 {
+                let (id, _) = Identifier::unwrap_new(store, ctx).await?;
+                revoked_ids.push(id);
+            }
+
+            let mut repeated_groups = Size(0);
+            ctx.absorb(&mut repeated_groups)?;
+            for _ in 0..repeated_groups.0 {
+                let mut name = Bytes::new();
+                let mut repeated_members = Size(0);
+                ctx.absorb(&mut name)?.absorb(&mut repeated_members)?;
+                let mut members = Vec::new();
+                for _ in 0..repeated_members.0 {
+                    let (id, _) = Identifier::unwrap_new(store, ctx).await?;
+                    members.push(id);
+                }
+                groups.push((String::from_utf8_lossy(&name.0).to_string(), members));
+            }
+
+            key_tree.unwrap(store, ctx).await?;
+            let mut repeated_node_keys = Size(0);
+            ctx.absorb(&mut repeated_node_keys)?;
+            for _ in 0..repeated_node_keys.0 {
+                let (node, _) = keyload::NodeKey::unwrap_new(store, ctx).await?;
+                node_keys.push(node);
+            }
+            let mut repeated_author_keys = Size(0);
+            ctx.absorb(&mut repeated_author_keys)?;
+            for _ in 0..repeated_author_keys.0 {
+                let mut pk = ed25519::PublicKey::default();
+                ctx.absorb(&mut pk)?;
+                author_key_history.push(pk);
+            }
+            let mut repeated_former_ke_sks = Size(0);
+            ctx.absorb(&mut repeated_former_ke_sks)?;
+            for _ in 0..repeated_former_ke_sks.0 {
+                let mut ke_sk_bytes = NBytes::<U32>::default();
+                ctx.mask(&mut ke_sk_bytes)?;
+                let mut ke_sk = [0_u8; 32];
+                ke_sk.copy_from_slice(ke_sk_bytes.as_ref());
+                former_ke_sks.push(x25519::StaticSecret::from(ke_sk));
+            }
+            let mut repeated_certificates = Size(0);
+            ctx.absorb(&mut repeated_certificates)?;
+            for _ in 0..repeated_certificates.0 {
+                let (publisher, _) = Identifier::unwrap_new(store, ctx).await?;
+                let mut scope = Uint8(0);
+                let mut expiry = Uint64(0);
+                ctx.absorb(&mut scope)?.absorb(&mut expiry)?;
+                certificates.push(publisher_certificate::Certificate::new(publisher, scope.0, expiry.0));
+            }
+        }
+        let mut frame_length = Uint64(0);
+        if version >= 2 {
+            ctx.absorb(&mut frame_length)?;
+        }
+        let mut pinned_author_sig_pk = None;
+        if version >= 3 {
+            let mut oneof_pinned_author_sig_pk = Uint8(0);
+            ctx.absorb(&mut oneof_pinned_author_sig_pk)?.guard(
+                oneof_pinned_author_sig_pk.0 < 2,
+                AuthorSigPkRecoveryFailure(oneof_pinned_author_sig_pk.0),
+            )?;
+            if oneof_pinned_author_sig_pk.0 == 1 {
+                let mut pk = ed25519::PublicKey::default();
+                ctx.absorb(&mut pk)?;
+                pinned_author_sig_pk = Some(pk);
+            }
+        }
+        let mut rotation_sig_kps = Vec::new();
+        if version >= 4 {
+            let mut repeated_rotation_sig_kps = Size(0);
+            ctx.absorb(&mut repeated_rotation_sig_kps)?;
+            for _ in 0..repeated_rotation_sig_kps.0 {
+                let mut sig_sk_bytes = NBytes::<U32>::default();
+                ctx.mask(&mut sig_sk_bytes)?;
+                let secret = ed25519::SecretKey::from_bytes(sig_sk_bytes.as_ref()).unwrap();
+                let public = ed25519::PublicKey::from(&secret);
+                rotation_sig_kps.push(ed25519::Keypair { secret, public });
+            }
+        }
+
         ctx.commit()?.squeeze(Mac(32))?;
 
         let sig_sk = ed25519::SecretKey::from_bytes(sig_sk_bytes.as_ref()).unwrap();
@@ -1299,6 +2484,31 @@ where
         self.flags = flags.0;
         self.message_encoding = message_encoding.0;
         self.uniform_payload_length = uniform_payload_length.0 as usize;
+        if version >= 1 {
+            self.pad_keyloads = pad_keyloads.0 != 0;
+            self.require_subscription_approval = require_subscription_approval.0 != 0;
+            self.ratchet = ratchet.0 != 0;
+            self.pending_subscriptions = pending_subscriptions;
+            self.subscribe_links = subscribe_links;
+            self.unsubscriptions = unsubscriptions;
+            self.keyloads = keyloads;
+            self.revoked_ids = revoked_ids;
+            self.groups = groups;
+            self.key_tree = key_tree;
+            self.node_keys = node_keys;
+            self.author_key_history = author_key_history;
+            self.former_ke_sks = former_ke_sks;
+            self.certificates = certificates;
+        }
+        if version >= 2 {
+            self.frame_length = frame_length.0 as usize;
+        }
+        if version >= 3 {
+            self.pinned_author_sig_pk = pinned_author_sig_pk;
+        }
+        if version >= 4 {
+            self.rotation_sig_kps = rotation_sig_kps;
+        }
         Ok(ctx)
     }
 }
@@ -1314,11 +2524,11 @@ where
     <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: AbsorbFallback<F>,
     Keys: KeyStore<Cursor<<Link as HasLink>::Rel>, F>,
 {
+    /// Export the user state encrypted with `pwd`, see [`User::import`].
     pub async fn export(&self, flag: u8, pwd: &str) -> Result<Vec<u8>> {
-        const VERSION: u8 = 0;
         let buf_size = {
             let mut ctx = sizeof::Context::<F>::new();
-            ctx.absorb(Uint8(VERSION))?.absorb(Uint8(flag))?;
+            ctx.absorb(Uint8(EXPORT_VERSION))?.absorb(Uint8(flag))?;
             self.sizeof(&mut ctx).await?;
             ctx.get_size()
         };
@@ -1329,7 +2539,7 @@ where
             let mut ctx = wrap::Context::new(&mut buf[..]);
             let prng = prng::from_seed::<F>("IOTA Streams Channels app", pwd);
             let key = NBytes::<U32>(prng.gen_arr("user export key"));
-            ctx.absorb(Uint8(VERSION))?
+            ctx.absorb(Uint8(EXPORT_VERSION))?
                 .absorb(Uint8(flag))?
                 .absorb(External(&key))?;
             let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
@@ -1352,25 +2562,38 @@ where
     <LS as LinkStore<F, <Link as HasLink>::Rel>>::Info: Default + AbsorbFallback<F>,
     Keys: KeyStore<Cursor<<Link as HasLink>::Rel>, F> + Default,
 {
+    /// Import a user state exported with `pwd`.
+    ///
+    /// States exported before `EXPORT_VERSION` are imported with the fields added since left to
+    /// their defaults, states exported by a later version are rejected with `UserVersionRecoveryFailure`.
     pub async fn import(bytes: &[u8], flag: u8, pwd: &str) -> Result<Self> {
-        const VERSION: u8 = 0;
+        let mut user = User::default();
+        user.import_state(bytes, flag, pwd).await?;
+        Ok(user)
+    }
 
+    /// Import a user state exported with `pwd` into this user, see [`User::import`].
+    ///
+    /// Fields added since the export version of the state keep their current value.
+    pub async fn import_state(&mut self, bytes: &[u8], flag: u8, pwd: &str) -> Result<()> {
         let mut ctx = unwrap::Context::new(bytes);
         let prng = prng::from_seed::<F>("IOTA Streams Channels app", pwd);
         let key = NBytes::<U32>(prng.gen_arr("user export key"));
         let mut version = Uint8(0);
         let mut flag2 = Uint8(0);
         ctx.absorb(&mut version)?
-            .guard(version.0 == VERSION, UserVersionRecoveryFailure(VERSION, version.0))?
+            .guard(
+                version.0 <= EXPORT_VERSION,
+                UserVersionRecoveryFailure(EXPORT_VERSION, version.0),
+            )?
             .absorb(&mut flag2)?
             .guard(flag2.0 == flag, UserFlagRecoveryFailure(flag, flag2.0))?
             .absorb(External(&key))?;
 
-        let mut user = User::default();
         let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
-        user.unwrap(&store, &mut ctx).await?;
+        self.unwrap_state(version.0, &store, &mut ctx).await?;
         try_or!(ctx.stream.is_empty(), InputStreamNotFullyConsumed(ctx.stream.len()))?;
-        Ok(user)
+        Ok(())
     }
 }
 
@@ -1421,3 +2644,174 @@ impl<'a> Lookup<&Identifier, &'a x25519::StaticSecret> for OwnKeys<'a> {
         }
     }
 }
+
+#[cfg(all(test, feature = "tangle"))]
+mod test {
+    use super::*;
+    use crate::api::{
+        tangle::{
+            Address,
+            KeyStore,
+            LinkGen,
+            LinkStore,
+        },
+        DefaultF,
+    };
+    use iota_streams_core::{
+        ensure,
+        Errors,
+    };
+
+    type TangleUser = User<DefaultF, Address, LinkGen, LinkStore, KeyStore>;
+
+    fn keypair(seed: u8) -> ed25519::Keypair {
+        let secret = ed25519::SecretKey::from_bytes(&[seed; ed25519::SECRET_KEY_LENGTH]).unwrap();
+        let public = ed25519::PublicKey::from(&secret);
+        ed25519::Keypair { secret, public }
+    }
+
+    /// Export an Author state in the layout of version 0, holding neither links nor keys.
+    fn export_v0(sig_sk: &[u8; 32], pwd: &str) -> Result<Vec<u8>> {
+        let mut buf = vec![0_u8; 256];
+        let unused = {
+            let mut ctx = wrap::Context::<DefaultF, _>::new(&mut buf[..]);
+            let prng = prng::from_seed::<DefaultF>("IOTA Streams Channels app", pwd);
+            let key = NBytes::<U32>(prng.gen_arr("user export key"));
+            ctx.absorb(Uint8(0))?
+                .absorb(Uint8(0))?
+                .absorb(External(&key))?
+                .mask(<&NBytes<U32>>::from(&sig_sk[..]))?
+                .absorb(Uint8(0))?
+                .absorb(&Bytes("utf-8".as_bytes().to_vec()))?
+                .absorb(Uint64(0))?
+                .absorb(Uint8(0))?
+                .absorb(Uint8(0))?
+                .absorb(Size(0))?
+                .absorb(Size(0))?
+                .commit()?
+                .squeeze(Mac(32))?;
+            ctx.stream.len()
+        };
+        buf.truncate(buf.len() - unused);
+        Ok(buf)
+    }
+
+    /// States of earlier export versions are imported with the fields added since left to their defaults.
+    async fn export_versions_example() -> Result<()> {
+        let sig_sk = [1_u8; 32];
+        let sig_pk = ed25519::PublicKey::from(&ed25519::SecretKey::from_bytes(&sig_sk).unwrap());
+        let mut bytes = export_v0(&sig_sk, "pwdLegacy")?;
+        let user = TangleUser::import(&bytes, 0, "pwdLegacy").await?;
+        ensure!(user.sig_kp.public == sig_pk, "signature key should be imported");
+        ensure!(user.message_encoding == b"utf-8", "message encoding should be imported");
+        ensure!(
+            !user.ratchet && user.pending_subscriptions.is_empty() && user.certificates.is_empty(),
+            "fields added since version 0 should be left to their defaults"
+        );
+
+        let user = TangleUser::import(&user.export(0, "pwdLegacy").await?, 0, "pwdLegacy").await?;
+        ensure!(
+            user.sig_kp.public == sig_pk,
+            "signature key should survive a fresh export"
+        );
+
+        bytes[0] = EXPORT_VERSION + 1;
+        let e = TangleUser::import(&bytes, 0, "pwdLegacy").await.err();
+        ensure!(
+            matches!(
+                e.as_ref().and_then(|e| e.downcast_ref::<Errors>()),
+                Some(UserVersionRecoveryFailure(..))
+            ),
+            "states of later export versions should be rejected"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_export_versions_scenario() {
+        smol::block_on(export_versions_example()).unwrap();
+    }
+
+    /// The frame length survives export, and states exported before it keep the frame length of the user.
+    async fn frame_length_export_example() -> Result<()> {
+        let user = TangleUser {
+            frame_length: 100,
+            ..TangleUser::default()
+        };
+        let bytes = user.export(0, "pwdFrames").await?;
+        ensure!(
+            TangleUser::import(&bytes, 0, "pwdFrames").await?.frame_length == 100,
+            "frame length should survive export"
+        );
+
+        let mut user = TangleUser {
+            frame_length: 200,
+            ..TangleUser::default()
+        };
+        user.import_state(&export_v0(&[1_u8; 32], "pwdFrames")?, 0, "pwdFrames")
+            .await?;
+        ensure!(
+            user.frame_length == 200,
+            "states exported before the frame length should keep the frame length of the user"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_frame_length_export_scenario() {
+        smol::block_on(frame_length_export_example()).unwrap();
+    }
+
+    /// The pinned Author public key survives export.
+    async fn pinned_author_export_example() -> Result<()> {
+        let author_pk = ed25519::PublicKey::from(&ed25519::SecretKey::from_bytes(&[2_u8; 32]).unwrap());
+        let user = TangleUser {
+            pinned_author_sig_pk: Some(author_pk),
+            ..TangleUser::default()
+        };
+        let bytes = user.export(1, "pwdPinned").await?;
+        ensure!(
+            TangleUser::import(&bytes, 1, "pwdPinned").await?.pinned_author_sig_pk == Some(author_pk),
+            "pinned author key should survive export"
+        );
+
+        let bytes = TangleUser::default().export(1, "pwdPinned").await?;
+        ensure!(
+            TangleUser::import(&bytes, 1, "pwdPinned")
+                .await?
+                .pinned_author_sig_pk
+                .is_none(),
+            "no author key should be pinned"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_pinned_author_export_scenario() {
+        smol::block_on(pinned_author_export_example()).unwrap();
+    }
+
+    /// Key pairs introduced by key rotations survive export.
+    async fn rotation_keys_export_example() -> Result<()> {
+        let mut user = TangleUser::default();
+        let sig_kps = vec![keypair(3), keypair(4)];
+        let sig_pks: Vec<_> = sig_kps.iter().map(|kp| kp.public).collect();
+        user.set_rotation_keys(sig_kps);
+        let bytes = user.export(0, "pwdRotation").await?;
+        let user = TangleUser::import(&bytes, 0, "pwdRotation").await?;
+        ensure!(
+            user.rotation_sig_kps.iter().map(|kp| kp.public).collect::<Vec<_>>() == sig_pks,
+            "rotation key pairs should survive export"
+        );
+        ensure!(
+            user.rotation_sig_kps[0].secret.as_bytes() == &[3_u8; 32],
+            "rotation secret keys should survive export"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_rotation_keys_export_scenario() {
+        smol::block_on(rotation_keys_export_example()).unwrap();
+    }
+}
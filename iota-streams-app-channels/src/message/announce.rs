@@ -7,6 +7,7 @@
 //! ```ddml
 //! message Announce {
 //!     absorb u8 ed25519pk[32];
+//!     absorb u8 flags;
 //!     commit;
 //!     squeeze external u8 tag[32];
 //!     ed25519(tag) sig;
@@ -17,6 +18,9 @@
 //!
 //! * `ed25519pk` -- channel owner's Ed25519 public key.
 //!
+//! * `flags` -- channel type and options, see [`FLAG_ANONYMOUS_KEYLOADS`] and
+//! [`FLAG_CERTIFIED_PUBLISHERS`].
+//!
 //! * `tag` -- hash-value to be signed.
 //!
 //! * `sig` -- signature of `tag` field produced with the Ed25519 private key corresponding to ed25519pk`.
@@ -39,6 +43,13 @@ use iota_streams_ddml::{
     types::*,
 };
 
+/// Bits of the channel flags holding the channel type.
+pub const CHANNEL_TYPE_MASK: u8 = 3;
+/// Channel flag: keyloads hide the identifiers of their recipients behind anonymous slots.
+pub const FLAG_ANONYMOUS_KEYLOADS: u8 = 4;
+/// Channel flag: packets are only accepted signed by publishers certified by the Author.
+pub const FLAG_CERTIFIED_PUBLISHERS: u8 = 8;
+
 pub struct ContentWrap<'a, F> {
     sig_kp: &'a ed25519::Keypair,
     flags: Uint8,
@@ -0,0 +1,124 @@
+//! `KeyRotation` message content. This message is published by the Author to replace its Ed25519
+//! signing key without creating a new channel.
+//!
+//! ```ddml
+//! message KeyRotation {
+//!     join link msgid;
+//!     absorb u8 ed25519pk[32];
+//!     absorb u8 new_ed25519pk[32];
+//!     commit;
+//!     squeeze external byte hash[32];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields:
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `ed25519pk` -- Author's current Ed25519 public key.
+//!
+//! * `new_ed25519pk` -- Ed25519 public key replacing `ed25519pk`.
+//!
+//! * `hash` -- hash value to be signed.
+//!
+//! * `sig` -- message signature generated with the current private key, rotations thus form a
+//! chain starting at the announced key.
+
+use iota_streams_app::message::{
+    self,
+    HasLink,
+};
+use iota_streams_core::{
+    async_trait,
+    prelude::Box,
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+pub struct ContentWrap<'a, F, Link: HasLink> {
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) new_sig_pk: &'a ed25519::PublicKey,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+#[async_trait(?Send)]
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        ctx.join(&store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(self.new_sig_pk)?
+            .commit()?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.join(store, self.link)?
+            .absorb(&self.sig_kp.public)?
+            .absorb(self.new_sig_pk)?
+            .commit()?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+#[derive(Default)]
+pub struct ContentUnwrap<F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) sig_pk: ed25519::PublicKey,
+    pub(crate) new_sig_pk: ed25519::PublicKey,
+    _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+#[async_trait(?Send)]
+impl<F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    async fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        ctx.join(store, &mut self.link)?
+            .absorb(&mut self.sig_pk)?
+            .absorb(&mut self.new_sig_pk)?
+            .commit()?
+            .ed25519(&self.sig_pk, HashSig)?;
+        Ok(ctx)
+    }
+}
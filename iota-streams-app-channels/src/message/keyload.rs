@@ -7,6 +7,15 @@
 //!     join link msgid;
 //!     absorb u8 nonce[16];
 //!     skip repeated {
+//!         mask u8 recipient_type;
+//!         mask u8 recipient[16 | 32];
+//!         fork;
+//!         absorb external u8 recipient_key[32] | absorb u8 eph_key[32], x25519(eph_key) u8 xkey[32];
+//!         commit;
+//!         mask u8 node_key[32];
+//!         mask u64 position;
+//!     }
+//!     skip repeated {
 //!         fork;
 //!         mask u8 id[16];
 //!         absorb external u8 psk[32];
@@ -21,7 +30,21 @@
 //!         commit;
 //!         mask u8 key[32];
 //!     }
+//!     skip oneof {
+//!         fork;
+//!         mask u8 group_id[16];
+//!         absorb external u8 group_key[32];
+//!         commit;
+//!         mask u8 key[32];
+//!     }
 //!     absorb external u8 key[32];
+//!     commit;  // anonymous keyloads only
+//!     repeated {  // anonymous keyloads only
+//!         mask u8 recipient_type;
+//!         mask u8 recipient[16 | 32];
+//!     }
+//!     mask u32 padding_length;
+//!     mask u8 padding[padding_length];
 //!     commit;
 //! }
 //! ```
@@ -41,14 +64,58 @@
 //!
 //! * `xkey` -- X25519 common key.
 //!
+//! * `recipient_type`, `recipient` -- Identifier of the key encapsulating a node key: the identifier
+//! of a pre-shared key or of a node key (type 1, 16 bytes), or an Ed25519 public key whose X25519
+//! key is used (type 0, 32 bytes).
+//!
+//! * `recipient_key` -- Pre-shared key or node key encapsulating a node key.
+//!
+//! * `node_key` -- New key of a node of the Author's key tree, encapsulated under the key of one of
+//! the children of the node (or for a leaf, under the X25519 key of its member).
+//!
+//! * `position` -- Position of the node in the key tree, a newer key replaces the one held for the same
+//! position.
+//!
+//! * `group_id`, `group_key` -- Identifier and key of the root of the key tree.
+//!
 //! * `key` -- Session key; a legit recipient gets it from corresponding fork.
 //!
+//! * `padding` -- zero filler extending the message to the uniform payload length.
+//!
 //! * `sig` -- Optional signature; allows to authenticate keyload.
 //!
+//! In channels with anonymous keyloads the forks of the first three lists are replaced with
+//! anonymous slots that recipients trial-decrypt with their pre-shared keys, node keys and X25519 key:
+//!
+//! ```ddml
+//! anonymous slot {
+//!     absorb u8 eph_key[32];
+//!     fork;
+//!     absorb external u8 psk[32] | x25519(eph_key) u8 xkey[32];
+//!     commit;
+//!     mask u8 key[32];
+//!     mask u64 position;  // node key updates only
+//!     commit;
+//!     squeeze u8 mac[16];
+//! }
+//! ```
+//!
+//! Slots for pre-shared keys carry an ephemeral key too, so that all slots look alike. The
+//! identifiers of the recipients, `recipient_type` and `recipient` as in key updates, are masked
+//! once the session key is absorbed and committed instead, one for each slot of the second list, so
+//! that recipients still learn the other publishers of the channel while the keystream stays out of
+//! reach of anyone lacking the session key.
+//!
+//! Messages of version `STREAMS_1_VER` carry neither node key updates, the group key, `padding`
+//! nor anonymous slots.
+//!
 //! Notes:
-//! 1) Keys identities are not encrypted and may be linked to recipients identities.
+//! 1) Keys identities are not encrypted and may be linked to recipients identities, unless slots
+//!     are anonymous. The number of recipients is disclosed either way.
 //! 2) Keyload is not authenticated (signed). It can later be implicitly authenticated
 //!     via `SignedPacket`.
+//! 3) Node keys are learned in order, so the key of a node must be updated before the keys of
+//!     its ancestors. Identifiers of node keys are derived from the keys themselves.
 
 use crate::Lookup;
 
@@ -57,8 +124,14 @@ use iota_streams_app::{
     identifier::Identifier,
     message::{
         self,
+        ContentSizeof as _,
+        ContentUnwrap as _,
         ContentUnwrapNew,
+        ContentWrap as _,
         HasLink,
+        Padding,
+        STREAMS_1_VER,
+        STREAMS_VER,
     },
 };
 use iota_streams_core::{
@@ -68,13 +141,17 @@ use iota_streams_core::{
         Box,
         Vec,
     },
+    prng,
     psk,
     sponge::{
         prp::PRP,
         spongos,
     },
     wrapped_err,
-    Errors::BadIdentifier,
+    Errors::{
+        BadIdentifier,
+        BadOneof,
+    },
     Result,
     WrappedError,
 };
@@ -92,6 +169,124 @@ use iota_streams_ddml::{
     types::*,
 };
 
+/// Key of a node of a key tree.
+#[derive(Clone)]
+pub struct NodeKey {
+    /// Position of the node in the tree.
+    pub position: u64,
+    pub id: Identifier,
+    pub key: psk::Psk,
+}
+
+impl NodeKey {
+    /// Node key with an identifier derived from the key.
+    pub fn new<F: PRP>(position: u64, key: psk::Psk) -> Self {
+        Self {
+            position,
+            id: Identifier::PskId(psk::pskid_from_psk::<F>(&key)),
+            key,
+        }
+    }
+}
+
+/// New key of a node of a key tree encapsulated for one recipient.
+#[derive(Clone)]
+pub struct KeyUpdate {
+    /// Identifier of the key encapsulating the node key.
+    pub recipient: Identifier,
+    /// Pre-shared key or X25519 public key of the recipient.
+    pub recipient_key: Vec<u8>,
+    pub node: NodeKey,
+}
+
+#[async_trait(?Send)]
+impl<F: PRP> message::ContentSizeof<F> for NodeKey {
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        ctx.absorb(Uint64(self.position))?;
+        self.id.sizeof(ctx).await?;
+        ctx.mask(<&NBytes<psk::PskSize>>::from(&self.key))?;
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> message::ContentWrap<F, Store> for NodeKey {
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.absorb(Uint64(self.position))?;
+        self.id.wrap(store, ctx).await?;
+        ctx.mask(<&NBytes<psk::PskSize>>::from(&self.key))?;
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> ContentUnwrapNew<F, Store> for NodeKey {
+    async fn unwrap_new<'c, IS: io::IStream>(
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<(Self, &'c mut unwrap::Context<F, IS>)> {
+        let mut position = Uint64(0);
+        let mut key = psk::Psk::default();
+        ctx.absorb(&mut position)?;
+        let (id, ctx) = Identifier::unwrap_new(store, ctx).await?;
+        ctx.mask(<&mut NBytes<psk::PskSize>>::from(&mut key))?;
+        let node = NodeKey {
+            position: position.0,
+            id,
+            key,
+        };
+        Ok((node, ctx))
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP> message::ContentSizeof<F> for KeyUpdate {
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        self.recipient.sizeof(ctx).await?;
+        ctx.mask(&Bytes(self.recipient_key.clone()))?;
+        self.node.sizeof(ctx).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> message::ContentWrap<F, Store> for KeyUpdate {
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        self.recipient.wrap(store, ctx).await?;
+        ctx.mask(&Bytes(self.recipient_key.clone()))?;
+        self.node.wrap(store, ctx).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, Store> ContentUnwrapNew<F, Store> for KeyUpdate {
+    async fn unwrap_new<'c, IS: io::IStream>(
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<(Self, &'c mut unwrap::Context<F, IS>)> {
+        let (recipient, ctx) = Identifier::unwrap_new(store, ctx).await?;
+        let mut recipient_key = Bytes::new();
+        ctx.mask(&mut recipient_key)?;
+        let (node, ctx) = NodeKey::unwrap_new(store, ctx).await?;
+        let update = KeyUpdate {
+            recipient,
+            recipient_key: recipient_key.0,
+            node,
+        };
+        Ok((update, ctx))
+    }
+}
+
+/// Length of the MAC closing anonymous slots.
+const SLOT_MAC_SIZE: usize = 16;
+
 pub struct ContentWrap<'a, F, Link>
 where
     Link: HasLink,
@@ -100,6 +295,11 @@ where
     pub nonce: NBytes<U16>,
     pub key: NBytes<U32>,
     pub(crate) keys: Vec<(&'a Identifier, Vec<u8>)>,
+    pub(crate) updates: &'a [KeyUpdate],
+    pub(crate) group_key: Option<&'a NodeKey>,
+    /// Hide recipient identifiers behind anonymous slots.
+    pub(crate) anonymous: bool,
+    pub(crate) padding: Padding,
     pub(crate) sig_kp: &'a ed25519::Keypair,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
@@ -118,28 +318,68 @@ where
 
         // fork into new context in order to hash Identifiers
         {
+            ctx.absorb(Size(self.updates.len()))?;
+            for update in self.updates {
+                if self.anonymous {
+                    ctx.absorb(&NBytes::<U32>::default())?
+                        .commit()?
+                        .mask(<&NBytes<psk::PskSize>>::from(&update.node.key))?
+                        .mask(Uint64(update.node.position))?
+                        .commit()?
+                        .squeeze(Mac(SLOT_MAC_SIZE))?;
+                } else {
+                    let ctx = update.recipient.sizeof(ctx).await?;
+                    encapsulate_sizeof(ctx, &update.recipient, &update.recipient_key, &update.node.key)?;
+                    ctx.mask(Uint64(update.node.position))?;
+                }
+            }
+
             ctx.absorb(repeated_keys)?;
             // Loop through provided identifiers, masking the shared key for each one
             for key_pair in self.keys.clone().into_iter() {
                 let (id, store_id) = key_pair;
-                let ctx = id.sizeof(ctx).await?;
-                // fork in order to skip the actual keyload data which may be unavailable to all recipients
-                {
-                    match &id {
-                        Identifier::PskId(_pskid) => ctx
-                            .absorb(External(<&NBytes<psk::PskSize>>::from(<&[u8]>::from(&store_id))))?
-                            .commit()?
-                            .mask(&self.key)?,
-                        Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id.as_ref()) {
-                            Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), &self.key)?,
-                            Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
-                        },
-                    };
+                if self.anonymous {
+                    ctx.absorb(&NBytes::<U32>::default())?
+                        .commit()?
+                        .mask(&self.key)?
+                        .commit()?
+                        .squeeze(Mac(SLOT_MAC_SIZE))?;
+                } else {
+                    let ctx = id.sizeof(ctx).await?;
+                    // fork in order to skip the actual keyload data which may be unavailable to all recipients
+                    {
+                        match &id {
+                            Identifier::PskId(_pskid) => ctx
+                                .absorb(External(<&NBytes<psk::PskSize>>::from(<&[u8]>::from(&store_id))))?
+                                .commit()?
+                                .mask(&self.key)?,
+                            Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id.as_ref()) {
+                                Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), &self.key)?,
+                                Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
+                            },
+                        };
+                    }
                 }
             }
+
+            ctx.absorb(Uint8(self.group_key.is_some() as u8))?;
+            if let Some(group_key) = self.group_key {
+                let ctx = group_key.id.sizeof(ctx).await?;
+                ctx.absorb(External(<&NBytes<psk::PskSize>>::from(&group_key.key)))?
+                    .commit()?
+                    .mask(&self.key)?;
+            }
         }
 
         ctx.absorb(External(&self.key))?;
+        if self.anonymous {
+            // Key the spongos with the session key before masking the identifiers
+            ctx.commit()?;
+            for (id, _) in &self.keys {
+                id.sizeof(ctx).await?;
+            }
+        }
+        self.padding.sizeof(ctx).await?;
         // Fork for signing
         ctx.ed25519(self.sig_kp, HashSig)?;
         ctx.commit()?;
@@ -167,26 +407,63 @@ where
         // fork into new context in order to hash Identifiers
         let saved_fork = ctx.spongos.fork();
         {
+            ctx.absorb(Size(self.updates.len()))?;
+            for update in self.updates {
+                if self.anonymous {
+                    let inner_fork = open_anonymous_slot(ctx, &update.recipient, &update.recipient_key)?;
+                    ctx.commit()?
+                        .mask(<&NBytes<psk::PskSize>>::from(&update.node.key))?
+                        .mask(&Uint64(update.node.position))?
+                        .commit()?
+                        .squeeze(Mac(SLOT_MAC_SIZE))?;
+                    ctx.spongos = inner_fork;
+                } else {
+                    let ctx = update.recipient.wrap(store, ctx).await?;
+                    let inner_fork = ctx.spongos.fork();
+                    {
+                        encapsulate_wrap(ctx, &update.recipient, &update.recipient_key, &update.node.key)?;
+                        ctx.mask(&Uint64(update.node.position))?;
+                    }
+                    ctx.spongos = inner_fork;
+                }
+            }
+
             ctx.absorb(repeated_keys)?;
             // Loop through provided identifiers, masking the shared key for each one
             for key_pair in self.keys.clone().into_iter() {
                 let (id, store_id) = key_pair;
-                let ctx = id.wrap(store, ctx).await?;
+                if self.anonymous {
+                    let inner_fork = open_anonymous_slot(ctx, id, &store_id)?;
+                    ctx.commit()?.mask(&self.key)?.commit()?.squeeze(Mac(SLOT_MAC_SIZE))?;
+                    ctx.spongos = inner_fork;
+                } else {
+                    let ctx = id.wrap(store, ctx).await?;
 
-                // fork in order to skip the actual keyload data which may be unavailable to all recipients
-                let inner_fork = ctx.spongos.fork();
-                {
-                    match &id {
-                        Identifier::PskId(_pskid) => ctx
-                            .absorb(External(<&NBytes<psk::PskSize>>::from(<&[u8]>::from(&store_id))))?
-                            .commit()?
-                            .mask(&self.key)?,
-                        Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id.as_ref()) {
-                            Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), &self.key)?,
-                            Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
-                        },
-                    };
+                    // fork in order to skip the actual keyload data which may be unavailable to all recipients
+                    let inner_fork = ctx.spongos.fork();
+                    {
+                        match &id {
+                            Identifier::PskId(_pskid) => ctx
+                                .absorb(External(<&NBytes<psk::PskSize>>::from(<&[u8]>::from(&store_id))))?
+                                .commit()?
+                                .mask(&self.key)?,
+                            Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id.as_ref()) {
+                                Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), &self.key)?,
+                                Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
+                            },
+                        };
+                    }
+                    ctx.spongos = inner_fork;
                 }
+            }
+
+            ctx.absorb(&Uint8(self.group_key.is_some() as u8))?;
+            if let Some(group_key) = self.group_key {
+                let ctx = group_key.id.wrap(store, ctx).await?;
+                let inner_fork = ctx.spongos.fork();
+                ctx.absorb(External(<&NBytes<psk::PskSize>>::from(&group_key.key)))?
+                    .commit()?
+                    .mask(&self.key)?;
                 ctx.spongos = inner_fork;
             }
             ctx.commit()?.squeeze(&mut id_hash)?;
@@ -194,6 +471,14 @@ where
         ctx.spongos = saved_fork;
 
         ctx.absorb(External(&self.key))?;
+        if self.anonymous {
+            // Key the spongos with the session key before masking the identifiers
+            ctx.commit()?;
+            for (id, _) in &self.keys {
+                id.wrap(store, ctx).await?;
+            }
+        }
+        self.padding.wrap(store, ctx).await?;
         // Fork the context to sign
         let signature_fork = ctx.spongos.fork();
         ctx.absorb(&id_hash)?.ed25519(self.sig_kp, HashSig)?;
@@ -212,8 +497,16 @@ where
     pub(crate) psk_store: PskStore,
     pub(crate) ke_sk_store: KeSkStore,
     pub(crate) key_ids: Vec<Identifier>,
+    /// Node keys of the key tree held by the recipient, updated with the keys learned from the keyload.
+    pub(crate) node_keys: Vec<NodeKey>,
+    /// Own X25519 secret key if slots are anonymous, they are then trial-decrypted with it and with
+    /// `trial_psks`.
+    pub(crate) trial_ke_sk: Option<&'a x25519::StaticSecret>,
+    pub(crate) trial_psks: Vec<psk::Psk>,
     pub key: Option<NBytes<U32>>, // TODO: unify with spongos::Spongos::<F>::KEY_SIZE
     pub(crate) sig_pk: &'a ed25519::PublicKey,
+    /// Version of the message, from its header.
+    pub(crate) version: Uint8,
     _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -230,11 +523,43 @@ where
             psk_store,
             ke_sk_store,
             key_ids: Vec::new(),
+            node_keys: Vec::new(),
+            trial_ke_sk: None,
+            trial_psks: Vec::new(),
             key: None,
             sig_pk,
+            version: STREAMS_VER,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Unwrap a message of `version`.
+    pub fn with_version(mut self, version: Uint8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Start from the node keys already held by the recipient.
+    pub fn with_node_keys(mut self, node_keys: Vec<NodeKey>) -> Self {
+        self.node_keys = node_keys;
+        self
+    }
+
+    /// Expect anonymous slots and trial-decrypt them with the recipient's own keys.
+    pub fn with_anonymous_slots(mut self, ke_sk: &'a x25519::StaticSecret, psks: Vec<psk::Psk>) -> Self {
+        self.trial_ke_sk = Some(ke_sk);
+        self.trial_psks = psks;
+        self
+    }
+
+    fn node_key(&self, id: &Identifier) -> Option<psk::Psk> {
+        self.node_keys.iter().find(|node| node.id == *id).map(|node| node.key)
+    }
+
+    fn learn_node_key(&mut self, node: NodeKey) {
+        self.node_keys.retain(|known| known.position != node.position);
+        self.node_keys.push(node);
+    }
 }
 
 #[async_trait(?Send)]
@@ -260,59 +585,165 @@ where
         let mut repeated_keys = Size(0);
         ctx.join(store, &mut self.link)?.absorb(&mut self.nonce)?;
 
+        // Node key updates, the group key, padding and anonymous slots are absent from messages of
+        // the first version
+        let legacy = self.version == STREAMS_1_VER;
+        let trial_ke_sk = if legacy { None } else { self.trial_ke_sk };
+
         // Fork to recover identifiers
         {
             let saved_fork = ctx.spongos.fork();
-            ctx.absorb(&mut repeated_keys)?;
-            // Loop through provided number of identifiers and subsequent keys
-            for _ in 0..repeated_keys.0 {
-                let (id, ctx) = Identifier::unwrap_new(store, ctx).await?;
 
-                // Fork in order to recover key that is meant for the recipient id
-                {
+            let mut repeated_updates = Size(0);
+            if !legacy {
+                ctx.absorb(&mut repeated_updates)?;
+            }
+            // Learn the node keys encapsulated for the recipient, skip the others
+            for _ in 0..repeated_updates.0 {
+                if let Some(ke_sk) = trial_ke_sk {
+                    let mut eph_pk = x25519::PublicKey::from([0_u8; 32]);
+                    ctx.absorb(&mut eph_pk)?;
+                    let slot = ctx.stream.try_advance(psk::PSK_SIZE + 8 + SLOT_MAC_SIZE)?.to_vec();
+                    let mut secrets: Vec<psk::Psk> = self.node_keys.iter().map(|node| node.key).collect();
+                    secrets.extend(self.trial_psks.iter().copied());
+                    if let Some((node_key, position)) =
+                        unwrap_anonymous_slot(&ctx.spongos, &eph_pk, &slot, &secrets, ke_sk, true)
+                    {
+                        self.learn_node_key(NodeKey::new::<F>(position, node_key.0));
+                    }
+                } else {
+                    let (id, ctx) = Identifier::unwrap_new(store, ctx).await?;
                     let internal_fork = ctx.spongos.fork();
-                    match &id {
-                        Identifier::PskId(_id) => {
-                            if let Some(psk) = self.psk_store.lookup(&id) {
-                                let mut key = NBytes::<U32>::default();
+                    let mut node_key = psk::Psk::default();
+                    let found = match &id {
+                        Identifier::PskId(_id) => match self.node_key(&id).or_else(|| self.psk_store.lookup(&id)) {
+                            Some(psk) => {
                                 ctx.absorb(External(<&NBytes<psk::PskSize>>::from(&psk)))?
                                     .commit()?
-                                    .mask(&mut key)?;
-                                self.key = Some(key);
-                                self.key_ids.push(id);
-                                // Ok(ctx)
-                            } else {
-                                self.key_ids.push(id);
-                                // Just drop the rest of the forked message so not to waste Spongos operations
-                                let n = Size(spongos::KeySize::<F>::USIZE);
-                                ctx.drop(n)?;
+                                    .mask(<&mut NBytes<psk::PskSize>>::from(&mut node_key))?;
+                                true
                             }
-                        }
-                        Identifier::EdPubKey(_ke_pk) => {
-                            if let Some(ke_sk) = self.ke_sk_store.lookup(&id) {
-                                let mut key = NBytes::<U32>::default();
-                                ctx.x25519(ke_sk, &mut key)?;
-                                self.key = Some(key);
-                                // Save the relevant public key
-                                self.key_ids.push(id);
-                            } else {
-                                self.key_ids.push(id);
-                                // Just drop the rest of the forked message so not to waste Spongos operations
-                                // TODO: key length
-                                let n = Size(64);
-                                ctx.drop(n)?;
+                            None => {
+                                ctx.drop(Size(spongos::KeySize::<F>::USIZE + 8))?;
+                                false
                             }
-                        }
+                        },
+                        Identifier::EdPubKey(_ke_pk) => match self.ke_sk_store.lookup(&id) {
+                            Some(ke_sk) => {
+                                ctx.x25519(ke_sk, <&mut NBytes<psk::PskSize>>::from(&mut node_key))?;
+                                true
+                            }
+                            None => {
+                                ctx.drop(Size(64 + 8))?;
+                                false
+                            }
+                        },
+                    };
+                    if found {
+                        let mut position = Uint64(0);
+                        ctx.mask(&mut position)?;
+                        self.learn_node_key(NodeKey::new::<F>(position.0, node_key));
                     }
                     ctx.spongos = internal_fork;
                 }
             }
+
+            ctx.absorb(&mut repeated_keys)?;
+            // Loop through provided number of identifiers and subsequent keys
+            for _ in 0..repeated_keys.0 {
+                if let Some(ke_sk) = trial_ke_sk {
+                    let mut eph_pk = x25519::PublicKey::from([0_u8; 32]);
+                    ctx.absorb(&mut eph_pk)?;
+                    let slot = ctx
+                        .stream
+                        .try_advance(spongos::KeySize::<F>::USIZE + SLOT_MAC_SIZE)?
+                        .to_vec();
+                    if let Some((key, _)) =
+                        unwrap_anonymous_slot(&ctx.spongos, &eph_pk, &slot, &self.trial_psks, ke_sk, false)
+                    {
+                        self.key = Some(key);
+                    }
+                } else {
+                    let (id, ctx) = Identifier::unwrap_new(store, ctx).await?;
+
+                    // Fork in order to recover key that is meant for the recipient id
+                    {
+                        let internal_fork = ctx.spongos.fork();
+                        match &id {
+                            Identifier::PskId(_id) => {
+                                if let Some(psk) = self.psk_store.lookup(&id) {
+                                    let mut key = NBytes::<U32>::default();
+                                    ctx.absorb(External(<&NBytes<psk::PskSize>>::from(&psk)))?
+                                        .commit()?
+                                        .mask(&mut key)?;
+                                    self.key = Some(key);
+                                    self.key_ids.push(id);
+                                    // Ok(ctx)
+                                } else {
+                                    self.key_ids.push(id);
+                                    // Just drop the rest of the forked message so not to waste Spongos operations
+                                    let n = Size(spongos::KeySize::<F>::USIZE);
+                                    ctx.drop(n)?;
+                                }
+                            }
+                            Identifier::EdPubKey(_ke_pk) => {
+                                if let Some(ke_sk) = self.ke_sk_store.lookup(&id) {
+                                    let mut key = NBytes::<U32>::default();
+                                    ctx.x25519(ke_sk, &mut key)?;
+                                    self.key = Some(key);
+                                    // Save the relevant public key
+                                    self.key_ids.push(id);
+                                } else {
+                                    self.key_ids.push(id);
+                                    // Just drop the rest of the forked message so not to waste Spongos operations
+                                    // TODO: key length
+                                    let n = Size(64);
+                                    ctx.drop(n)?;
+                                }
+                            }
+                        }
+                        ctx.spongos = internal_fork;
+                    }
+                }
+            }
+
+            let mut oneof_group_key = Uint8(0);
+            if !legacy {
+                ctx.absorb(&mut oneof_group_key)?
+                    .guard(oneof_group_key.0 < 2, BadOneof)?;
+            }
+            if oneof_group_key.0 == 1 {
+                let (id, ctx) = Identifier::unwrap_new(store, ctx).await?;
+                let internal_fork = ctx.spongos.fork();
+                if let Some(group_key) = self.node_key(&id).or_else(|| self.psk_store.lookup(&id)) {
+                    let mut key = NBytes::<U32>::default();
+                    ctx.absorb(External(<&NBytes<psk::PskSize>>::from(&group_key)))?
+                        .commit()?
+                        .mask(&mut key)?;
+                    self.key = Some(key);
+                } else {
+                    let n = Size(spongos::KeySize::<F>::USIZE);
+                    ctx.drop(n)?;
+                }
+                ctx.spongos = internal_fork;
+            }
             ctx.commit()?.squeeze(&mut id_hash)?;
             ctx.spongos = saved_fork;
         }
 
         if let Some(ref key) = self.key {
             ctx.absorb(External(key))?;
+            if trial_ke_sk.is_some() {
+                ctx.commit()?;
+                for _ in 0..repeated_keys.0 {
+                    let (id, _) = Identifier::unwrap_new(store, ctx).await?;
+                    self.key_ids.push(id);
+                }
+            }
+            if !legacy {
+                let mut padding = Padding::default();
+                padding.unwrap(store, ctx).await?;
+            }
 
             // Fork for signature verification
             let signature_fork = ctx.spongos.fork();
@@ -326,4 +757,212 @@ where
     }
 }
 
+/// Absorb the ephemeral X25519 public key opening an anonymous slot and key a fork of the spongos
+/// state with the secret shared with the recipient. Returns the spongos state to restore once the
+/// slot is wrapped.
+///
+/// Slots for pre-shared keys carry an ephemeral key as well, so that they cannot be told apart from
+/// slots for public keys.
+fn open_anonymous_slot<F: PRP, OS: io::OStream>(
+    ctx: &mut wrap::Context<F, OS>,
+    id: &Identifier,
+    store_id: &[u8],
+) -> Result<spongos::Spongos<F>> {
+    let mut eph_sk_bytes = [0_u8; 32];
+    eph_sk_bytes.copy_from_slice(&prng::random_key());
+    let eph_sk = x25519::StaticSecret::from(eph_sk_bytes);
+    let eph_pk = x25519::PublicKey::from(&eph_sk);
+    ctx.absorb(&eph_pk)?;
+    let inner_fork = ctx.spongos.fork();
+    match id {
+        Identifier::PskId(_pskid) => ctx.absorb(External(<&NBytes<psk::PskSize>>::from(store_id)))?,
+        Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id) {
+            Ok(slice) => ctx.x25519(&eph_sk, &x25519::PublicKey::from(slice))?,
+            Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
+        },
+    };
+    Ok(inner_fork)
+}
+
+/// Trial-decrypt an anonymous slot with each pre-shared key and then with the X25519 secret key of
+/// the recipient, the MAC closing the slot only matches for the secret it was wrapped for. Returns
+/// the key in the slot, and the position following it if any.
+fn unwrap_anonymous_slot<F: PRP>(
+    spongos: &spongos::Spongos<F>,
+    eph_pk: &x25519::PublicKey,
+    slot: &[u8],
+    psks: &[psk::Psk],
+    ke_sk: &x25519::StaticSecret,
+    with_position: bool,
+) -> Option<(NBytes<U32>, u64)> {
+    let trial = |psk: Option<&psk::Psk>| -> Result<(NBytes<U32>, u64)> {
+        let mut ctx = unwrap::Context::new(slot);
+        ctx.spongos = spongos.fork();
+        match psk {
+            Some(psk) => ctx.absorb(External(<&NBytes<psk::PskSize>>::from(psk)))?,
+            None => ctx.x25519(ke_sk, eph_pk)?,
+        };
+        let mut key = NBytes::<U32>::default();
+        let mut position = Uint64(0);
+        ctx.commit()?.mask(&mut key)?;
+        if with_position {
+            ctx.mask(&mut position)?;
+        }
+        ctx.commit()?.squeeze(Mac(SLOT_MAC_SIZE))?;
+        Ok((key, position.0))
+    };
+    psks.iter()
+        .map(Some)
+        .chain(core::iter::once(None))
+        .find_map(|psk| trial(psk).ok())
+}
+
+fn encapsulate_sizeof<F: PRP>(
+    ctx: &mut sizeof::Context<F>,
+    id: &Identifier,
+    store_id: &[u8],
+    key: &psk::Psk,
+) -> Result<()> {
+    match id {
+        Identifier::PskId(_pskid) => ctx
+            .absorb(External(<&NBytes<psk::PskSize>>::from(store_id)))?
+            .commit()?
+            .mask(<&NBytes<psk::PskSize>>::from(key))?,
+        Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id) {
+            Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), <&NBytes<psk::PskSize>>::from(key))?,
+            Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
+        },
+    };
+    Ok(())
+}
+
+fn encapsulate_wrap<F: PRP, OS: io::OStream>(
+    ctx: &mut wrap::Context<F, OS>,
+    id: &Identifier,
+    store_id: &[u8],
+    key: &psk::Psk,
+) -> Result<()> {
+    match id {
+        Identifier::PskId(_pskid) => ctx
+            .absorb(External(<&NBytes<psk::PskSize>>::from(store_id)))?
+            .commit()?
+            .mask(<&NBytes<psk::PskSize>>::from(key))?,
+        Identifier::EdPubKey(_pk) => match <[u8; 32]>::try_from(store_id) {
+            Ok(slice) => ctx.x25519(&x25519::PublicKey::from(slice), <&NBytes<psk::PskSize>>::from(key))?,
+            Err(e) => return Err(wrapped_err(BadIdentifier, WrappedError(e))),
+        },
+    };
+    Ok(())
+}
+
 // TODO: add test cases: 0,1,2 pks + 0,1,2 psks + key found/notfound + unwrap modify/fuzz to check sig does work
+
+#[cfg(all(test, feature = "tangle"))]
+mod test {
+    use super::*;
+    use crate::api::DefaultF;
+    use iota_streams_app::{
+        message::{
+            ContentSizeof as _,
+            ContentUnwrap as _,
+            ContentWrap as _,
+        },
+        transport::tangle::{
+            MsgId,
+            TangleAddress,
+        },
+    };
+    use iota_streams_core::ensure;
+    use iota_streams_ddml::link_store::DefaultLinkStore;
+
+    type Store = DefaultLinkStore<DefaultF, MsgId, ()>;
+
+    /// Key stores of a party holding none of the keys of a keyload.
+    struct NoKeys;
+
+    impl Lookup<&Identifier, psk::Psk> for NoKeys {
+        fn lookup(&self, _id: &Identifier) -> Option<psk::Psk> {
+            None
+        }
+    }
+
+    impl<'a> Lookup<&Identifier, &'a x25519::StaticSecret> for NoKeys {
+        fn lookup(&self, _id: &Identifier) -> Option<&'a x25519::StaticSecret> {
+            None
+        }
+    }
+
+    fn keypair(seed: u8) -> ed25519::Keypair {
+        let secret = ed25519::SecretKey::from_bytes(&[seed; ed25519::SECRET_KEY_LENGTH]).unwrap();
+        let public = ed25519::PublicKey::from(&secret);
+        ed25519::Keypair { secret, public }
+    }
+
+    /// Unwrap an anonymous keyload as a party holding none of the slot keys but assuming `key` is the
+    /// session key, returning the recipient identifiers it unmasks.
+    async fn unmasked_ids(
+        bytes: &[u8],
+        store: &Store,
+        sig_pk: &ed25519::PublicKey,
+        key: NBytes<U32>,
+    ) -> Vec<Identifier> {
+        let ke_sk = x25519::StaticSecret::from([7_u8; 32]);
+        let mut content = ContentUnwrap::<DefaultF, TangleAddress, _, _>::new(NoKeys, NoKeys, sig_pk)
+            .with_anonymous_slots(&ke_sk, Vec::new());
+        content.key = Some(key);
+        let mut ctx = unwrap::Context::new(bytes);
+        // The signature only verifies with the right session key, the identifiers are unmasked before
+        let _ = content.unwrap(store, &mut ctx).await;
+        content.key_ids
+    }
+
+    /// The recipient identifiers of anonymous keyloads are only unmasked with the session key.
+    async fn masked_identifiers_example() -> Result<()> {
+        let link = MsgId::default();
+        let mut store = Store::default();
+        let mut spongos = spongos::Spongos::<DefaultF>::init();
+        spongos.commit();
+        store.insert(&link, spongos.to_inner()?, ())?;
+
+        let author = keypair(1);
+        let recipient = keypair(2);
+        let psk = psk::psk_from_seed::<DefaultF>(b"A masked pre shared key");
+        let id_a = Identifier::from(recipient.public);
+        let id_b = Identifier::PskId(psk::pskid_from_psk::<DefaultF>(&psk));
+        let key_a = x25519::keypair_from_ed25519(&recipient).1.as_bytes().to_vec();
+        let key = NBytes::<U32>::from(prng::random_key());
+        let content = ContentWrap::<DefaultF, TangleAddress> {
+            link: &link,
+            nonce: NBytes::default(),
+            key: key.clone(),
+            keys: vec![(&id_a, key_a), (&id_b, psk.to_vec())],
+            updates: &[],
+            group_key: None,
+            anonymous: true,
+            padding: Padding::default(),
+            sig_kp: &author,
+            _phantom: core::marker::PhantomData,
+        };
+        let mut sizeof_ctx = sizeof::Context::<DefaultF>::new();
+        content.sizeof(&mut sizeof_ctx).await?;
+        let mut bytes = vec![0_u8; sizeof_ctx.get_size()];
+        content.wrap(&store, &mut wrap::Context::new(&mut bytes[..])).await?;
+
+        ensure!(
+            unmasked_ids(&bytes, &store, &author.public, key).await == vec![id_a, id_b],
+            "identifiers should be unmasked with the session key"
+        );
+        let guessed_key = NBytes::<U32>::from(prng::random_key());
+        let ids = unmasked_ids(&bytes, &store, &author.public, guessed_key).await;
+        ensure!(
+            !ids.contains(&id_a) && !ids.contains(&id_b),
+            "identifiers should not be unmasked without the session key"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_masked_identifiers_scenario() {
+        smol::block_on(masked_identifiers_example()).unwrap();
+    }
+}
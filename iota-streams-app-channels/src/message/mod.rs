@@ -21,6 +21,12 @@ pub mod sequence;
 /// Unsubscribe message.
 pub mod unsubscribe;
 
+/// KeyRotation message.
+pub mod key_rotation;
+
+/// PublisherCertificate message.
+pub mod publisher_certificate;
+
 /// Announcement Message Type
 pub const ANNOUNCE: u8 = 0;
 /// Keyload Message Type
@@ -35,3 +41,7 @@ pub const TAGGED_PACKET: u8 = 4;
 pub const SUBSCRIBE: u8 = 5;
 /// Unsubscribe Message Type
 pub const UNSUBSCRIBE: u8 = 6;
+/// Key Rotation Message Type
+pub const KEY_ROTATION: u8 = 7;
+/// Publisher Certificate Message Type
+pub const PUBLISHER_CERTIFICATE: u8 = 8;
@@ -0,0 +1,190 @@
+//! `PublisherCertificate` message content. This message is published by the Author to grant a
+//! publisher the right to publish in the channel.
+//!
+//! ```ddml
+//! message PublisherCertificate {
+//!     join link msgid;
+//!     mask u8 publisher_id[];
+//!     mask u8 scope;
+//!     mask u64 expiry;
+//!     commit;
+//!     squeeze external byte hash[32];
+//!     ed25519(hash) sig;
+//! }
+//! ```
+//!
+//! # Fields:
+//!
+//! * `msgid` -- link to the base message.
+//!
+//! * `publisher_id` -- Identifier (Ed25519 public key or PSK identifier) of the publisher.
+//!
+//! * `scope` -- Messages the publisher may send, a combination of `SCOPE_` flags. Only signed
+//! packets can be certified, as tagged packets do not authenticate their publisher.
+//!
+//! * `expiry` -- Time the certificate expires at in milliseconds since the Unix epoch, zero if it
+//! does not expire. Packets whose time of publishing is unknown are out of expiring certificates.
+//!
+//! * `hash` -- hash value to be signed.
+//!
+//! * `sig` -- message signature generated with the Author's private key.
+//!
+//! A certificate replaces the former certificate of the same publisher, thus a certificate with
+//! an empty scope revokes the publish rights.
+
+use iota_streams_app::{
+    identifier::Identifier,
+    message::{
+        self,
+        ContentSizeof,
+        ContentUnwrapNew,
+        ContentWrap as _,
+        HasLink,
+    },
+};
+use iota_streams_core::{
+    async_trait,
+    prelude::Box,
+    sponge::prp::PRP,
+    Result,
+};
+use iota_streams_core_edsig::signature::ed25519;
+use iota_streams_ddml::{
+    command::*,
+    io,
+    link_store::{
+        EmptyLinkStore,
+        LinkStore,
+    },
+    types::*,
+};
+
+/// Publisher may send signed packets.
+pub const SCOPE_SIGNED_PACKETS: u8 = 1;
+
+/// Publish rights granted by the Author.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Certificate {
+    /// Identifier of the publisher.
+    pub publisher: Identifier,
+    /// Messages the publisher may send, see `SCOPE_SIGNED_PACKETS`.
+    pub scope: u8,
+    /// Time the certificate expires at in milliseconds since the Unix epoch, zero if it does not
+    /// expire.
+    pub expiry: u64,
+}
+
+impl Certificate {
+    pub fn new(publisher: Identifier, scope: u8, expiry: u64) -> Self {
+        Self {
+            publisher,
+            scope,
+            expiry,
+        }
+    }
+
+    /// Whether the certificate covers `scope` at time `timestamp`. A zero `timestamp` stands for an
+    /// unknown time, eg. of a message not referenced by a milestone yet, which only certificates
+    /// without expiry cover.
+    pub fn allows(&self, scope: u8, timestamp: u64) -> bool {
+        self.scope & scope == scope && (self.expiry == 0 || (timestamp != 0 && timestamp < self.expiry))
+    }
+}
+
+pub struct ContentWrap<'a, F, Link: HasLink> {
+    pub(crate) link: &'a <Link as HasLink>::Rel,
+    pub(crate) certificate: &'a Certificate,
+    pub(crate) sig_kp: &'a ed25519::Keypair,
+    pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+#[async_trait(?Send)]
+impl<'a, F, Link> message::ContentSizeof<F> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+{
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        ctx.join(&store, self.link)?;
+        self.certificate.publisher.sizeof(ctx).await?;
+        ctx.mask(Uint8(self.certificate.scope))?
+            .mask(Uint64(self.certificate.expiry))?
+            .commit()?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, F, Link, Store> message::ContentWrap<F, Store> for ContentWrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: 'a + Eq + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        ctx.join(store, self.link)?;
+        self.certificate.publisher.wrap(store, ctx).await?;
+        ctx.mask(&Uint8(self.certificate.scope))?
+            .mask(&Uint64(self.certificate.expiry))?
+            .commit()?
+            .ed25519(self.sig_kp, HashSig)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ContentUnwrap<'a, F, Link: HasLink> {
+    pub(crate) link: <Link as HasLink>::Rel,
+    pub(crate) certificate: Option<Certificate>,
+    pub(crate) sig_pk: &'a ed25519::PublicKey,
+    _phantom: core::marker::PhantomData<(F, Link)>,
+}
+
+impl<'a, F, Link> ContentUnwrap<'a, F, Link>
+where
+    Link: HasLink,
+    <Link as HasLink>::Rel: Default,
+{
+    /// Certificate to be verified with the Author's public key `sig_pk`.
+    pub fn new(sig_pk: &'a ed25519::PublicKey) -> Self {
+        Self {
+            link: <Link as HasLink>::Rel::default(),
+            certificate: None,
+            sig_pk,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, F, Link, Store> message::ContentUnwrap<F, Store> for ContentUnwrap<'a, F, Link>
+where
+    F: PRP,
+    Link: HasLink,
+    <Link as HasLink>::Rel: Eq + Default + SkipFallback<F>,
+    Store: LinkStore<F, <Link as HasLink>::Rel>,
+{
+    async fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut scope = Uint8(0);
+        let mut expiry = Uint64(0);
+        ctx.join(store, &mut self.link)?;
+        let (publisher, ctx) = Identifier::unwrap_new(store, ctx).await?;
+        ctx.mask(&mut scope)?
+            .mask(&mut expiry)?
+            .commit()?
+            .ed25519(self.sig_pk, HashSig)?;
+        self.certificate = Some(Certificate::new(publisher, scope.0, expiry.0));
+        Ok(ctx)
+    }
+}
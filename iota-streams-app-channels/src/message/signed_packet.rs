@@ -9,7 +9,11 @@
 //! message SignedPacket {
 //!     join link msgid;
 //!     absorb bytes public_payload;
+//!     absorb u8 has_timestamp;
+//!     absorb u64 timestamp; // if has_timestamp
 //!     mask bytes masked_payload;
+//!     mask u32 padding_length;
+//!     mask u8 padding[padding_length];
 //!     commit;
 //!     squeeze external byte hash[78];
 //!     mssig(hash) sig;
@@ -22,20 +26,33 @@
 //!
 //! * `public_payload` -- public part of payload.
 //!
+//! * `timestamp` -- optional time of publishing asserted by the sender, in milliseconds since the Unix epoch.
+//!
 //! * `masked_payload` -- masked part of payload.
 //!
+//! * `padding` -- zero filler extending the message to the uniform payload length.
+//!
 //! * `hash` -- hash value to be signed.
 //!
 //! * `sig` -- message signature generated with the senders private key.
+//!
+//! Messages of version `STREAMS_1_VER` carry neither `has_timestamp`, `timestamp` nor `padding`.
 
 use iota_streams_app::message::{
     self,
+    ContentSizeof as _,
+    ContentUnwrap as _,
+    ContentWrap as _,
     HasLink,
+    Padding,
+    STREAMS_1_VER,
+    STREAMS_VER,
 };
 use iota_streams_core::{
     async_trait,
     prelude::Box,
     sponge::prp::PRP,
+    Errors::BadOneof,
     Result,
 };
 use iota_streams_core_edsig::signature::ed25519;
@@ -57,6 +74,8 @@ where
     pub(crate) link: &'a <Link as HasLink>::Rel,
     pub(crate) public_payload: &'a Bytes,
     pub(crate) masked_payload: &'a Bytes,
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) padding: Padding,
     pub(crate) sig_kp: &'a ed25519::Keypair,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
@@ -73,8 +92,12 @@ where
         ctx.join(&store, self.link)?
             .absorb(&self.sig_kp.public)?
             .absorb(self.public_payload)?
-            .mask(self.masked_payload)?
-            .ed25519(self.sig_kp, HashSig)?;
+            .absorb(Uint8(self.timestamp.is_some() as u8))?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.mask(self.masked_payload)?;
+        self.padding.sizeof(ctx).await?.ed25519(self.sig_kp, HashSig)?;
         // TODO: Is both public and masked payloads are ok? Leave public only or masked only?
         Ok(ctx)
     }
@@ -96,8 +119,12 @@ where
         ctx.join(store, self.link)?
             .absorb(&self.sig_kp.public)?
             .absorb(self.public_payload)?
-            .mask(self.masked_payload)?
-            .ed25519(self.sig_kp, HashSig)?;
+            .absorb(Uint8(self.timestamp.is_some() as u8))?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.mask(self.masked_payload)?;
+        self.padding.wrap(store, ctx).await?.ed25519(self.sig_kp, HashSig)?;
         Ok(ctx)
     }
 }
@@ -106,7 +133,11 @@ pub struct ContentUnwrap<F, Link: HasLink> {
     pub(crate) link: <Link as HasLink>::Rel,
     pub(crate) public_payload: Bytes,
     pub(crate) masked_payload: Bytes,
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) padding: Padding,
     pub(crate) sig_pk: ed25519::PublicKey,
+    /// Version of the message, from its header.
+    pub(crate) version: Uint8,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -120,7 +151,10 @@ where
             link: <<Link as HasLink>::Rel as Default>::default(),
             public_payload: Bytes::default(),
             masked_payload: Bytes::default(),
+            timestamp: None,
+            padding: Padding::default(),
             sig_pk: ed25519::PublicKey::default(),
+            version: STREAMS_VER,
             _phantom: core::marker::PhantomData,
         }
     }
@@ -141,9 +175,23 @@ where
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
         ctx.join(store, &mut self.link)?
             .absorb(&mut self.sig_pk)?
-            .absorb(&mut self.public_payload)?
-            .mask(&mut self.masked_payload)?
-            .ed25519(&self.sig_pk, HashSig)?;
+            .absorb(&mut self.public_payload)?;
+        // Timestamp and padding are absent from messages of the first version
+        let legacy = self.version == STREAMS_1_VER;
+        if !legacy {
+            let mut has_timestamp = Uint8(0);
+            ctx.absorb(&mut has_timestamp)?.guard(has_timestamp.0 < 2, BadOneof)?;
+            if has_timestamp.0 == 1 {
+                let mut timestamp = Uint64(0);
+                ctx.absorb(&mut timestamp)?;
+                self.timestamp = Some(timestamp.0);
+            }
+        }
+        ctx.mask(&mut self.masked_payload)?;
+        if !legacy {
+            self.padding.unwrap(store, ctx).await?;
+        }
+        ctx.ed25519(&self.sig_pk, HashSig)?;
         Ok(ctx)
     }
 }
@@ -38,6 +38,10 @@
 //!
 //! Note, the `unsubscribe_key` is masked and verified in the `x25519` operation and
 //! thus is not additionally `absorb`ed in this message.
+//!
+//! The ephemeral key of the `x25519` operation is chosen by the subscriber rather than generated
+//! in the operation, so that a subscriber deriving it from its own secret key can reproduce the
+//! message, eg. when recovering its state from the seed.
 
 use iota_streams_app::message::{
     self,
@@ -69,6 +73,7 @@ use iota_streams_ddml::{
 pub struct ContentWrap<'a, F, Link: HasLink> {
     pub(crate) link: &'a <Link as HasLink>::Rel,
     pub unsubscribe_key: NBytes<U32>,
+    pub(crate) ephemeral_ke_sk: x25519::StaticSecret,
     pub(crate) subscriber_sig_kp: &'a ed25519::Keypair,
     pub(crate) author_ke_pk: &'a x25519::PublicKey,
     pub(crate) _phantom: core::marker::PhantomData<(Link, F)>,
@@ -83,8 +88,12 @@ where
 {
     async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
         let store = EmptyLinkStore::<F, <Link as HasLink>::Rel, ()>::default();
+        let ephemeral_ke_pk = x25519::PublicKey::from(&self.ephemeral_ke_sk);
         ctx.join(&store, self.link)?
-            .x25519(self.author_ke_pk, &self.unsubscribe_key)?
+            .absorb(&ephemeral_ke_pk)?
+            .x25519(&self.ephemeral_ke_sk, self.author_ke_pk)?
+            .commit()?
+            .mask(&self.unsubscribe_key)?
             .mask(&self.subscriber_sig_kp.public)?
             .ed25519(self.subscriber_sig_kp, HashSig)?;
         Ok(ctx)
@@ -104,8 +113,12 @@ where
         store: &Store,
         ctx: &'c mut wrap::Context<F, OS>,
     ) -> Result<&'c mut wrap::Context<F, OS>> {
+        let ephemeral_ke_pk = x25519::PublicKey::from(&self.ephemeral_ke_sk);
         ctx.join(store, self.link)?
-            .x25519(self.author_ke_pk, &self.unsubscribe_key)?
+            .absorb(&ephemeral_ke_pk)?
+            .x25519(&self.ephemeral_ke_sk, self.author_ke_pk)?
+            .commit()?
+            .mask(&self.unsubscribe_key)?
             .mask(&self.subscriber_sig_kp.public)?
             .ed25519(self.subscriber_sig_kp, HashSig)?;
         Ok(ctx)
@@ -8,7 +8,11 @@
 //! message TaggedPacket {
 //!     join link msgid;
 //!     absorb bytes public_payload;
+//!     absorb u8 has_timestamp;
+//!     absorb u64 timestamp; // if has_timestamp
 //!     mask bytes masked_payload;
+//!     mask u32 padding_length;
+//!     mask u8 padding[padding_length];
 //!     commit;
 //!     squeeze byte mac[32];
 //! }
@@ -20,13 +24,25 @@
 //!
 //! * `public_payload` -- public part of payload.
 //!
+//! * `timestamp` -- optional time of publishing asserted by the sender, in milliseconds since the Unix epoch.
+//!
 //! * `masked_payload` -- masked part of payload.
 //!
+//! * `padding` -- zero filler extending the message to the uniform payload length.
+//!
 //! * `mac` -- MAC of the message.
+//!
+//! Messages of version `STREAMS_1_VER` carry neither `has_timestamp`, `timestamp` nor `padding`.
 
 use iota_streams_app::message::{
     self,
+    ContentSizeof as _,
+    ContentUnwrap as _,
+    ContentWrap as _,
     HasLink,
+    Padding,
+    STREAMS_1_VER,
+    STREAMS_VER,
 };
 use iota_streams_core::{
     async_trait,
@@ -38,6 +54,7 @@ use iota_streams_core::{
         prp::PRP,
         spongos,
     },
+    Errors::BadOneof,
     Result,
 };
 use iota_streams_ddml::{
@@ -58,6 +75,8 @@ where
     pub(crate) link: &'a <Link as HasLink>::Rel,
     pub(crate) public_payload: &'a Bytes,
     pub(crate) masked_payload: &'a Bytes,
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) padding: Padding,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -73,9 +92,12 @@ where
         let mac = Mac(spongos::MacSize::<F>::USIZE);
         ctx.join(&store, self.link)?
             .absorb(self.public_payload)?
-            .mask(self.masked_payload)?
-            .commit()?
-            .squeeze(&mac)?;
+            .absorb(Uint8(self.timestamp.is_some() as u8))?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.mask(self.masked_payload)?;
+        self.padding.sizeof(ctx).await?.commit()?.squeeze(&mac)?;
         // TODO: Is bot public and masked payloads are ok? Leave public only or masked only?
         Ok(ctx)
     }
@@ -97,9 +119,12 @@ where
         let mac = Mac(spongos::MacSize::<F>::USIZE);
         ctx.join(store, self.link)?
             .absorb(self.public_payload)?
-            .mask(self.masked_payload)?
-            .commit()?
-            .squeeze(&mac)?;
+            .absorb(Uint8(self.timestamp.is_some() as u8))?;
+        if let Some(timestamp) = self.timestamp {
+            ctx.absorb(Uint64(timestamp))?;
+        }
+        ctx.mask(self.masked_payload)?;
+        self.padding.wrap(store, ctx).await?.commit()?.squeeze(&mac)?;
         Ok(ctx)
     }
 }
@@ -109,6 +134,10 @@ pub struct ContentUnwrap<F, Link: HasLink> {
     pub(crate) link: <Link as HasLink>::Rel,
     pub(crate) public_payload: Bytes,
     pub(crate) masked_payload: Bytes,
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) padding: Padding,
+    /// Version of the message, from its header.
+    pub(crate) version: Uint8,
     pub(crate) _phantom: core::marker::PhantomData<(F, Link)>,
 }
 
@@ -121,6 +150,9 @@ where
             link: Link::Rel::default(),
             public_payload: Bytes::default(),
             masked_payload: Bytes::default(),
+            timestamp: None,
+            padding: Padding::default(),
+            version: STREAMS_VER,
             _phantom: core::marker::PhantomData,
         }
     }
@@ -140,11 +172,23 @@ where
         ctx: &'c mut unwrap::Context<F, IS>,
     ) -> Result<&'c mut unwrap::Context<F, IS>> {
         let mac = Mac(spongos::MacSize::<F>::USIZE);
-        ctx.join(store, &mut self.link)?
-            .absorb(&mut self.public_payload)?
-            .mask(&mut self.masked_payload)?
-            .commit()?
-            .squeeze(&mac)?;
+        ctx.join(store, &mut self.link)?.absorb(&mut self.public_payload)?;
+        // Timestamp and padding are absent from messages of the first version
+        let legacy = self.version == STREAMS_1_VER;
+        if !legacy {
+            let mut has_timestamp = Uint8(0);
+            ctx.absorb(&mut has_timestamp)?.guard(has_timestamp.0 < 2, BadOneof)?;
+            if has_timestamp.0 == 1 {
+                let mut timestamp = Uint64(0);
+                ctx.absorb(&mut timestamp)?;
+                self.timestamp = Some(timestamp.0);
+            }
+        }
+        ctx.mask(&mut self.masked_payload)?;
+        if !legacy {
+            self.padding.unwrap(store, ctx).await?;
+        }
+        ctx.commit()?.squeeze(&mac)?;
         Ok(ctx)
     }
 }
@@ -13,7 +13,9 @@
 //!
 //! # Fields:
 //!
-//! * `msgid` -- link to the `Subscribe` message published by the subscriber.
+//! * `msgid` -- link to the `Subscribe` message published by the subscriber. Its spongos state
+//! is keyed with the `unsubscribe_key` known only to the subscriber and the channel owner, thus
+//! the signature proves the unsubscription was requested by the subscriber of that message.
 //!
 //! * `ed25519pk` -- subscriber's Ed25519 public key.
 //!
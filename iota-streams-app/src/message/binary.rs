@@ -6,6 +6,11 @@ use core::fmt::Debug;
 use iota_streams_core::{
     prelude::Vec,
     sponge::prp::PRP,
+    try_or,
+    Errors::{
+        InvalidMsgType,
+        ValueMismatch,
+    },
 };
 use iota_streams_ddml::{
     command::unwrap,
@@ -74,4 +79,51 @@ where
 
         Ok(PreparsedMessage { header, ctx })
     }
+
+    /// Number of frames the message was split into, as declared in its header.
+    pub async fn payload_frame_count(&self) -> Result<u32> {
+        Ok(self.parse_header().await?.header.get_payload_frame_count().max(1))
+    }
+
+    /// Append continuation frames of a message split with [`PreparedMessage::wrap_frames`].
+    ///
+    /// `frames` must contain all the frames following the first one, in order. The whole message
+    /// is authenticated once the joined message is unwrapped.
+    pub async fn join_frames(&mut self, frames: Vec<Self>) -> Result<()> {
+        let frame_count = self.payload_frame_count().await? as usize;
+        try_or!(
+            frames.len() + 1 == frame_count,
+            ValueMismatch(frame_count, frames.len() + 1)
+        )?;
+        for (i, frame) in frames.iter().enumerate() {
+            let payload = frame.frame_payload(i + 2, frame_count)?;
+            self.body.bytes.extend_from_slice(payload);
+        }
+        Ok(())
+    }
+
+    /// Whether the message is the continuation frame `frame_num` of a message split into
+    /// `frame_count` frames. Frames are not authenticated until the joined message is unwrapped.
+    pub fn is_frame(&self, frame_num: u32, frame_count: u32) -> bool {
+        self.frame_payload(frame_num as usize, frame_count as usize).is_ok()
+    }
+
+    /// Payload of the continuation frame `frame_num` of a message split into `frame_count` frames.
+    fn frame_payload(&self, frame_num: usize, frame_count: usize) -> Result<&[u8]> {
+        let (pcf, payload) = PCF::unwrap_frame::<F>(&self.body.bytes)?;
+        let frame_type = if frame_num == frame_count {
+            FINAL_PCF_ID
+        } else {
+            INTER_PCF_ID
+        };
+        try_or!(
+            pcf.frame_type == frame_type,
+            InvalidMsgType(frame_type.0, pcf.frame_type.0)
+        )?;
+        try_or!(
+            pcf.get_payload_frame_num() as usize == frame_num,
+            ValueMismatch(frame_num, pcf.get_payload_frame_num() as usize)
+        )?;
+        Ok(payload)
+    }
 }
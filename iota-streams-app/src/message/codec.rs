@@ -0,0 +1,329 @@
+//! Payload codecs.
+//!
+//! Payloads of signed and tagged packets are encoded by the sender with the codec named by its
+//! `message_encoding` and the codec id is signalled in `HDF.encoding`, so that recipients can
+//! decode payloads without prior agreement. Encoding happens before wrapping, thus compressed
+//! payloads are masked and padded like plain ones.
+//!
+//! Built-in codecs:
+//!
+//! * `utf-8` -- payload is passed through unchanged, id `0`, the label used before codecs were introduced, so that
+//!   binary payloads of earlier messages stay readable;
+//! * `raw` -- payload is passed through unchanged, id `1`;
+//! * `deflate` -- payload is compressed with DEFLATE, id `2` (feature `codec-deflate`);
+//! * `zstd` -- payload is compressed with Zstandard, id `3` (feature `codec-zstd`);
+//! * `cbor` -- payload must be a single well-formed CBOR item, id `4` (feature `codec-cbor`);
+//! * `utf-8-strict` -- payload must be valid UTF-8, id `5`.
+//!
+//! Custom codecs can be added to [`PayloadCodecs`] with ids and names not taken by other codecs.
+
+use iota_streams_core::{
+    err,
+    prelude::{
+        Box,
+        String,
+        ToString,
+        Vec,
+    },
+    try_or,
+    wrapped_err,
+    Errors::{
+        PayloadCodecDuplication,
+        PayloadDecodingFailure,
+        PayloadEncodingFailure,
+        UnknownPayloadEncoding,
+        UnsupportedPayloadEncoding,
+    },
+    Result,
+    WrappedError,
+};
+use iota_streams_ddml::types::{
+    Bytes,
+    Uint8,
+};
+
+use super::*;
+
+/// Upper bound on the size of a decompressed payload, protects against decompression bombs.
+#[cfg(any(feature = "codec-deflate", feature = "codec-zstd"))]
+pub const MAX_DECODED_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+/// Payload encoding scheme.
+pub trait PayloadCodec {
+    /// Codec id signalled in `HDF.encoding`.
+    fn id(&self) -> Uint8;
+
+    /// Codec name used in `message_encoding`.
+    fn name(&self) -> &str;
+
+    /// Encode payload before wrapping.
+    fn encode(&self, payload: &Bytes) -> Result<Bytes>;
+
+    /// Decode unwrapped payload.
+    fn decode(&self, payload: &Bytes) -> Result<Bytes>;
+}
+
+/// Payload passed through unchanged.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Raw;
+
+impl PayloadCodec for Raw {
+    fn id(&self) -> Uint8 {
+        RAW
+    }
+
+    fn name(&self) -> &str {
+        "raw"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        Ok(payload.clone())
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        Ok(payload.clone())
+    }
+}
+
+/// Payload labelled as UTF-8 text and passed through unchanged.
+///
+/// Payloads are not validated as the label does not guarantee the contents of earlier messages,
+/// see [`Utf8Strict`] for validated text.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Utf8;
+
+impl PayloadCodec for Utf8 {
+    fn id(&self) -> Uint8 {
+        UTF8
+    }
+
+    fn name(&self) -> &str {
+        "utf-8"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        Ok(payload.clone())
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        Ok(payload.clone())
+    }
+}
+
+/// Payload validated to be UTF-8 text.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Utf8Strict;
+
+impl PayloadCodec for Utf8Strict {
+    fn id(&self) -> Uint8 {
+        UTF8_STRICT
+    }
+
+    fn name(&self) -> &str {
+        "utf-8-strict"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        match core::str::from_utf8(&payload.0) {
+            Ok(_) => Ok(payload.clone()),
+            Err(e) => Err(wrapped_err!(
+                PayloadEncodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        match core::str::from_utf8(&payload.0) {
+            Ok(_) => Ok(payload.clone()),
+            Err(e) => Err(wrapped_err!(
+                PayloadDecodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+}
+
+/// Payload compressed with DEFLATE.
+#[cfg(feature = "codec-deflate")]
+#[derive(Clone, Copy, Debug)]
+pub struct Deflate {
+    /// Compression level [0-10].
+    pub level: u8,
+}
+
+#[cfg(feature = "codec-deflate")]
+impl Default for Deflate {
+    fn default() -> Self {
+        Self { level: 6 }
+    }
+}
+
+#[cfg(feature = "codec-deflate")]
+impl PayloadCodec for Deflate {
+    fn id(&self) -> Uint8 {
+        DEFLATE
+    }
+
+    fn name(&self) -> &str {
+        "deflate"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        Ok(Bytes(miniz_oxide::deflate::compress_to_vec(&payload.0, self.level)))
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        match miniz_oxide::inflate::decompress_to_vec_with_limit(&payload.0, MAX_DECODED_PAYLOAD_SIZE) {
+            Ok(bytes) => Ok(Bytes(bytes)),
+            Err(e) => Err(wrapped_err!(
+                PayloadDecodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+}
+
+/// Payload compressed with Zstandard.
+#[cfg(feature = "codec-zstd")]
+#[derive(Clone, Copy, Debug)]
+pub struct Zstd {
+    /// Compression level [1-22], `0` selects the zstd default.
+    pub level: i32,
+}
+
+#[cfg(feature = "codec-zstd")]
+impl Default for Zstd {
+    fn default() -> Self {
+        Self { level: 0 }
+    }
+}
+
+#[cfg(feature = "codec-zstd")]
+impl PayloadCodec for Zstd {
+    fn id(&self) -> Uint8 {
+        ZSTD
+    }
+
+    fn name(&self) -> &str {
+        "zstd"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        match zstd::bulk::compress(&payload.0, self.level) {
+            Ok(bytes) => Ok(Bytes(bytes)),
+            Err(e) => Err(wrapped_err!(
+                PayloadEncodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        match zstd::bulk::decompress(&payload.0, MAX_DECODED_PAYLOAD_SIZE) {
+            Ok(bytes) => Ok(Bytes(bytes)),
+            Err(e) => Err(wrapped_err!(
+                PayloadDecodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+}
+
+/// Payload validated to be a single CBOR data item.
+#[cfg(feature = "codec-cbor")]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Cbor;
+
+#[cfg(feature = "codec-cbor")]
+impl PayloadCodec for Cbor {
+    fn id(&self) -> Uint8 {
+        CBOR
+    }
+
+    fn name(&self) -> &str {
+        "cbor"
+    }
+
+    fn encode(&self, payload: &Bytes) -> Result<Bytes> {
+        match serde_cbor::from_slice::<serde_cbor::Value>(&payload.0) {
+            Ok(_) => Ok(payload.clone()),
+            Err(e) => Err(wrapped_err!(
+                PayloadEncodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+
+    fn decode(&self, payload: &Bytes) -> Result<Bytes> {
+        match serde_cbor::from_slice::<serde_cbor::Value>(&payload.0) {
+            Ok(_) => Ok(payload.clone()),
+            Err(e) => Err(wrapped_err!(
+                PayloadDecodingFailure(self.name().to_string()),
+                WrappedError(e)
+            )),
+        }
+    }
+}
+
+/// Registry of payload codecs looked up by name when sending and by id when receiving.
+pub struct PayloadCodecs {
+    codecs: Vec<Box<dyn PayloadCodec>>,
+}
+
+impl PayloadCodecs {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self { codecs: Vec::new() }
+    }
+
+    /// Register a codec, its id and name must not be taken.
+    pub fn register<C: PayloadCodec + 'static>(&mut self, codec: C) -> Result<()> {
+        try_or!(
+            self.codecs
+                .iter()
+                .all(|c| c.id() != codec.id() && c.name() != codec.name()),
+            PayloadCodecDuplication(codec.id().0, codec.name().to_string())
+        )?;
+        self.codecs.push(Box::new(codec));
+        Ok(())
+    }
+
+    /// Codec selected by `message_encoding`.
+    pub fn by_name(&self, name: &[u8]) -> Result<&dyn PayloadCodec> {
+        match self.codecs.iter().find(|c| c.name().as_bytes() == name) {
+            Some(codec) => Ok(codec.as_ref()),
+            None => err!(UnknownPayloadEncoding(String::from_utf8_lossy(name).to_string())),
+        }
+    }
+
+    /// Codec signalled in `HDF.encoding`.
+    pub fn by_id(&self, id: Uint8) -> Result<&dyn PayloadCodec> {
+        match self.codecs.iter().find(|c| c.id() == id) {
+            Some(codec) => Ok(codec.as_ref()),
+            None => err!(UnsupportedPayloadEncoding(id.0)),
+        }
+    }
+
+    /// Names of the registered codecs.
+    pub fn names(&self) -> Vec<&str> {
+        self.codecs.iter().map(|c| c.name()).collect()
+    }
+}
+
+impl Default for PayloadCodecs {
+    /// Registry of the built-in codecs enabled by crate features.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.codecs.push(Box::new(Utf8));
+        registry.codecs.push(Box::new(Raw));
+        #[cfg(feature = "codec-deflate")]
+        registry.codecs.push(Box::new(Deflate::default()));
+        #[cfg(feature = "codec-zstd")]
+        registry.codecs.push(Box::new(Zstd::default()));
+        #[cfg(feature = "codec-cbor")]
+        registry.codecs.push(Box::new(Cbor));
+        registry.codecs.push(Box::new(Utf8Strict));
+        registry
+    }
+}
@@ -14,11 +14,35 @@ pub struct GenericMessage<AbsLink, Body> {
 
     /// Message body -- header + content.
     pub body: Body,
+
+    /// Time the message was published at according to the transport, in milliseconds since the
+    /// Unix epoch; zero if unknown.
+    pub timestamp: u64,
+
+    /// Time the message was published at according to the sender, in milliseconds since the Unix
+    /// epoch. It is authenticated along with the message content.
+    pub sender_timestamp: Option<u64>,
 }
 
 impl<AbsLink, Body> GenericMessage<AbsLink, Body> {
     pub fn new(link: AbsLink, prev_link: AbsLink, body: Body) -> Self {
-        Self { link, prev_link, body }
+        Self {
+            link,
+            prev_link,
+            body,
+            timestamp: 0,
+            sender_timestamp: None,
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_sender_timestamp(mut self, sender_timestamp: Option<u64>) -> Self {
+        self.sender_timestamp = sender_timestamp;
+        self
     }
 
     pub fn map<B, F: FnOnce(Body) -> B>(self, f: F) -> GenericMessage<AbsLink, B> {
@@ -26,6 +50,8 @@ impl<AbsLink, Body> GenericMessage<AbsLink, Body> {
             link: self.link,
             prev_link: self.prev_link,
             body: f(self.body),
+            timestamp: self.timestamp,
+            sender_timestamp: self.sender_timestamp,
         }
     }
 
@@ -35,6 +61,8 @@ impl<AbsLink, Body> GenericMessage<AbsLink, Body> {
             link: self.link,
             prev_link: self.prev_link,
             body,
+            timestamp: self.timestamp,
+            sender_timestamp: self.sender_timestamp,
         })
     }
 }
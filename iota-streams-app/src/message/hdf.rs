@@ -51,7 +51,7 @@ impl<Link: Default> HDF<Link> {
         Self {
             link,
             encoding: UTF8,
-            version: STREAMS_1_VER,
+            version: STREAMS_VER,
             content_type: 0,
             payload_length: 0,
             frame_type: HDF_ID,
@@ -134,7 +134,7 @@ impl<Link: Default> HDF<Link> {
         try_or!(payload_length < 0x0400, MaxSizeExceeded(0x0400_usize, payload_length))?;
         Ok(Self {
             encoding: UTF8,
-            version: STREAMS_1_VER,
+            version: STREAMS_VER,
             content_type,
             payload_length,
             frame_type: HDF_ID,
@@ -151,7 +151,7 @@ impl<Link: Default> Default for HDF<Link> {
     fn default() -> Self {
         Self {
             encoding: UTF8,
-            version: STREAMS_1_VER,
+            version: STREAMS_VER,
             content_type: 0,
             payload_length: 0,
             frame_type: HDF_ID,
@@ -266,8 +266,8 @@ where
         ctx.absorb(&mut self.encoding)?
             .absorb(&mut self.version)?
             .guard(
-                self.version == STREAMS_1_VER,
-                InvalidMsgVersion(STREAMS_1_VER.0, self.version.0),
+                self.version == STREAMS_1_VER || self.version == STREAMS_2_VER,
+                InvalidMsgVersion(STREAMS_VER.0, self.version.0),
             )?
             .skip(&mut content_type_and_payload_length)?;
         {
@@ -119,12 +119,21 @@ pub trait LinkGenerator<Link: HasLink>: Default {
     /// Used by Subscriber to initialize link generator with the same state as Author
     fn reset(&mut self, seed: Link);
 
+    /// Used by Subscriber to check that an announcement link was generated by `gen` from the
+    /// announced public key
+    fn is_announcement_link(&self, link: &Link, pk: &ed25519::PublicKey) -> bool;
+
     /// Used by users to pseudo-randomly generate a new uniform message link from a cursor
     fn uniform_link_from(&self, cursor: Cursor<&<Link as HasLink>::Rel>) -> Link;
 
     /// Used by users to pseudo-randomly generate a new message link from a cursor
     fn link_from<T: AsRef<[u8]>>(&self, id: T, cursor: Cursor<&<Link as HasLink>::Rel>) -> Link;
 
+    /// Used by users to derive the link of a continuation frame of the message at `link`
+    fn frame_link_from(&self, link: &Link, frame_num: u32) -> Link {
+        self.uniform_link_from(Cursor::new_at(link.rel(), 0, frame_num))
+    }
+
     /// Derive a new link and construct a header with given content type.
     fn uniform_header_from(
         &self,
@@ -9,6 +9,13 @@ pub mod hdf;
 pub use hdf::HDF;
 pub mod pcf;
 pub use pcf::PCF;
+mod padding;
+pub use padding::Padding;
+pub mod codec;
+pub use codec::{
+    PayloadCodec,
+    PayloadCodecs,
+};
 
 mod generic;
 pub use generic::*;
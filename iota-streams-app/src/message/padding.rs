@@ -0,0 +1,118 @@
+//! Masked filler used to pad messages to a uniform length.
+//!
+//! ```ddml
+//! message Padding {
+//!     mask u32 length;
+//!     mask u8 filler[length];
+//! }
+//! ```
+//!
+//! Filler bytes are zero; unwrapping fails if they are not. Being masked, the filler is
+//! indistinguishable from the rest of the masked content.
+
+use iota_streams_core::{
+    async_trait,
+    prelude::{
+        typenum::U32,
+        Box,
+    },
+    sponge::prp::PRP,
+    try_or,
+    Errors::{
+        BadPadding,
+        UniformPayloadLengthExceeded,
+    },
+    Result,
+};
+use iota_streams_ddml::{
+    command::*,
+    io,
+    types::*,
+};
+
+use super::*;
+
+/// Size of the filler chunks.
+const CHUNK_SIZE: usize = 32;
+
+/// Masked padding of the given number of bytes, not counting the length field.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Padding(pub usize);
+
+impl Padding {
+    /// Padding extending a message of `size` bytes, wrapped with empty padding, to `length` bytes.
+    pub fn to_length(size: usize, length: usize) -> Result<Self> {
+        try_or!(
+            size <= length && length - size <= u32::MAX as usize,
+            UniformPayloadLengthExceeded(length, size)
+        )?;
+        Ok(Self(length - size))
+    }
+}
+
+#[async_trait(?Send)]
+impl<F> ContentSizeof<F> for Padding
+where
+    F: PRP,
+{
+    async fn sizeof<'c>(&self, ctx: &'c mut sizeof::Context<F>) -> Result<&'c mut sizeof::Context<F>> {
+        let chunk = NBytes::<U32>::default();
+        ctx.mask(Uint32(self.0 as u32))?;
+        for _ in 0..self.0 / CHUNK_SIZE {
+            ctx.mask(&chunk)?;
+        }
+        for _ in 0..self.0 % CHUNK_SIZE {
+            ctx.mask(Uint8(0))?;
+        }
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, Store> ContentWrap<F, Store> for Padding
+where
+    F: PRP,
+{
+    async fn wrap<'c, OS: io::OStream>(
+        &self,
+        _store: &Store,
+        ctx: &'c mut wrap::Context<F, OS>,
+    ) -> Result<&'c mut wrap::Context<F, OS>> {
+        let chunk = NBytes::<U32>::default();
+        ctx.mask(&Uint32(self.0 as u32))?;
+        for _ in 0..self.0 / CHUNK_SIZE {
+            ctx.mask(&chunk)?;
+        }
+        for _ in 0..self.0 % CHUNK_SIZE {
+            ctx.mask(&Uint8(0))?;
+        }
+        Ok(ctx)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, Store> ContentUnwrap<F, Store> for Padding
+where
+    F: PRP,
+{
+    async fn unwrap<'c, IS: io::IStream>(
+        &mut self,
+        _store: &Store,
+        ctx: &'c mut unwrap::Context<F, IS>,
+    ) -> Result<&'c mut unwrap::Context<F, IS>> {
+        let mut length = Uint32(0);
+        let mut chunk = NBytes::<U32>::default();
+        let mut byte = Uint8(0);
+        ctx.mask(&mut length)?;
+        let length = length.0 as usize;
+        for _ in 0..length / CHUNK_SIZE {
+            ctx.mask(&mut chunk)?
+                .guard(chunk.as_ref().iter().all(|b| *b == 0), BadPadding)?;
+        }
+        for _ in 0..length % CHUNK_SIZE {
+            ctx.mask(&mut byte)?.guard(byte.0 == 0, BadPadding)?;
+        }
+        self.0 = length;
+        Ok(ctx)
+    }
+}
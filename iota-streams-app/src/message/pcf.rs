@@ -1,13 +1,19 @@
 use iota_streams_core::{
     async_trait,
-    prelude::Box,
+    prelude::{
+        Box,
+        Vec,
+    },
     Result,
 };
 
 use iota_streams_core::{
     sponge::prp::PRP,
     try_or,
-    Errors::ValueOutOfRange,
+    Errors::{
+        OutputStreamNotFullyConsumed,
+        ValueOutOfRange,
+    },
 };
 use iota_streams_ddml::{
     command::*,
@@ -21,6 +27,9 @@ use iota_streams_ddml::{
 
 use super::*;
 
+/// Size of the PCF preceding the payload of a continuation frame: frame type and frame number.
+pub const FRAME_PCF_SIZE: usize = 4;
+
 #[allow(clippy::upper_case_acronyms)]
 pub struct PCF<Content> {
     pub frame_type: Uint8,
@@ -61,6 +70,29 @@ impl PCF<()> {
             content,
         }
     }
+
+    /// Wrap the PCF of a continuation frame.
+    ///
+    /// Continuation frames carry a slice of the message wrapped as a whole, so they are authenticated
+    /// by the MAC or signature of the whole message rather than by their own spongos.
+    pub fn wrap_frame<F: PRP>(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![0; FRAME_PCF_SIZE];
+        {
+            let mut ctx = wrap::Context::<F, &mut [u8]>::new(&mut buf[..]);
+            ctx.absorb(&self.frame_type)?.skip(&self.payload_frame_num)?;
+            try_or!(ctx.stream.is_empty(), OutputStreamNotFullyConsumed(ctx.stream.len()))?;
+        }
+        Ok(buf)
+    }
+
+    /// Unwrap the PCF of a continuation frame and return it together with the frame payload.
+    pub fn unwrap_frame<F: PRP>(bytes: &[u8]) -> Result<(Self, &[u8])> {
+        let mut pcf = Self::new_final_frame();
+        let mut ctx = unwrap::Context::<F, &[u8]>::new(bytes);
+        ctx.absorb(&mut pcf.frame_type)?.skip(&mut pcf.payload_frame_num)?;
+        payload_frame_num_check(&pcf.payload_frame_num)?;
+        Ok((pcf, ctx.stream))
+    }
 }
 
 fn payload_frame_num_from(n: u32) -> Result<NBytes<U3>> {
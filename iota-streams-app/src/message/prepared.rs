@@ -2,14 +2,20 @@ use iota_streams_core::Result;
 
 use super::*;
 use iota_streams_core::{
+    prelude::Vec,
     sponge::prp::PRP,
     try_or,
-    Errors::OutputStreamNotFullyConsumed,
+    Errors::{
+        MaxSizeExceeded,
+        OutputStreamNotFullyConsumed,
+    },
 };
 use iota_streams_ddml::{
     command::{
         sizeof,
         wrap,
+        Absorb,
+        Skip,
     },
     types::*,
 };
@@ -43,17 +49,23 @@ where
     Link::Rel: Eq + SkipFallback<F>,
     // Store: LinkStore<F, <Link as HasLink>::Rel>,
 {
+    /// Size of the wrapped message.
+    pub async fn sizeof(&self) -> Result<usize>
+    where
+        Content: ContentSizeof<F>,
+    {
+        let mut ctx = sizeof::Context::<F>::new();
+        self.header.sizeof(&mut ctx).await?;
+        self.content.sizeof(&mut ctx).await?;
+        Ok(ctx.get_size())
+    }
+
     pub async fn wrap<Store>(&self, store: &Store) -> Result<WrappedMessage<F, Link>>
     where
         HDF<Link>: ContentWrap<F, Store>,
         Content: ContentWrap<F, Store>,
     {
-        let buf_size = {
-            let mut ctx = sizeof::Context::<F>::new();
-            self.header.sizeof(&mut ctx).await?;
-            self.content.sizeof(&mut ctx).await?;
-            ctx.get_size()
-        };
+        let buf_size = self.sizeof().await?;
 
         let mut buf = vec![0; buf_size];
 
@@ -75,6 +87,87 @@ where
                 prev_link: Link::default(),
                 body: buf.into(),
             },
+            frames: Vec::new(),
+        })
+    }
+
+    /// Wrap message splitting it into frames of at most `frame_length` bytes.
+    ///
+    /// The message is wrapped as a whole, thus MAC and signature cover the complete payload. The
+    /// first frame contains the header, declaring the number of frames, and is sent to the message
+    /// link. The rest of the wrapped message is sliced into continuation frames, each starting with
+    /// an intermediate or final PCF, linked to the message with `link_gen`. Setting `frame_length`
+    /// to 0 disables splitting.
+    pub async fn wrap_frames<Store, LG>(
+        &self,
+        store: &Store,
+        link_gen: &LG,
+        frame_length: usize,
+    ) -> Result<WrappedMessage<F, Link>>
+    where
+        HDF<Link>: ContentWrap<F, Store>,
+        Content: ContentWrap<F, Store>,
+        LG: LinkGenerator<Link>,
+    {
+        let (head_size, buf_size) = {
+            let mut ctx = sizeof::Context::<F>::new();
+            self.header.sizeof(&mut ctx).await?;
+            let head_size = ctx.get_size() + pcf::FRAME_PCF_SIZE;
+            self.content.sizeof(&mut ctx).await?;
+            (head_size, ctx.get_size())
+        };
+        if frame_length == 0 || buf_size <= frame_length {
+            return self.wrap(store).await;
+        }
+        // Header must be parsable from the first frame alone and continuation frames must carry payload.
+        try_or!(
+            head_size <= frame_length && pcf::FRAME_PCF_SIZE < frame_length,
+            MaxSizeExceeded(frame_length, head_size)
+        )?;
+
+        let frame_payload_length = frame_length - pcf::FRAME_PCF_SIZE;
+        let frame_count = 1 + (buf_size - frame_length + frame_payload_length - 1) / frame_payload_length;
+        let header = self.header.clone().with_payload_frame_count(frame_count as u32)?;
+
+        let mut buf = vec![0; buf_size];
+
+        let spongos = {
+            let mut ctx = wrap::Context::new(&mut buf[..]);
+            header.wrap(store, &mut ctx).await?;
+            ctx.absorb(&INIT_PCF_ID)?.skip(&self.content.payload_frame_num)?;
+            self.content.content.wrap(store, &mut ctx).await?;
+            try_or!(ctx.stream.is_empty(), OutputStreamNotFullyConsumed(ctx.stream.len()))?;
+            ctx.spongos
+        };
+
+        let link = self.header.link.clone();
+        let mut frames = Vec::with_capacity(frame_count - 1);
+        for (i, chunk) in buf[frame_length..].chunks(frame_payload_length).enumerate() {
+            let frame_num = i as u32 + 2;
+            let pcf = if frame_num as usize == frame_count {
+                PCF::new_final_frame()
+            } else {
+                PCF::new_inter_frame()
+            }
+            .with_payload_frame_num(frame_num)?;
+            let mut body = pcf.wrap_frame::<F>()?;
+            body.extend_from_slice(chunk);
+            let frame_link = link_gen.frame_link_from(&link, frame_num);
+            frames.push(BinaryMessage::new(frame_link, link.clone(), body.into()));
+        }
+        buf.truncate(frame_length);
+
+        Ok(WrappedMessage {
+            wrapped: WrapState {
+                link: link.clone(),
+                spongos,
+            },
+            message: BinaryMessage {
+                link,
+                prev_link: Link::default(),
+                body: buf.into(),
+            },
+            frames,
         })
     }
 }
@@ -15,9 +15,19 @@ use iota_streams_ddml::types::Uint8;
 
 /// Streams version number.
 pub const STREAMS_1_VER: Uint8 = Uint8(0);
+/// Streams version adding padding and sender timestamps to packets and node key updates, the group
+/// key, padding and anonymous slots to keyloads.
+pub const STREAMS_2_VER: Uint8 = Uint8(1);
+/// Version of the messages sent, messages of earlier versions are still read.
+pub const STREAMS_VER: Uint8 = STREAMS_2_VER;
 
 /// Encoding Constants
 pub const UTF8: Uint8 = Uint8(0);
+pub const RAW: Uint8 = Uint8(1);
+pub const DEFLATE: Uint8 = Uint8(2);
+pub const ZSTD: Uint8 = Uint8(3);
+pub const CBOR: Uint8 = Uint8(4);
+pub const UTF8_STRICT: Uint8 = Uint8(5);
 
 //
 pub const HDF_ID: Uint8 = Uint8(4);
@@ -1,5 +1,8 @@
 use core::fmt;
-use iota_streams_core::Result;
+use iota_streams_core::{
+    prelude::Vec,
+    Result,
+};
 
 use super::*;
 use iota_streams_core::sponge::{
@@ -43,6 +46,8 @@ where
 pub struct WrappedMessage<F, Link: HasLink> {
     pub message: BinaryMessage<F, Link>,
     pub wrapped: WrapState<F, Link>,
+    /// Continuation frames to be sent after `message` if the message was split, in order.
+    pub frames: Vec<BinaryMessage<F, Link>>,
 }
 
 // impl<F: PRP, Link: HasLink> WrappedMessage<F, Link>
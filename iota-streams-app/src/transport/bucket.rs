@@ -77,7 +77,7 @@ where
     async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
         let mut msgs = self.recv_messages(link).await?;
         if let Some(msg) = msgs.pop() {
-            try_or!(msgs.is_empty(), MessageNotUnique(link.to_string())).unwrap();
+            try_or!(msgs.is_empty(), MessageNotUnique(link.to_string()))?;
             Ok(msg)
         } else {
             err!(MessageLinkNotFoundInBucket(link.to_string()))?
@@ -0,0 +1,263 @@
+//! Read-through caching transport wrapper.
+//!
+//! Messages are immutable once published, so the messages received at a link can be served again
+//! without a round trip to the wrapped transport. They are kept in memory, the least recently used
+//! links being evicted beyond a capacity, and optionally on disk, where they outlive the process.
+//! Links without messages are not cached as messages may still be published there, and messages
+//! published at a cached link by another party remain unseen while the link stays cached, unless
+//! the link is refreshed. Users refresh a link once all the messages cached there were rejected, so
+//! that spam published ahead of the genuine message does not hide it for good.
+
+use super::*;
+use crate::message::HasLink;
+use core::{
+    fmt,
+    hash,
+};
+use std::{
+    fs,
+    path::Path,
+};
+
+use iota_streams_core::{
+    async_trait,
+    prelude::{
+        Box,
+        HashMap,
+        ToString,
+    },
+    wrapped_err,
+    Errors::{
+        FileTransportFailure,
+        MessageNotUnique,
+    },
+    WrappedError,
+};
+
+/// Number of receptions served by each tier of a [`CachingTransport`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Receptions served from memory.
+    pub hits: usize,
+    /// Receptions served from disk.
+    pub disk_hits: usize,
+    /// Receptions forwarded to the wrapped transport.
+    pub misses: usize,
+}
+
+/// Transport caching the messages received through the wrapped transport.
+///
+/// Sent messages are passed through to the wrapped transport and added to the links already
+/// cached. Eviction scans the cached links, the capacity is thus meant to stay moderate.
+pub struct CachingTransport<T, Link, Msg> {
+    inner: T,
+    capacity: usize,
+    /// Cached messages by link, with the tick of their last use.
+    entries: HashMap<Link, (u64, Vec<Msg>)>,
+    tick: u64,
+    disk: Option<FileTransport<Link, Msg>>,
+    stats: CacheStats,
+}
+
+impl<T, Link, Msg> CachingTransport<T, Link, Msg>
+where
+    Link: HasLink + hash::Hash,
+{
+    /// Wrap `inner` caching the messages of at most `capacity` links in memory.
+    pub fn new(inner: T, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+            disk: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Wrap `inner` caching the messages of at most `capacity` links in memory and of all links in
+    /// the directory `root`, which may be shared with other processes.
+    pub fn with_disk<P: AsRef<Path>>(inner: T, capacity: usize, root: P) -> Self {
+        Self {
+            disk: Some(FileTransport::new(root)),
+            ..Self::new(inner, capacity)
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Drop the messages cached in memory, the messages cached on disk are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Cache `msgs` in memory, evicting the least recently used link if needed.
+    fn insert(&mut self, link: Link, msgs: Vec<Msg>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&link) {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (tick, _))| *tick)
+                .map(|(link, _)| link.clone());
+            if let Some(lru) = lru {
+                self.entries.remove(&lru);
+            }
+        }
+        let tick = self.next_tick();
+        self.entries.insert(link, (tick, msgs));
+    }
+}
+
+impl<T, Link, Msg> CachingTransport<T, Link, Msg>
+where
+    Link: HasLink + hash::Hash + fmt::Display,
+    Msg: FileMessage<Link> + Clone,
+{
+    /// Cached messages at `link`, looked up in memory then on disk.
+    async fn lookup(&mut self, link: &Link) -> Result<Option<Vec<Msg>>> {
+        let tick = self.next_tick();
+        if let Some((last_used, msgs)) = self.entries.get_mut(link) {
+            *last_used = tick;
+            self.stats.hits += 1;
+            return Ok(Some(msgs.clone()));
+        }
+        if let Some(disk) = self.disk.as_mut() {
+            match disk.recv_messages(link).await {
+                Ok(msgs) => {
+                    self.stats.disk_hits += 1;
+                    self.insert(link.clone(), msgs.clone());
+                    return Ok(Some(msgs));
+                }
+                Err(e) if is_message_not_found(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.stats.misses += 1;
+        Ok(None)
+    }
+
+    /// Drop the messages cached at `link`, in memory and on disk.
+    fn evict(&mut self, link: &Link) -> Result<()> {
+        self.entries.remove(link);
+        if let Some(disk) = self.disk.as_ref() {
+            let dir = disk.link_dir(link);
+            if dir.is_dir() {
+                fs::remove_dir_all(&dir)
+                    .map_err(|e| wrapped_err!(FileTransportFailure(dir.display().to_string()), WrappedError(e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Cache the messages received from the wrapped transport at `link`.
+    async fn store(&mut self, link: &Link, msgs: &[Msg]) -> Result<()> {
+        if let Some(disk) = self.disk.as_mut() {
+            for msg in msgs {
+                disk.send_message(msg).await?;
+            }
+        }
+        self.insert(link.clone(), msgs.to_vec());
+        Ok(())
+    }
+}
+
+impl<T: TransportOptions, Link, Msg> TransportOptions for CachingTransport<T, Link, Msg> {
+    type SendOptions = <T as TransportOptions>::SendOptions;
+    fn get_send_options(&self) -> Self::SendOptions {
+        self.inner.get_send_options()
+    }
+    fn set_send_options(&mut self, opt: Self::SendOptions) {
+        self.inner.set_send_options(opt)
+    }
+
+    type RecvOptions = <T as TransportOptions>::RecvOptions;
+    fn get_recv_options(&self) -> Self::RecvOptions {
+        self.inner.get_recv_options()
+    }
+    fn set_recv_options(&mut self, opt: Self::RecvOptions) {
+        self.inner.set_recv_options(opt)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: TransportDetails<Link>, Link, Msg> TransportDetails<Link> for CachingTransport<T, Link, Msg> {
+    type Details = <T as TransportDetails<Link>>::Details;
+    async fn get_link_details(&mut self, link: &Link) -> Result<Self::Details> {
+        self.inner.get_link_details(link).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<T, Link, Msg> Transport<Link, Msg> for CachingTransport<T, Link, Msg>
+where
+    T: Transport<Link, Msg>,
+    Link: HasLink + hash::Hash + fmt::Display,
+    Msg: FileMessage<Link> + Clone,
+{
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        self.inner.send_message(msg).await?;
+        let link = msg.link();
+        if let Some((_, msgs)) = self.entries.get_mut(link) {
+            msgs.push(msg.clone());
+        }
+        if let Some(disk) = self.disk.as_mut() {
+            if disk.link_dir(link).is_dir() {
+                disk.send_message(msg).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        if let Some(msgs) = self.lookup(link).await? {
+            return Ok(msgs);
+        }
+        let msgs = self.inner.recv_messages(link).await?;
+        if !msgs.is_empty() {
+            self.store(link, &msgs).await?;
+        }
+        Ok(msgs)
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        match self.lookup(link).await? {
+            Some(mut msgs) => {
+                try_or!(msgs.len() == 1, MessageNotUnique(link.to_string()))?;
+                Ok(msgs.remove(0))
+            }
+            None => {
+                let msg = self.inner.recv_message(link).await?;
+                self.store(link, core::slice::from_ref(&msg)).await?;
+                Ok(msg)
+            }
+        }
+    }
+
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.evict(link)?;
+        self.stats.misses += 1;
+        let msgs = self.inner.recv_messages(link).await?;
+        if !msgs.is_empty() {
+            self.store(link, &msgs).await?;
+        }
+        Ok(msgs)
+    }
+}
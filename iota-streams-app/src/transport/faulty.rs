@@ -0,0 +1,252 @@
+//! Fault injecting transport wrapper.
+//!
+//! Sent messages are lost, published twice, altered or withheld for a while before being
+//! forwarded to the wrapped transport, and calls fail as if the node was temporarily unreachable.
+//! Faults are drawn from a PRNG initialized with a seed, the same sequence of calls thus always
+//! suffers the same faults. Withheld messages become visible after a number of receptions,
+//! messages sent later may thus become visible earlier.
+
+use super::*;
+use crate::message::BinaryMessage;
+
+use iota_streams_core::{
+    async_trait,
+    err,
+    prelude::Box,
+    prng,
+    sponge::prp::PRP,
+    Errors::InjectedFault,
+};
+
+/// Messages whose binary body can be altered by [`FaultyTransport`].
+pub trait CorruptibleMessage {
+    /// Binary body of the message.
+    fn body_bytes_mut(&mut self) -> &mut [u8];
+}
+
+impl<F, Link> CorruptibleMessage for BinaryMessage<F, Link> {
+    fn body_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.body.bytes[..]
+    }
+}
+
+#[cfg(feature = "tangle")]
+impl<F> CorruptibleMessage for tangle::TangleMessage<F> {
+    fn body_bytes_mut(&mut self) -> &mut [u8] {
+        self.binary.body_bytes_mut()
+    }
+}
+
+/// Probabilities of the faults injected into every sent message.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Faults {
+    /// Probability that the message is lost.
+    pub drop: f64,
+    /// Probability that the message is published twice.
+    pub duplicate: f64,
+    /// Probability that a byte of the message body is altered.
+    pub corrupt: f64,
+    /// Probability that the message is withheld for `defer_polls` receptions.
+    pub defer: f64,
+    /// Number of receptions a withheld message stays invisible for.
+    pub defer_polls: usize,
+    /// Probability that a call fails with a transient error, without reaching the wrapped transport.
+    pub fail: f64,
+}
+
+/// Number of faults injected so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    pub dropped: usize,
+    pub duplicated: usize,
+    pub corrupted: usize,
+    pub deferred: usize,
+    pub failed: usize,
+}
+
+/// Transport injecting faults into the messages sent through the wrapped transport.
+///
+/// Users sharing the faulty transport should share one instance, see
+/// [`new_shared_transport`](super::new_shared_transport), so that faults are drawn from a single
+/// sequence.
+pub struct FaultyTransport<F: PRP, T, Msg> {
+    inner: T,
+    faults: Faults,
+    prng: prng::Prng<F>,
+    counter: u64,
+    /// Withheld messages with the number of receptions they stay invisible for.
+    deferred: Vec<(usize, Msg)>,
+    stats: FaultStats,
+}
+
+impl<F: PRP, T, Msg> FaultyTransport<F, T, Msg> {
+    /// Wrap `inner` injecting `faults` drawn from a PRNG initialized with `seed`.
+    pub fn new(inner: T, seed: &str, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            prng: prng::from_seed("IOTA Streams faulty transport", seed),
+            counter: 0,
+            deferred: Vec::new(),
+            stats: FaultStats::default(),
+        }
+    }
+
+    pub fn faults(&self) -> Faults {
+        self.faults
+    }
+
+    /// Change the faults injected into subsequently sent messages.
+    pub fn set_faults(&mut self, faults: Faults) {
+        self.faults = faults;
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        self.stats
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Make the withheld messages visible with the next reception.
+    pub fn release_deferred(&mut self) {
+        for (polls, _) in self.deferred.iter_mut() {
+            *polls = 0;
+        }
+    }
+
+    /// Count a reception and publish the withheld messages that become visible.
+    async fn poll_deferred<Link>(&mut self) -> Result<()>
+    where
+        T: Transport<Link, Msg>,
+    {
+        let mut withheld = Vec::with_capacity(self.deferred.len());
+        for (polls, msg) in core::mem::take(&mut self.deferred) {
+            if polls == 0 {
+                self.inner.send_message(&msg).await?;
+            } else {
+                withheld.push((polls - 1, msg));
+            }
+        }
+        self.deferred = withheld;
+        Ok(())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut rnd = [0_u8; 8];
+        self.prng.gen(&self.counter.to_be_bytes(), &mut rnd);
+        self.counter += 1;
+        u64::from_be_bytes(rnd)
+    }
+
+    /// Fail the call with a transient error if drawn.
+    fn check_fail(&mut self) -> Result<()> {
+        if self.chance(self.faults.fail) {
+            self.stats.failed += 1;
+            return err!(InjectedFault);
+        }
+        Ok(())
+    }
+
+    /// Draw whether an event of probability `p` occurs. Nothing is drawn for impossible events.
+    fn chance(&mut self, p: f64) -> bool {
+        // 53 random bits give a uniform value in [0, 1)
+        0.0 < p && ((self.next_u64() >> 11) as f64) / ((1_u64 << 53) as f64) < p
+    }
+}
+
+impl<F: PRP, T, Msg: CorruptibleMessage> FaultyTransport<F, T, Msg> {
+    fn corrupt(&mut self, msg: &mut Msg) {
+        let len = msg.body_bytes_mut().len();
+        if len != 0 {
+            let rnd = self.next_u64();
+            // Flip at least one bit of a random byte
+            msg.body_bytes_mut()[(rnd % len as u64) as usize] ^= 1 + ((rnd >> 32) % 255) as u8;
+            self.stats.corrupted += 1;
+        }
+    }
+}
+
+impl<F: PRP, T: TransportOptions, Msg> TransportOptions for FaultyTransport<F, T, Msg> {
+    type SendOptions = <T as TransportOptions>::SendOptions;
+    fn get_send_options(&self) -> Self::SendOptions {
+        self.inner.get_send_options()
+    }
+    fn set_send_options(&mut self, opt: Self::SendOptions) {
+        self.inner.set_send_options(opt)
+    }
+
+    type RecvOptions = <T as TransportOptions>::RecvOptions;
+    fn get_recv_options(&self) -> Self::RecvOptions {
+        self.inner.get_recv_options()
+    }
+    fn set_recv_options(&mut self, opt: Self::RecvOptions) {
+        self.inner.set_recv_options(opt)
+    }
+}
+
+#[async_trait(?Send)]
+impl<F: PRP, T: TransportDetails<Link>, Link, Msg> TransportDetails<Link> for FaultyTransport<F, T, Msg> {
+    type Details = <T as TransportDetails<Link>>::Details;
+    async fn get_link_details(&mut self, link: &Link) -> Result<Self::Details> {
+        self.inner.get_link_details(link).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, T, Link, Msg> Transport<Link, Msg> for FaultyTransport<F, T, Msg>
+where
+    F: PRP,
+    T: Transport<Link, Msg>,
+    Msg: CorruptibleMessage + Clone,
+{
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        self.check_fail()?;
+        if self.chance(self.faults.drop) {
+            self.stats.dropped += 1;
+            return Ok(());
+        }
+        let mut msg = msg.clone();
+        if self.chance(self.faults.corrupt) {
+            self.corrupt(&mut msg);
+        }
+        let copies = if self.chance(self.faults.duplicate) {
+            self.stats.duplicated += 1;
+            2
+        } else {
+            1
+        };
+        for _ in 0..copies {
+            if self.chance(self.faults.defer) {
+                self.stats.deferred += 1;
+                self.deferred.push((self.faults.defer_polls, msg.clone()));
+            } else {
+                self.inner.send_message(&msg).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.check_fail()?;
+        self.poll_deferred::<Link>().await?;
+        self.inner.recv_messages(link).await
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        self.check_fail()?;
+        self.poll_deferred::<Link>().await?;
+        self.inner.recv_message(link).await
+    }
+
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.check_fail()?;
+        self.poll_deferred::<Link>().await?;
+        self.inner.refresh_messages(link).await
+    }
+}
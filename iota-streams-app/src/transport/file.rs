@@ -0,0 +1,430 @@
+//! File system backed transport.
+//!
+//! Messages are stored under a root directory, one sub-directory per message index. The index
+//! of a link is the hex encoded blake2b256 hash of its binary representation, which for
+//! [`TangleAddress`](super::tangle::TangleAddress) coincides with the index the message would be
+//! attached to in the Tangle. Every message sent to the same link gets its own file so that
+//! several processes can publish to a shared directory concurrently: files are first written
+//! under a temporary name and then atomically renamed, thus readers never observe partially
+//! written messages.
+
+use super::*;
+use crate::message::{
+    BinaryMessage,
+    HasLink,
+    LinkedMessage,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process,
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use iota_streams_core::{
+    async_trait,
+    crypto::hashes::{
+        blake2b,
+        Digest,
+    },
+    err,
+    prelude::{
+        hex,
+        Box,
+        String,
+        ToString,
+    },
+    wrapped_err,
+    Error,
+    Errors::{
+        FileTransportFailure,
+        MalformedFileMessage,
+        MessageLinkNotFoundInFile,
+        MessageNotUnique,
+    },
+    WrappedError,
+};
+
+/// Extension of stored message files.
+const MSG_FILE_EXT: &str = "msg";
+
+/// Counter distinguishing messages written within the same process and nanosecond.
+static MSG_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Binary encoding of messages stored by [`FileTransport`].
+///
+/// The link of the message is not encoded as it is implied by the location of the file.
+pub trait FileMessage<Link>: LinkedMessage<Link> + Sized {
+    /// Encode message into bytes.
+    fn to_file_bytes(&self) -> Vec<u8>;
+
+    /// Decode message located at `link` from bytes.
+    fn from_file_bytes(link: &Link, bytes: &[u8]) -> Result<Self>;
+}
+
+impl<F, Link> FileMessage<Link> for BinaryMessage<F, Link>
+where
+    Link: HasLink,
+{
+    fn to_file_bytes(&self) -> Vec<u8> {
+        let prev_link = self.prev_link.to_bytes();
+        let mut bytes = Vec::with_capacity(4 + prev_link.len() + self.body.bytes.len());
+        bytes.extend_from_slice(&(prev_link.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&prev_link);
+        bytes.extend_from_slice(&self.body.bytes);
+        bytes
+    }
+
+    fn from_file_bytes(link: &Link, bytes: &[u8]) -> Result<Self> {
+        try_or!(4 <= bytes.len(), MalformedFileMessage(4, bytes.len()))?;
+        let mut len = [0_u8; 4];
+        len.copy_from_slice(&bytes[..4]);
+        let prev_end = 4 + u32::from_be_bytes(len) as usize;
+        try_or!(prev_end <= bytes.len(), MalformedFileMessage(prev_end, bytes.len()))?;
+        let prev_link = Link::try_from_bytes(&bytes[4..prev_end])?;
+        Ok(BinaryMessage::new(
+            link.clone(),
+            prev_link,
+            bytes[prev_end..].to_vec().into(),
+        ))
+    }
+}
+
+#[cfg(feature = "tangle")]
+impl<F> FileMessage<tangle::TangleAddress> for tangle::TangleMessage<F> {
+    fn to_file_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.timestamp.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.binary.to_file_bytes());
+        bytes
+    }
+
+    fn from_file_bytes(link: &tangle::TangleAddress, bytes: &[u8]) -> Result<Self> {
+        try_or!(8 <= bytes.len(), MalformedFileMessage(8, bytes.len()))?;
+        let mut timestamp = [0_u8; 8];
+        timestamp.copy_from_slice(&bytes[..8]);
+        let binary = BinaryMessage::from_file_bytes(link, &bytes[8..])?;
+        Ok(Self::with_timestamp(binary, u64::from_be_bytes(timestamp)))
+    }
+}
+
+/// Transport storing messages as files in a local directory.
+///
+/// Cloned instances share the same directory, as do instances created in other processes with
+/// the same root path.
+pub struct FileTransport<Link, Msg> {
+    root: PathBuf,
+    _phantom: PhantomData<(Link, Msg)>,
+}
+
+impl<Link, Msg> Clone for FileTransport<Link, Msg> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Link, Msg> FileTransport<Link, Msg>
+where
+    Link: HasLink,
+{
+    /// Create a transport storing messages under `root`. The directory is created lazily.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Root directory of the transport.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory containing the messages sent to `link`.
+    pub fn link_dir(&self, link: &Link) -> PathBuf {
+        let index = blake2b::Blake2b256::digest(&link.to_bytes());
+        self.root.join(hex::encode(index))
+    }
+}
+
+fn io_err(path: &Path, e: io::Error) -> Error {
+    wrapped_err!(FileTransportFailure(path.display().to_string()), WrappedError(e))
+}
+
+fn unique_file_name() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+    let counter = MSG_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:020}-{:010}-{:010}", nanos, process::id(), counter)
+}
+
+impl<Link, Msg> TransportOptions for FileTransport<Link, Msg> {
+    type SendOptions = ();
+    fn get_send_options(&self) {}
+    fn set_send_options(&mut self, _opt: ()) {}
+
+    type RecvOptions = ();
+    fn get_recv_options(&self) {}
+    fn set_recv_options(&mut self, _opt: ()) {}
+}
+
+#[async_trait(?Send)]
+impl<Link, Msg> Transport<Link, Msg> for FileTransport<Link, Msg>
+where
+    Link: HasLink + fmt::Display,
+    Msg: FileMessage<Link>,
+{
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        let dir = self.link_dir(msg.link());
+        fs::create_dir_all(&dir).map_err(|e| io_err(&dir, e))?;
+
+        // Dot-prefixed temporary files are ignored by readers until renamed.
+        let name = unique_file_name();
+        let tmp = dir.join(format!(".{}.tmp", name));
+        let path = dir.join(format!("{}.{}", name, MSG_FILE_EXT));
+        fs::write(&tmp, msg.to_file_bytes()).map_err(|e| io_err(&tmp, e))?;
+        fs::rename(&tmp, &path).map_err(|e| io_err(&path, e))
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        let dir = self.link_dir(link);
+        if !dir.is_dir() {
+            return err!(MessageLinkNotFoundInFile(link.to_string()));
+        }
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(|e| io_err(&dir, e))? {
+            let path = entry.map_err(|e| io_err(&dir, e))?.path();
+            let is_msg = path.extension().map_or(false, |ext| ext == MSG_FILE_EXT)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| !name.starts_with('.'));
+            if is_msg {
+                paths.push(path);
+            }
+        }
+        // File names start with the timestamp, so messages are returned in the order they were sent.
+        paths.sort();
+
+        let mut msgs = Vec::with_capacity(paths.len());
+        for path in paths {
+            let bytes = fs::read(&path).map_err(|e| io_err(&path, e))?;
+            msgs.push(Msg::from_file_bytes(link, &bytes)?);
+        }
+        if msgs.is_empty() {
+            err!(MessageLinkNotFoundInFile(link.to_string()))
+        } else {
+            Ok(msgs)
+        }
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        let mut msgs = self.recv_messages(link).await?;
+        if let Some(msg) = msgs.pop() {
+            try_or!(msgs.is_empty(), MessageNotUnique(link.to_string()))?;
+            Ok(msg)
+        } else {
+            err!(MessageLinkNotFoundInFile(link.to_string()))
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<Link, Msg> TransportDetails<Link> for FileTransport<Link, Msg>
+where
+    Link: HasLink,
+{
+    type Details = ();
+    async fn get_link_details(&mut self, _link: &Link) -> Result<Self::Details> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tangle"))]
+mod test {
+    use super::*;
+    use crate::{
+        message::BinaryBody,
+        transport::tangle::{
+            AppInst,
+            MsgId,
+            TangleAddress,
+            TangleMessage,
+        },
+    };
+    use core::sync::atomic::AtomicBool;
+    use std::{
+        env,
+        sync::Arc,
+        thread,
+    };
+
+    use iota_streams_core::{
+        ensure,
+        Errors,
+    };
+    use iota_streams_core_keccak::sponge::prp::keccak::KeccakF1600;
+
+    type Message = TangleMessage<KeccakF1600>;
+    type Files = FileTransport<TangleAddress, Message>;
+
+    /// Counter distinguishing the directories of the tests run by the same process.
+    static TEST_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Empty directory of its own for the test `name`.
+    fn test_dir(name: &str) -> PathBuf {
+        let counter = TEST_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("iota-streams-file-{}-{}-{}", name, process::id(), counter));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Address of the `n`th message of the test channel.
+    fn address(n: u8) -> TangleAddress {
+        TangleAddress::new(AppInst::from(&[2_u8; 40][..]), MsgId::from(&[n; 12][..]))
+    }
+
+    /// Message at the address `n` with `body`, timestamped with `n`.
+    fn message(n: u8, body: &str) -> Message {
+        let binary = BinaryMessage::new(address(n), address(0), BinaryBody::from(body.as_bytes().to_vec()));
+        TangleMessage::with_timestamp(binary, n as u64)
+    }
+
+    fn body(msg: &Message) -> &[u8] {
+        &msg.binary.body.bytes
+    }
+
+    /// Messages sent to one index are all received, in the order they were sent.
+    async fn several_messages_example(dir: &Path) -> Result<()> {
+        let mut transport = Files::new(dir);
+        transport.send_message(&message(1, "FIRST")).await?;
+        transport.send_message(&message(1, "SECOND")).await?;
+        let msgs = transport.recv_messages(&address(1)).await?;
+        ensure!(
+            msgs.len() == 2 && body(&msgs[0]) == b"FIRST" && body(&msgs[1]) == b"SECOND",
+            "messages should be received in the order they were sent"
+        );
+
+        let e = transport.recv_message(&address(1)).await.unwrap_err();
+        ensure!(
+            matches!(e.downcast_ref::<Errors>(), Some(MessageNotUnique(..))),
+            "several messages at one index should not be received as one"
+        );
+        let e = transport.recv_messages(&address(2)).await.unwrap_err();
+        ensure!(is_message_not_found(&e), "index without messages should not be found");
+        Ok(())
+    }
+
+    #[test]
+    fn run_several_messages_scenario() {
+        let dir = test_dir("several-messages");
+        smol::block_on(several_messages_example(&dir)).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Messages are read back by a transport opened later on the same directory.
+    async fn reopen_example(dir: &Path) -> Result<()> {
+        Files::new(dir).send_message(&message(1, "STORED")).await?;
+
+        let msg = Files::new(dir).recv_message(&address(1)).await?;
+        ensure!(
+            body(&msg) == b"STORED" && msg.timestamp == 1 && msg.binary.prev_link == address(0),
+            "message should be read back after reopening"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_reopen_scenario() {
+        let dir = test_dir("reopen");
+        smol::block_on(reopen_example(&dir)).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Readers never observe partially written messages while writers publish to the same index.
+    #[test]
+    fn run_concurrent_access_scenario() {
+        const WRITERS: usize = 4;
+        const MESSAGES: usize = 16;
+        let dir = test_dir("concurrent-access");
+        let bodies: Vec<String> = (0..WRITERS * MESSAGES)
+            .map(|i| format!("WRITER{}MESSAGE{}", i / MESSAGES, i % MESSAGES))
+            .collect();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (dir, bodies, done) = (dir.clone(), bodies.clone(), done.clone());
+            thread::spawn(move || {
+                smol::block_on(async {
+                    let mut transport = Files::new(&dir);
+                    let mut received = 0;
+                    while !done.load(Ordering::Acquire) {
+                        match transport.recv_messages(&address(1)).await {
+                            Ok(msgs) => {
+                                assert!(received <= msgs.len(), "received messages should not vanish");
+                                assert!(
+                                    msgs.iter().all(|msg| bodies.iter().any(|b| b.as_bytes() == body(msg))),
+                                    "received messages should be whole"
+                                );
+                                received = msgs.len();
+                            }
+                            Err(e) => assert!(is_message_not_found(&e), "unexpected error {}", e),
+                        }
+                    }
+                })
+            })
+        };
+        let writers: Vec<_> = bodies
+            .chunks(MESSAGES)
+            .map(|chunk| {
+                let (dir, chunk) = (dir.clone(), chunk.to_vec());
+                thread::spawn(move || {
+                    smol::block_on(async {
+                        let mut transport = Files::new(&dir);
+                        for body in chunk.iter() {
+                            transport.send_message(&message(1, body)).await.unwrap();
+                        }
+                    })
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+        reader.join().unwrap();
+
+        let msgs = smol::block_on(Files::new(&dir).recv_messages(&address(1))).unwrap();
+        assert_eq!(msgs.len(), WRITERS * MESSAGES, "every message should be received");
+        for writer in bodies.chunks(MESSAGES) {
+            let received: Vec<&[u8]> = msgs
+                .iter()
+                .map(body)
+                .filter(|b| writer.iter().any(|w| w.as_bytes() == *b))
+                .collect();
+            let sent: Vec<&[u8]> = writer.iter().map(|w| w.as_bytes()).collect();
+            assert_eq!(
+                received, sent,
+                "messages of a writer should be received in the order they were sent"
+            );
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -6,6 +6,9 @@ use iota_streams_core::{
         RefCell,
         Vec,
     },
+    try_or,
+    Error,
+    Errors,
     Result,
 };
 
@@ -38,6 +41,42 @@ pub trait Transport<Link, Msg>: TransportOptions + TransportDetails<Link> {
 
     /// Receive a message with default options.
     async fn recv_message(&mut self, link: &Link) -> Result<Msg>;
+
+    /// Receive the messages at `link` again, bypassing any cache, once all the messages received
+    /// there were rejected. Transports without cache have nothing to refresh and return no message.
+    async fn refresh_messages(&mut self, _link: &Link) -> Result<Vec<Msg>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Whether a transport failed to receive a message because none has been published at the link (yet),
+/// as opposed to failing to reach or read the storage.
+pub fn is_message_not_found(error: &Error) -> bool {
+    matches!(
+        error.downcast_ref::<Errors>(),
+        Some(Errors::MessageLinkNotFoundInTangle(_))
+            | Some(Errors::MessageLinkNotFoundInBucket(_))
+            | Some(Errors::MessageLinkNotFoundInFile(_))
+            | Some(Errors::MessageLinkNotFoundInTrace(_))
+    )
+}
+
+/// Whether a transport call failed for a reason that may not persist, and is thus worth retrying:
+/// timeouts, nodes that could not be reached or failed to serve the call and message contents that
+/// could not be fetched. Other errors, eg. a missing message or a rejected call, are not transient.
+pub fn is_transient(error: &Error) -> bool {
+    matches!(
+        error.downcast_ref::<Errors>(),
+        Some(Errors::TransportTimedOut(_))
+            | Some(Errors::ClientUnavailable(_))
+            | Some(Errors::MessageContentsNotFound)
+            | Some(Errors::InjectedFault)
+    )
+}
+
+/// Wrap a transport so that it can be shared by several users.
+pub fn new_shared_transport<Tsp>(tsp: Tsp) -> Rc<RefCell<Tsp>> {
+    Rc::new(RefCell::new(tsp))
 }
 
 impl<Tsp: TransportOptions> TransportOptions for Rc<RefCell<Tsp>> {
@@ -82,6 +121,11 @@ impl<Link, Msg, Tsp: Transport<Link, Msg>> Transport<Link, Msg> for Rc<RefCell<T
     async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
         self.borrow_mut().recv_message(link).await
     }
+
+    // Receive messages again bypassing any cache.
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.borrow_mut().refresh_messages(link).await
+    }
 }
 
 #[cfg(any(feature = "sync-spin", feature = "sync-parking-lot"))]
@@ -144,12 +188,55 @@ mod sync {
         async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
             self.lock().recv_message(link).await
         }
+
+        // Receive messages again bypassing any cache.
+        async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+            self.lock().refresh_messages(link).await
+        }
     }
 }
 
 mod bucket;
 pub use bucket::BucketTransport;
-use iota_streams_core::try_or;
+mod faulty;
+pub use faulty::{
+    CorruptibleMessage,
+    FaultStats,
+    Faults,
+    FaultyTransport,
+};
+#[cfg(feature = "std")]
+mod caching;
+#[cfg(feature = "std")]
+pub use caching::{
+    CacheStats,
+    CachingTransport,
+};
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+pub use file::{
+    FileMessage,
+    FileTransport,
+};
+#[cfg(feature = "std")]
+mod replay;
+#[cfg(feature = "std")]
+pub use replay::{
+    RecordingTransport,
+    ReplayTransport,
+};
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "std")]
+pub use retry::{
+    RetryOptions,
+    RetryTransport,
+    RetryTransportOptions,
+};
 
 #[cfg(feature = "tangle")]
 pub mod tangle;
+
+#[cfg(all(test, feature = "std", feature = "tangle"))]
+mod test;
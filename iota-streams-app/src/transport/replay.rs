@@ -0,0 +1,398 @@
+//! Recording and replay of transport sessions.
+//!
+//! [`RecordingTransport`] forwards every call to the wrapped transport and appends the call with
+//! its result to a trace file. [`ReplayTransport`] serves the recorded results back in the same
+//! order, so that a session of a user can be reproduced without the original transport, eg. for
+//! bisecting a failure offline. Messages are encoded with [`FileMessage`]:
+//!
+//! ```text
+//! trace   = "STRMTRC" version:u8 call*
+//! call    = kind:u8 link:bytes outcome
+//! outcome = 0 | 1 n:u32 message:bytes^n | 2 | 3 error:bytes
+//! bytes   = length:u32 byte^length
+//! ```
+//!
+//! Call kinds are 0 for `send_message`, 1 for `recv_messages` and 2 for `recv_message`. Outcomes
+//! are 0 for a sent message, 1 for received messages, 2 for no message found at the link and 3 for
+//! any other failure, which is replayed with its description only. Sent messages are only valid
+//! outcomes of sending and received messages of receiving. Refreshed messages are recorded as
+//! received messages.
+
+use super::*;
+use crate::message::HasLink;
+use core::{
+    fmt,
+    marker::PhantomData,
+};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::Write,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use iota_streams_core::{
+    async_trait,
+    err,
+    prelude::{
+        Box,
+        String,
+        ToString,
+    },
+    wrapped_err,
+    Error,
+    Errors::{
+        FileTransportFailure,
+        MalformedTrace,
+        MessageLinkNotFoundInTrace,
+        ReplayDiverged,
+        ReplayedTransportFailure,
+        TraceVersionMismatch,
+    },
+    WrappedError,
+};
+
+/// Magic bytes starting a trace file.
+const TRACE_MAGIC: &[u8] = b"STRMTRC";
+/// Version of the trace format.
+const TRACE_VERSION: u8 = 0;
+
+const CALL_SEND: u8 = 0;
+const CALL_RECV_MESSAGES: u8 = 1;
+const CALL_RECV_MESSAGE: u8 = 2;
+
+const OUTCOME_SENT: u8 = 0;
+const OUTCOME_MESSAGES: u8 = 1;
+const OUTCOME_NOT_FOUND: u8 = 2;
+const OUTCOME_FAILED: u8 = 3;
+
+fn call_name(call: u8) -> &'static str {
+    match call {
+        CALL_SEND => "send_message",
+        CALL_RECV_MESSAGES => "recv_messages",
+        _ => "recv_message",
+    }
+}
+
+fn io_err(path: &Path, e: std::io::Error) -> Error {
+    wrapped_err!(FileTransportFailure(path.display().to_string()), WrappedError(e))
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Transport recording the calls to the wrapped transport in a trace file.
+pub struct RecordingTransport<T, Link, Msg> {
+    inner: T,
+    path: PathBuf,
+    file: fs::File,
+    _phantom: PhantomData<(Link, Msg)>,
+}
+
+impl<T, Link, Msg> RecordingTransport<T, Link, Msg> {
+    /// Wrap `inner` recording its calls in the trace file at `path`, which is overwritten.
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = fs::File::create(&path).map_err(|e| io_err(&path, e))?;
+        let mut header = TRACE_MAGIC.to_vec();
+        header.push(TRACE_VERSION);
+        file.write_all(&header).map_err(|e| io_err(&path, e))?;
+        Ok(Self {
+            inner,
+            path,
+            file,
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Append a call to the trace, the trace is flushed so that it survives a crash of the session.
+    fn record(&mut self, call: u8, link: &[u8], outcome: &[u8]) -> Result<()> {
+        let mut buf = vec![call];
+        put_bytes(&mut buf, link);
+        buf.extend_from_slice(outcome);
+        let path = &self.path;
+        self.file.write_all(&buf).map_err(|e| io_err(path, e))?;
+        self.file.flush().map_err(|e| io_err(path, e))
+    }
+}
+
+impl<T, Link: HasLink, Msg: FileMessage<Link>> RecordingTransport<T, Link, Msg> {
+    /// Record the messages received at `link`.
+    fn record_messages(&mut self, link: &Link, result: &Result<Vec<Msg>>) -> Result<()> {
+        let outcome = match result {
+            Ok(msgs) => encode_messages(&msgs.iter().map(|msg| msg.to_file_bytes()).collect::<Vec<_>>()),
+            Err(e) => encode_failure(e),
+        };
+        self.record(CALL_RECV_MESSAGES, &link.to_bytes(), &outcome)
+    }
+}
+
+fn encode_failure(error: &Error) -> Vec<u8> {
+    if is_message_not_found(error) {
+        vec![OUTCOME_NOT_FOUND]
+    } else {
+        let mut buf = vec![OUTCOME_FAILED];
+        put_bytes(&mut buf, error.to_string().as_bytes());
+        buf
+    }
+}
+
+fn encode_messages(msgs: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = vec![OUTCOME_MESSAGES];
+    buf.extend_from_slice(&(msgs.len() as u32).to_be_bytes());
+    for msg in msgs {
+        put_bytes(&mut buf, msg);
+    }
+    buf
+}
+
+impl<T: TransportOptions, Link, Msg> TransportOptions for RecordingTransport<T, Link, Msg> {
+    type SendOptions = <T as TransportOptions>::SendOptions;
+    fn get_send_options(&self) -> Self::SendOptions {
+        self.inner.get_send_options()
+    }
+    fn set_send_options(&mut self, opt: Self::SendOptions) {
+        self.inner.set_send_options(opt)
+    }
+
+    type RecvOptions = <T as TransportOptions>::RecvOptions;
+    fn get_recv_options(&self) -> Self::RecvOptions {
+        self.inner.get_recv_options()
+    }
+    fn set_recv_options(&mut self, opt: Self::RecvOptions) {
+        self.inner.set_recv_options(opt)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: TransportDetails<Link>, Link, Msg> TransportDetails<Link> for RecordingTransport<T, Link, Msg> {
+    type Details = <T as TransportDetails<Link>>::Details;
+    async fn get_link_details(&mut self, link: &Link) -> Result<Self::Details> {
+        self.inner.get_link_details(link).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<T, Link, Msg> Transport<Link, Msg> for RecordingTransport<T, Link, Msg>
+where
+    T: Transport<Link, Msg>,
+    Link: HasLink,
+    Msg: FileMessage<Link>,
+{
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        let result = self.inner.send_message(msg).await;
+        let outcome = match &result {
+            Ok(()) => vec![OUTCOME_SENT],
+            Err(e) => encode_failure(e),
+        };
+        self.record(CALL_SEND, &msg.link().to_bytes(), &outcome)?;
+        result
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        let result = self.inner.recv_messages(link).await;
+        self.record_messages(link, &result)?;
+        result
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        let result = self.inner.recv_message(link).await;
+        let outcome = match &result {
+            Ok(msg) => encode_messages(&[msg.to_file_bytes()]),
+            Err(e) => encode_failure(e),
+        };
+        self.record(CALL_RECV_MESSAGE, &link.to_bytes(), &outcome)?;
+        result
+    }
+
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        let result = self.inner.refresh_messages(link).await;
+        self.record_messages(link, &result)?;
+        result
+    }
+}
+
+/// Recorded result of a call.
+enum Outcome {
+    Sent,
+    Messages(Vec<Vec<u8>>),
+    NotFound,
+    Failed(String),
+}
+
+/// Recorded call.
+struct Call {
+    kind: u8,
+    link: Vec<u8>,
+    outcome: Outcome,
+}
+
+/// Reader of a trace, failing with the position of truncated or malformed content.
+struct TraceReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TraceReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        try_or!(n <= self.bytes.len() - self.pos, MalformedTrace(self.pos))?;
+        let taken = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut n = [0_u8; 4];
+        n.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(n))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let n = self.u32()? as usize;
+        self.take(n)
+    }
+
+    fn call(&mut self) -> Result<Call> {
+        let pos = self.pos;
+        let kind = self.u8()?;
+        try_or!(kind <= CALL_RECV_MESSAGE, MalformedTrace(pos))?;
+        let link = self.bytes()?.to_vec();
+        let pos = self.pos;
+        let outcome = match self.u8()? {
+            // Only sending records a sent message and only receiving records received messages
+            OUTCOME_SENT if kind == CALL_SEND => Outcome::Sent,
+            OUTCOME_MESSAGES if kind != CALL_SEND => {
+                let n = self.u32()?;
+                // A received message is recorded alone
+                try_or!(kind != CALL_RECV_MESSAGE || n == 1, MalformedTrace(pos))?;
+                let mut msgs = Vec::new();
+                for _ in 0..n {
+                    msgs.push(self.bytes()?.to_vec());
+                }
+                Outcome::Messages(msgs)
+            }
+            OUTCOME_NOT_FOUND => Outcome::NotFound,
+            OUTCOME_FAILED => Outcome::Failed(String::from_utf8_lossy(self.bytes()?).to_string()),
+            _ => return err!(MalformedTrace(pos)),
+        };
+        Ok(Call { kind, link, outcome })
+    }
+}
+
+/// Transport serving the results recorded by a [`RecordingTransport`].
+///
+/// Calls must be made in the recorded order with the recorded links, sent messages themselves
+/// are not compared as they may contain fresh randomness.
+pub struct ReplayTransport<Link, Msg> {
+    calls: VecDeque<Call>,
+    _phantom: PhantomData<(Link, Msg)>,
+}
+
+impl<Link, Msg> ReplayTransport<Link, Msg> {
+    /// Load the trace file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| io_err(path, e))?;
+        Self::from_trace(&bytes)
+    }
+
+    /// Load a trace from its bytes.
+    pub fn from_trace(bytes: &[u8]) -> Result<Self> {
+        let mut reader = TraceReader { bytes, pos: 0 };
+        try_or!(reader.take(TRACE_MAGIC.len())? == TRACE_MAGIC, MalformedTrace(0))?;
+        let version = reader.u8()?;
+        try_or!(version == TRACE_VERSION, TraceVersionMismatch(TRACE_VERSION, version))?;
+        let mut calls = VecDeque::new();
+        while reader.pos < bytes.len() {
+            calls.push_back(reader.call()?);
+        }
+        Ok(Self {
+            calls,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Number of recorded calls not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.calls.len()
+    }
+}
+
+impl<Link: HasLink + fmt::Display, Msg: FileMessage<Link>> ReplayTransport<Link, Msg> {
+    /// Take the next recorded call, which must be a call of `kind` at `link`.
+    fn replay(&mut self, kind: u8, link: &Link) -> Result<Vec<Msg>> {
+        let call = match self.calls.pop_front() {
+            Some(call) if call.kind == kind && call.link == link.to_bytes() => call,
+            Some(call) => {
+                return err!(ReplayDiverged(format!(
+                    "{} at {}, recorded {}",
+                    call_name(kind),
+                    link,
+                    call_name(call.kind)
+                )))
+            }
+            None => return err!(ReplayDiverged(format!("{} at {}, trace ended", call_name(kind), link))),
+        };
+        match call.outcome {
+            Outcome::Sent => Ok(Vec::new()),
+            Outcome::Messages(msgs) => msgs.iter().map(|msg| Msg::from_file_bytes(link, msg)).collect(),
+            Outcome::NotFound => err!(MessageLinkNotFoundInTrace(link.to_string())),
+            Outcome::Failed(e) => err!(ReplayedTransportFailure(e)),
+        }
+    }
+}
+
+impl<Link, Msg> TransportOptions for ReplayTransport<Link, Msg> {
+    type SendOptions = ();
+    fn get_send_options(&self) {}
+    fn set_send_options(&mut self, _opt: ()) {}
+
+    type RecvOptions = ();
+    fn get_recv_options(&self) {}
+    fn set_recv_options(&mut self, _opt: ()) {}
+}
+
+#[async_trait(?Send)]
+impl<Link, Msg> TransportDetails<Link> for ReplayTransport<Link, Msg> {
+    type Details = ();
+    async fn get_link_details(&mut self, _link: &Link) -> Result<Self::Details> {
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl<Link, Msg> Transport<Link, Msg> for ReplayTransport<Link, Msg>
+where
+    Link: HasLink + fmt::Display,
+    Msg: FileMessage<Link>,
+{
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        self.replay(CALL_SEND, msg.link()).map(|_| ())
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.replay(CALL_RECV_MESSAGES, link)
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        // Traces are checked to hold exactly one message for received messages
+        Ok(self.replay(CALL_RECV_MESSAGE, link)?.remove(0))
+    }
+
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        self.replay(CALL_RECV_MESSAGES, link)
+    }
+}
@@ -0,0 +1,213 @@
+//! Retrying transport wrapper.
+//!
+//! Calls to the wrapped transport that fail with a retryable error are attempted again after a
+//! backoff doubling with every attempt. Each attempt may be bounded by a timeout, an attempt that
+//! times out fails with [`TransportTimedOut`], which is retryable by default.
+
+use super::*;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{
+        Context,
+        Poll,
+    },
+    time::Duration,
+};
+use futures_timer::Delay;
+
+use iota_streams_core::{
+    async_trait,
+    err,
+    prelude::Box,
+    Error,
+    Errors::TransportTimedOut,
+};
+
+/// Retry policy of the calls to a transport.
+#[derive(Clone, Copy)]
+pub struct RetryOptions {
+    /// Number of attempts after the first one.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every subsequent retry.
+    pub backoff: Duration,
+    /// Upper bound of the delay between retries.
+    pub max_backoff: Duration,
+    /// Time after which an attempt fails, attempts are not bounded by default.
+    pub timeout: Option<Duration>,
+    /// Whether an error is worth retrying, see [`is_transient`](super::is_transient).
+    pub retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            timeout: None,
+            retryable: is_transient,
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Attempt calls once without timeout.
+    pub fn no_retry() -> Self {
+        Self {
+            retries: 0,
+            ..Self::default()
+        }
+    }
+}
+
+/// Options of a [`RetryTransport`] together with the options of the wrapped transport.
+#[derive(Clone)]
+pub struct RetryTransportOptions<O> {
+    pub retry: RetryOptions,
+    pub transport: O,
+}
+
+/// Future failing with [`TransportTimedOut`] unless the call completes within `timeout`.
+struct Timeout<Fut> {
+    call: Pin<Box<Fut>>,
+    delay: Delay,
+    timeout: Duration,
+}
+
+impl<T, Fut: Future<Output = Result<T>>> Future for Timeout<Fut> {
+    type Output = Result<T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T>> {
+        if let Poll::Ready(result) = self.call.as_mut().poll(cx) {
+            return Poll::Ready(result);
+        }
+        let timeout = self.timeout;
+        match Pin::new(&mut self.delay).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(err!(TransportTimedOut(timeout.as_millis()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+async fn with_timeout<T, Fut: Future<Output = Result<T>>>(timeout: Option<Duration>, call: Fut) -> Result<T> {
+    match timeout {
+        Some(timeout) => {
+            Timeout {
+                call: Box::pin(call),
+                delay: Delay::new(timeout),
+                timeout,
+            }
+            .await
+        }
+        None => call.await,
+    }
+}
+
+/// Evaluate `$call` until it succeeds, fails with an error that is not retryable or the retries of
+/// `$opt` are exhausted, in which case the last error is returned.
+macro_rules! retry {
+    ($opt:expr, $call:expr) => {{
+        let opt: RetryOptions = $opt;
+        let mut backoff = opt.backoff;
+        let mut retries = 0;
+        loop {
+            match with_timeout(opt.timeout, $call).await {
+                Err(e) if retries < opt.retries && (opt.retryable)(&e) => {
+                    retries += 1;
+                    Delay::new(backoff).await;
+                    backoff = core::cmp::min(backoff * 2, opt.max_backoff);
+                }
+                result => break result,
+            }
+        }
+    }};
+}
+
+/// Transport retrying the failed calls to the wrapped transport.
+///
+/// Sending is retried with the send options and receiving, including link details, with the
+/// receive options. Note that a sent message may be published by an attempt that nonetheless
+/// failed, eg. timed out, retries may thus publish the message more than once.
+#[derive(Clone)]
+pub struct RetryTransport<T> {
+    inner: T,
+    send_opt: RetryOptions,
+    recv_opt: RetryOptions,
+}
+
+impl<T> RetryTransport<T> {
+    /// Wrap `inner` with the default retry policy.
+    pub fn new(inner: T) -> Self {
+        Self::with_options(inner, RetryOptions::default(), RetryOptions::default())
+    }
+
+    /// Wrap `inner` with the retry policies of sending and receiving.
+    pub fn with_options(inner: T, send_opt: RetryOptions, recv_opt: RetryOptions) -> Self {
+        Self {
+            inner,
+            send_opt,
+            recv_opt,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: TransportOptions> TransportOptions for RetryTransport<T> {
+    type SendOptions = RetryTransportOptions<<T as TransportOptions>::SendOptions>;
+    fn get_send_options(&self) -> Self::SendOptions {
+        RetryTransportOptions {
+            retry: self.send_opt,
+            transport: self.inner.get_send_options(),
+        }
+    }
+    fn set_send_options(&mut self, opt: Self::SendOptions) {
+        self.send_opt = opt.retry;
+        self.inner.set_send_options(opt.transport)
+    }
+
+    type RecvOptions = RetryTransportOptions<<T as TransportOptions>::RecvOptions>;
+    fn get_recv_options(&self) -> Self::RecvOptions {
+        RetryTransportOptions {
+            retry: self.recv_opt,
+            transport: self.inner.get_recv_options(),
+        }
+    }
+    fn set_recv_options(&mut self, opt: Self::RecvOptions) {
+        self.recv_opt = opt.retry;
+        self.inner.set_recv_options(opt.transport)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: TransportDetails<Link>, Link> TransportDetails<Link> for RetryTransport<T> {
+    type Details = <T as TransportDetails<Link>>::Details;
+    async fn get_link_details(&mut self, link: &Link) -> Result<Self::Details> {
+        retry!(self.recv_opt, self.inner.get_link_details(link))
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: Transport<Link, Msg>, Link, Msg> Transport<Link, Msg> for RetryTransport<T> {
+    async fn send_message(&mut self, msg: &Msg) -> Result<()> {
+        retry!(self.send_opt, self.inner.send_message(msg))
+    }
+
+    async fn recv_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        retry!(self.recv_opt, self.inner.recv_messages(link))
+    }
+
+    async fn recv_message(&mut self, link: &Link) -> Result<Msg> {
+        retry!(self.recv_opt, self.inner.recv_message(link))
+    }
+
+    async fn refresh_messages(&mut self, link: &Link) -> Result<Vec<Msg>> {
+        retry!(self.recv_opt, self.inner.refresh_messages(link))
+    }
+}
@@ -14,9 +14,11 @@ use iota_client::{
 use iota_client::bee_message::{
     payload::Payload,
     Message,
+    MessageId,
 };
 
 use iota_streams_core::{
+    anyhow,
     err,
     prelude::Vec,
     try_or,
@@ -68,8 +70,24 @@ impl fmt::Display for Details {
     }
 }
 
+/// Whether the client failed to reach a node or the node failed to serve the request, as opposed
+/// to rejecting it.
+fn is_unavailable(error: &iota_client::Error) -> bool {
+    match error {
+        iota_client::Error::ReqwestError(_) | iota_client::Error::SyncedNodePoolEmpty => true,
+        iota_client::Error::ResponseError(status, _) => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
 fn handle_client_result<T>(result: iota_client::Result<T>) -> Result<T> {
-    result.map_err(|err| wrapped_err!(ClientOperationFailure, WrappedError(err)))
+    result.map_err(|err| {
+        if is_unavailable(&err) {
+            anyhow!(ClientUnavailable(err.to_string()))
+        } else {
+            wrapped_err!(ClientOperationFailure, WrappedError(err))
+        }
+    })
 }
 
 /// Reconstruct Streams Message from bundle.
@@ -84,7 +102,7 @@ pub fn msg_from_tangle_message<F>(message: &Message, link: &TangleAddress) -> Re
         }
 
         let binary = BinaryMessage::new(*link, TangleAddress::default(), bytes.into());
-        // TODO get timestamp
+        // Timestamp is not part of the message, it is set by `async_recv_messages`
         let timestamp: u64 = 0;
 
         Ok(TangleMessage { binary, timestamp })
@@ -93,16 +111,31 @@ pub fn msg_from_tangle_message<F>(message: &Message, link: &TangleAddress) -> Re
     }
 }
 
-async fn get_messages(client: &iota_client::Client, link: &TangleAddress) -> Result<Vec<Message>> {
+/// Timestamp of the milestone referencing the message in milliseconds, zero if the message is not
+/// referenced yet.
+async fn get_message_timestamp(client: &iota_client::Client, msg_id: &MessageId) -> u64 {
+    match client.get_message().metadata(msg_id).await {
+        Ok(metadata) => match metadata.referenced_by_milestone_index {
+            Some(ms_index) => client
+                .get_milestone(ms_index)
+                .await
+                .map_or(0, |milestone| milestone.timestamp * 1000),
+            None => 0,
+        },
+        Err(_) => 0,
+    }
+}
+
+async fn get_messages(client: &iota_client::Client, link: &TangleAddress) -> Result<Vec<(Message, u64)>> {
     let hash = link.to_msg_index();
     let msg_ids = handle_client_result(client.get_message().index(hash).await)?;
     try_or!(!msg_ids.is_empty(), IndexNotFound)?;
 
-    let msgs = join_all(
-        msg_ids
-            .iter()
-            .map(|msg| async move { handle_client_result(client.get_message().data(msg).await) }),
-    )
+    let msgs = join_all(msg_ids.iter().map(|msg| async move {
+        let data = handle_client_result(client.get_message().data(msg).await);
+        let timestamp = get_message_timestamp(client, msg).await;
+        data.map(|data| (data, timestamp))
+    }))
     .await
     .into_iter()
     .filter_map(|msg| msg.ok())
@@ -116,16 +149,21 @@ pub async fn async_send_message_with_options<F>(client: &iota_client::Client, ms
     let hash = msg.binary.link.to_msg_index();
 
     // TODO: Get rid of copy caused by to_owned
-    client
-        .message()
-        .with_index(hash)
-        .with_data(msg.binary.body.bytes.clone())
-        .finish()
-        .await?;
+    handle_client_result(
+        client
+            .message()
+            .with_index(hash)
+            .with_data(msg.binary.body.bytes.clone())
+            .finish()
+            .await,
+    )?;
     Ok(())
 }
 
 /// Retrieve a message from the tangle using a node client
+///
+/// No message is returned when the index of the link is not found, failures to reach the node are
+/// returned as errors so that they can be told apart from the absence of messages.
 pub async fn async_recv_messages<F>(
     client: &iota_client::Client,
     link: &TangleAddress,
@@ -133,9 +171,14 @@ pub async fn async_recv_messages<F>(
     match get_messages(client, link).await {
         Ok(txs) => Ok(txs
             .iter()
-            .filter_map(|b| msg_from_tangle_message(b, link).ok()) // Ignore errors
+            .filter_map(|(b, timestamp)| {
+                msg_from_tangle_message(b, link)
+                    .ok() // Ignore messages with a foreign payload
+                    .map(|msg| TangleMessage::with_timestamp(msg.binary, *timestamp))
+            })
             .collect()),
-        Err(_) => Ok(Vec::new()), // Just ignore the error?
+        Err(e) if matches!(e.downcast_ref::<iota_streams_core::Errors>(), Some(IndexNotFound)) => Ok(Vec::new()),
+        Err(e) => Err(e),
     }
 }
 
@@ -84,30 +84,28 @@ impl<F> LinkedMessage<TangleAddress> for TangleMessage<F> {
     }
 }
 
+/// Current time in milliseconds since the Unix epoch.
 // TODO: Use better feature to detect `chrono::Utc::new()`.
 #[cfg(feature = "std")]
-impl<F> TangleMessage<F> {
-    /// Create TangleMessage from BinaryMessage and add the current timestamp.
-    pub fn new(msg: BinaryMessage<F, TangleAddress>) -> Self {
-        Self {
-            binary: msg,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-        }
-    }
+pub fn timestamp_now() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
 }
 
+/// Current time is not available without `std`, zero is returned instead.
 #[cfg(not(feature = "std"))]
+pub fn timestamp_now() -> u64 {
+    0_u64
+}
+
 impl<F> TangleMessage<F> {
     /// Create TangleMessage from BinaryMessage and add the current timestamp.
     pub fn new(msg: BinaryMessage<F, TangleAddress>) -> Self {
         Self {
             binary: msg,
-            timestamp: 0_u64,
+            timestamp: timestamp_now(),
         }
     }
-}
 
-impl<F> TangleMessage<F> {
     /// Create TangleMessage from BinaryMessage and an explicit timestamp.
     pub fn with_timestamp(msg: BinaryMessage<F, TangleAddress>, timestamp: u64) -> Self {
         Self { binary: msg, timestamp }
@@ -140,8 +138,8 @@ impl<F> TangleMessage<F> {
 /// assert_eq!(
 ///     address.to_msg_index().as_ref(),
 ///     &[
-///         44, 181, 155, 1, 109, 141, 169, 177, 209, 70, 226, 18, 190, 121, 40, 44, 90, 108, 159, 109, 241, 37, 30, 0,
-///         185, 80, 245, 59, 235, 75, 128, 97
+///         44, 181, 155, 1, 109, 141, 169, 177, 209, 70, 226, 18, 190, 121, 40, 44, 90, 108, 159,
+///         109, 241, 37, 30, 0, 185, 80, 245, 59, 235, 75, 128, 97
 ///     ],
 /// );
 /// assert_eq!(
@@ -355,6 +353,14 @@ impl<F: PRP> LinkGenerator<TangleAddress> for DefaultTangleLinkGenerator<F> {
         self.addr = announcement_link;
     }
 
+    /// Used by Subscriber to check that an announcement link was generated by `gen` from the
+    /// announced public key
+    fn is_announcement_link(&self, link: &TangleAddress, pk: &ed25519::PublicKey) -> bool {
+        let mut link_gen = Self::default();
+        link_gen.gen(pk, link.appinst.channel_idx());
+        link_gen.addr == *link
+    }
+
     /// Used by users to pseudo-randomly generate a new uniform message link from a cursor
     fn uniform_link_from(&self, cursor: Cursor<&MsgId>) -> TangleAddress {
         TangleAddress {
@@ -392,6 +398,13 @@ impl AppInst {
         }
     }
 
+    /// Get the channel index the AppInst was derived with
+    pub fn channel_idx(&self) -> u64 {
+        let mut idx = [0_u8; 8];
+        idx.copy_from_slice(&self.id.as_slice()[32..]);
+        u64::from_be_bytes(idx)
+    }
+
     /// Get the hexadecimal representation of the AppInst
     pub fn to_hex_string(&self) -> String {
         format!("{:x}", self.id)
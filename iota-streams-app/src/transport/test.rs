@@ -0,0 +1,230 @@
+use super::*;
+use crate::{
+    message::{
+        BinaryBody,
+        BinaryMessage,
+    },
+    transport::tangle::{
+        AppInst,
+        MsgId,
+        TangleAddress,
+        TangleMessage,
+    },
+};
+use core::time::Duration;
+use std::{
+    env,
+    fs,
+};
+
+use iota_streams_core::{
+    ensure,
+    Errors::{
+        InjectedFault,
+        MalformedTrace,
+        ReplayDiverged,
+    },
+};
+use iota_streams_core_keccak::sponge::prp::keccak::KeccakF1600;
+
+type Message = TangleMessage<KeccakF1600>;
+type Bucket = BucketTransport<TangleAddress, Message>;
+
+/// Address of the `n`th message of the test channel.
+fn address(n: u8) -> TangleAddress {
+    TangleAddress::new(AppInst::from(&[1_u8; 40][..]), MsgId::from(&[n; 12][..]))
+}
+
+/// The `n`th message of the test channel.
+fn message(n: u8) -> Message {
+    let body = format!("MESSAGE{}", n).into_bytes();
+    TangleMessage::with_timestamp(BinaryMessage::new(address(n), address(0), BinaryBody::from(body)), 0)
+}
+
+async fn send<T: Transport<TangleAddress, Message>>(transport: &mut T, n: u8) -> Result<()> {
+    transport.send_message(&message(n)).await
+}
+
+async fn recv<T: Transport<TangleAddress, Message>>(transport: &mut T, n: u8) -> Result<Message> {
+    transport.recv_message(&address(n)).await
+}
+
+/// Messages withheld by a faulty transport become visible after a number of receptions, and faults
+/// drawn from the same seed hit the same messages.
+pub async fn faulty_transport_example() -> Result<()> {
+    let faults = Faults {
+        defer: 1.0,
+        defer_polls: 1,
+        ..Faults::default()
+    };
+    let mut transport = FaultyTransport::<KeccakF1600, _, Message>::new(Bucket::new(), "FAULTY9SEED", faults);
+    send(&mut transport, 1).await?;
+    let e = recv(&mut transport, 1).await.unwrap_err();
+    ensure!(is_message_not_found(&e), "message should be withheld");
+    recv(&mut transport, 1).await?;
+    ensure!(transport.stats().deferred == 1, "message should be deferred");
+
+    let (stats, received) = lossy_sync("FAULTY9SEED").await?;
+    ensure!(stats != FaultStats::default(), "faults should be injected");
+    ensure!(
+        received.iter().sum::<usize>() == 10 - stats.dropped + stats.duplicated,
+        "messages that are not dropped should be received"
+    );
+    ensure!(
+        (stats, received) == lossy_sync("FAULTY9SEED").await?,
+        "same seed should inject the same faults"
+    );
+    Ok(())
+}
+
+/// Send messages through a lossy transport and receive them once the withheld messages are
+/// released, returning the faults injected and the number of messages received at each link.
+async fn lossy_sync(seed: &str) -> Result<(FaultStats, Vec<usize>)> {
+    let faults = Faults {
+        drop: 0.1,
+        duplicate: 0.3,
+        corrupt: 0.2,
+        defer: 0.3,
+        defer_polls: 2,
+        fail: 0.0,
+    };
+    let mut transport = FaultyTransport::<KeccakF1600, _, Message>::new(Bucket::new(), seed, faults);
+    for n in 1..=10 {
+        send(&mut transport, n).await?;
+    }
+    transport.release_deferred();
+    let mut received = Vec::new();
+    for n in 1..=10 {
+        match transport.recv_messages(&address(n)).await {
+            Ok(msgs) => received.push(msgs.len()),
+            Err(e) if is_message_not_found(&e) => received.push(0),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((transport.stats(), received))
+}
+
+#[test]
+fn run_faulty_transport_scenario() {
+    smol::block_on(faulty_transport_example()).unwrap();
+}
+
+/// A recorded session replays offline, and a session making other calls diverges from the trace.
+pub async fn replay_example() -> Result<()> {
+    let trace = env::temp_dir().join(format!("iota-streams-replay-test-{}.trace", std::process::id()));
+    let mut transport = RecordingTransport::create(Bucket::new(), &trace)?;
+    let recorded = replayed_session(&mut transport, 2).await?;
+
+    let mut transport = ReplayTransport::open(&trace)?;
+    let replayed = replayed_session(&mut transport, 2).await?;
+    ensure!(replayed == recorded, "replayed messages should match");
+    ensure!(transport.remaining() == 0, "the whole trace should be replayed");
+
+    let mut transport = ReplayTransport::open(&trace)?;
+    let e = replayed_session(&mut transport, 1).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(ReplayDiverged(..))),
+        "a session receiving another message should diverge from the trace"
+    );
+    let _ = fs::remove_file(&trace);
+
+    // A received message recorded as sent, the link being empty
+    let e = ReplayTransport::<TangleAddress, Message>::from_trace(b"STRMTRC\x00\x02\x00\x00\x00\x00\x00").err();
+    ensure!(
+        matches!(
+            e.as_ref().and_then(|e| e.downcast_ref::<Errors>()),
+            Some(MalformedTrace(13))
+        ),
+        "a call should not be replayed with the outcome of another kind of call"
+    );
+    Ok(())
+}
+
+/// Send two messages, look for a missing one and receive the `n`th one, returning its body.
+async fn replayed_session<T: Transport<TangleAddress, Message>>(transport: &mut T, n: u8) -> Result<Vec<u8>> {
+    send(transport, 1).await?;
+    send(transport, 2).await?;
+    let e = recv(transport, 3).await.unwrap_err();
+    ensure!(is_message_not_found(&e), "missing message should not be found");
+    Ok(recv(transport, n).await?.binary.body.bytes)
+}
+
+#[test]
+fn run_replay_scenario() {
+    smol::block_on(replay_example()).unwrap();
+}
+
+/// Transient failures are retried until the calls succeed, and surface without retries.
+pub async fn retry_example() -> Result<()> {
+    let flaky = new_shared_transport(FaultyTransport::<KeccakF1600, _, Message>::new(
+        Bucket::new(),
+        "RETRY9SEED",
+        Faults {
+            fail: 0.3,
+            ..Faults::default()
+        },
+    ));
+    let retry = RetryOptions {
+        retries: 20,
+        backoff: Duration::from_millis(1),
+        ..RetryOptions::default()
+    };
+    let mut transport = RetryTransport::with_options(flaky.clone(), retry, retry);
+    for n in 1..=10 {
+        send(&mut transport, n).await?;
+    }
+    for n in 1..=10 {
+        let msg = recv(&mut transport, n).await?;
+        ensure!(
+            msg.binary.body == message(n).binary.body,
+            "message should survive the failures"
+        );
+    }
+    ensure!(flaky.borrow().stats().failed > 0, "calls should fail");
+
+    flaky.borrow_mut().set_faults(Faults {
+        fail: 1.0,
+        ..Faults::default()
+    });
+    let mut transport = RetryTransport::with_options(flaky, RetryOptions::no_retry(), RetryOptions::no_retry());
+    let e = send(&mut transport, 11).await.unwrap_err();
+    ensure!(
+        matches!(e.downcast_ref::<Errors>(), Some(InjectedFault)),
+        "failure should surface without retries"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_retry_scenario() {
+    smol::block_on(retry_example()).unwrap();
+}
+
+/// Received messages are served again from memory, and from disk by another caching transport.
+pub async fn caching_example() -> Result<()> {
+    let dir = env::temp_dir().join(format!("iota-streams-caching-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut transport = CachingTransport::with_disk(Bucket::new(), 16, &dir);
+    send(&mut transport, 1).await?;
+    recv(&mut transport, 1).await?;
+    recv(&mut transport, 1).await?;
+    ensure!(
+        transport.stats().hits == 1 && transport.stats().misses == 1,
+        "message should be served from memory"
+    );
+
+    // The wrapped transport of another caching transport does not know the message
+    let mut transport = CachingTransport::with_disk(Bucket::new(), 16, &dir);
+    let msg = recv(&mut transport, 1).await?;
+    ensure!(
+        transport.stats().disk_hits == 1 && msg.binary.body == message(1).binary.body,
+        "message should be served from disk"
+    );
+    let _ = fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+#[test]
+fn run_caching_scenario() {
+    smol::block_on(caching_example()).unwrap();
+}
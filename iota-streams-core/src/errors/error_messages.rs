@@ -76,8 +76,28 @@ pub enum Errors {
     MessageLinkNotFoundInTangle(String),
     /// Message at link {0} not found in Bucket transport
     MessageLinkNotFoundInBucket(String),
+    /// Message at link {0} not found in File transport
+    MessageLinkNotFoundInFile(String),
+    /// File transport failed to access {0}
+    FileTransportFailure(String),
+    /// Stored message file is truncated (expected at least: {0}, found: {1})
+    MalformedFileMessage(usize, usize),
+    /// Message at link {0} not found in replayed trace
+    MessageLinkNotFoundInTrace(String),
+    /// Trace is truncated or malformed at byte {0}
+    MalformedTrace(usize),
+    /// Trace version mismatch (expected: {0}, found: {1})
+    TraceVersionMismatch(u8, u8),
+    /// Replayed session diverged from the trace: {0}
+    ReplayDiverged(String),
+    /// Recorded transport failure: {0}
+    ReplayedTransportFailure(String),
     /// Transport object is already borrowed
     TransportNotAvailable,
+    /// Transport call timed out after {0} ms
+    TransportTimedOut(u128),
+    /// Transport call failed by an injected fault
+    InjectedFault,
 
     //////////
     // Iota Transport
@@ -114,6 +134,8 @@ pub enum Errors {
     MessageBuildFailure,
     /// Iota Client failed to perform operation.
     ClientOperationFailure,
+    /// Iota Client failed to reach a node: {0}
+    ClientUnavailable(String),
 
     //////////
     // Messages
@@ -134,6 +156,20 @@ pub enum Errors {
     MessageCreationFailure,
     /// Identifier could not be generated with given bytes. Must be an ed25519 Public Key or a PskId
     IdentifierGenerationFailure,
+    /// Message does not fit the uniform payload length (length: {0}, message size: {1})
+    UniformPayloadLengthExceeded(usize, usize),
+    /// Message padding is malformed, filler must be zero
+    BadPadding,
+    /// Payload encoding '{0}' is not registered
+    UnknownPayloadEncoding(String),
+    /// Payload encoding id {0} is not registered
+    UnsupportedPayloadEncoding(u8),
+    /// A payload codec with id {0} or name '{1}' is already registered
+    PayloadCodecDuplication(u8, String),
+    /// Failed to encode payload as {0}
+    PayloadEncodingFailure(String),
+    /// Failed to decode {0} payload
+    PayloadDecodingFailure(String),
 
     //////////
     // Users
@@ -164,6 +200,40 @@ pub enum Errors {
     ChannelNotSingleDepth,
     /// Message '{0}' does not have a previous message
     NoPreviousMessage(String),
+    /// No pending subscription from subscriber {0}
+    SubscriptionNotPending(String),
+    /// Unsubscribe message of subscriber {0} is not linked to its subscribe message
+    UnsubscribeLinkMismatch(String),
+    /// Recipient group {0} not found
+    GroupNotFound(String),
+    /// None of the members of recipient group {0} are known
+    GroupMembersNotFound(String),
+    /// {0} is already a member of the key tree
+    TreeMemberAlreadyExists(String),
+    /// {0} is not a member of the key tree
+    TreeMemberNotFound(String),
+    /// No key of recipient {0} in the key store
+    RecipientKeyNotFound(String),
+    /// Announcement link of channel {0} is not derived from the announced author public key {1}
+    AnnouncementLinkMismatch(String, String),
+    /// Announced author public key does not match the pinned one (expected: {0}, found: {1})
+    AuthorSigPkMismatch(String, String),
+    /// Only the Author can rotate the channel signing key
+    KeyRotationNotAuthor,
+    /// Key rotation is signed by {0} instead of the current author public key {1}
+    KeyRotationSignerMismatch(String, String),
+    /// Key pair introduced by the key rotation to {0} was not provided for recovering the channel
+    KeyRotationKeyNotFound(String),
+    /// Publisher {0} is not certified by the Author to send this message
+    PublisherNotCertified(String),
+    /// Only the Author can certify publishers
+    PublisherCertificateNotAuthor,
+    /// Tagged packets do not authenticate their publisher and are not accepted in channels requiring certificates
+    TaggedPacketNotCertifiable,
+    /// Channel {0} is already managed
+    ChannelAlreadyManaged(u64),
+    /// No managed channel has application instance {0}
+    ChannelNotManaged(String),
 
     //////////
     // User Recovery
@@ -176,6 +246,10 @@ pub enum Errors {
     UserVersionRecoveryFailure(u8, u8),
     /// Recovered flag does not match expected: flag (expected: {0}, found: {1})
     UserFlagRecoveryFailure(u8, u8),
+    /// Announcement {0} is not the announcement derived from the seed of the user
+    AnnouncementRecoveryMismatch(String),
+    /// Subscribe message {0} is not the subscribe message derived from the seed of the user
+    SubscribeRecoveryMismatch(String),
 
     //////////
     // Examples
@@ -22,6 +22,15 @@ impl<F: PRP> Inner<F> {
     pub fn arr_mut(&mut self) -> &mut GenericArray<u8, F::CapacitySize> {
         &mut self.inner
     }
+
+    /// Overwrite the state with zeros before it is dropped.
+    pub fn zeroize(&mut self) {
+        for byte in self.inner.iter_mut() {
+            // Volatile writes are not elided even though the state is not read afterwards
+            unsafe { core::ptr::write_volatile(byte, 0) };
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
 }
 
 impl<F: PRP> PartialEq for Inner<F> {
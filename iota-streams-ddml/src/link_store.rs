@@ -52,7 +52,8 @@ pub trait LinkStore<F, Link> {
     where
         F: PRP;
 
-    /// Remove link and associated info from the store.
+    /// Remove link and associated info from the store. The spongos state should be zeroized, so
+    /// that it does not linger in memory.
     fn erase(&mut self, _link: &Link) {}
 
     fn iter(&self) -> Vec<(&Link, &(Inner<F>, Self::Info))>
@@ -189,8 +190,11 @@ where
         Ok(())
     }
 
-    /// Remove info for the link.
+    /// Remove info for the link, the spongos state is zeroized in place first.
     fn erase(&mut self, link: &Link) {
+        if let Some((inner, _)) = self.map.get_mut(link) {
+            inner.zeroize();
+        }
         self.map.remove(link);
     }
 